@@ -0,0 +1,49 @@
+use std::{
+    path::Path,
+    sync::{Arc, OnceLock},
+};
+
+use libset::{Config, Error, MemoryFilesystem};
+use serde_json::{json, Value};
+
+const THREADS: usize = 16;
+const ROUNDS: usize = 200;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+fn config() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        Config::new_with_filesystem(
+            MemoryFilesystem::default(),
+            Path::new("/settings"),
+            "org.example.Demo",
+            1,
+            Some("threads"),
+        )
+        .unwrap()
+    })
+}
+
+fn main() -> Result<(), Error> {
+    config().set_json("shared", json!({ "accent": "#7a7af9" }))?;
+    let threads: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            std::thread::spawn(move || -> Result<(), Error> {
+                let own = format!("thread-{thread}");
+                for round in 0..ROUNDS {
+                    config().set_json(&own, json!({ "round": round }))?;
+                    let value: Value = config().get_json(&own)?;
+                    assert_eq!(value["round"], round);
+                    let shared: Arc<Value> = config().cached_json("shared")?;
+                    assert_eq!(shared["accent"], "#7a7af9");
+                }
+                Ok(())
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().expect("thread panicked")?;
+    }
+    println!("{} reads and writes completed.", THREADS * ROUNDS * 3);
+    Ok(())
+}
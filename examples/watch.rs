@@ -0,0 +1,8 @@
+use libset::{Config, Error};
+
+fn main() -> Result<(), Error> {
+    let config = Config::new("org.example.Demo", 1, None)?;
+    let _watcher = config.watch_scope(Some("appearance"), |change| println!("{change:?}"))?;
+    std::thread::park();
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use std::{
+    io::{Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use zeroize::Zeroizing;
+
+use crate::{deserialize, utils::FileType, Config, Error};
+
+/// The program used to encrypt and decrypt files.
+///
+/// Going through the reference implementation keeps files readable by `age` itself and lets
+/// identities use any installed plugin, such as hardware tokens.
+const AGE: &str = "age";
+
+impl Config {
+    /// Serializes a value into an age-encrypted json file.
+    ///
+    /// The file is stored as `key.json.age` and can be decrypted with the standard `age`
+    /// command, which must be installed. Unlike the encryption middleware, files are
+    /// encrypted to public keys, so they can be written without access to the secret key.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # use serde_json::json;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let recipient = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+    /// config.set_json_age("backup", json!({ "token": "hunter2" }), &[recipient])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `recipients` - The public keys able to decrypt the file, in any form `age -r` accepts.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn set_json_age<T: Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        recipients: &[&str],
+    ) -> Result<(), Error> {
+        self.check_sealed()?;
+        let path = self.path(&age_key(key), FileType::Plain)?;
+        let failed = |reason: String| Error::Age {
+            key: key.to_string(),
+            path: path.clone(),
+            reason,
+        };
+        if recipients.is_empty() {
+            return Err(failed("at least one recipient is required".to_string()));
+        }
+        let data = Zeroizing::new(self.serialize_value(key, FileType::Json, &path, &value)?);
+        let mut args = vec!["--encrypt"];
+        for recipient in recipients {
+            args.extend(["--recipient", recipient]);
+        }
+        let encrypted = run(&args, data.as_bytes()).map_err(failed)?;
+        if let Some(flusher) = &self.flusher {
+            flusher.cancel(&path);
+        }
+        self.cache.invalidate(&path);
+        self.fs
+            .write(&path, &encrypted, false)
+            .map_err(|err| Error::write(&path, err))?;
+        self.log("wrote file", key, &path);
+        Ok(())
+    }
+
+    /// Gets an age-encrypted json file and deserializes it into a type.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # use std::path::Path;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let identity = Path::new("/home/user/.config/age/key.txt");
+    /// let backup: serde_json::Value = config.get_json_age("backup", identity)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `identity` - The identity file holding a secret key for one of the recipients.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value or an `Error` if an error occurred.
+    pub fn get_json_age<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        identity: &Path,
+    ) -> Result<T, Error> {
+        let path = self.resolve(&age_key(key), FileType::Plain)?;
+        let encrypted = self
+            .fs
+            .read(&path)
+            .map_err(|err| Error::for_key(key, FileType::Json, &path, err))?;
+        let failed = |reason: String| Error::Age {
+            key: key.to_string(),
+            path: path.clone(),
+            reason,
+        };
+        let identity = identity.to_string_lossy();
+        let mut data = run(&["--decrypt", "--identity", &identity], &encrypted).map_err(failed)?;
+        let data = Zeroizing::new(
+            String::from_utf8(std::mem::take(&mut *data))
+                .map_err(|_| failed("decrypted contents are not valid UTF-8".to_string()))?,
+        );
+        let value = deserialize(key, FileType::Json, &path, &data, &self.parse)?;
+        self.log("read file", key, &path);
+        Ok(value)
+    }
+}
+
+/// Returns the key of the plain file holding an encrypted json file.
+fn age_key(key: &str) -> String {
+    format!("{key}.json.age")
+}
+
+/// Runs `age` with the given arguments, passing `input` on stdin.
+///
+/// # Returns
+///
+/// A `Result` containing the output or why `age` failed.
+fn run(args: &[&str], input: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    let mut child = Command::new(AGE)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run {AGE}: {err}"))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut output = Zeroizing::new(Vec::new());
+    // Writing and reading concurrently, large inputs would otherwise fill the pipes.
+    let written = std::thread::scope(|scope| {
+        let writer = scope.spawn(move || stdin.write_all(input));
+        let read = stdout.read_to_end(&mut output);
+        writer.join().expect("writer thread panicked").and(read)
+    });
+    let result = child
+        .wait_with_output()
+        .map_err(|err| format!("failed to run {AGE}: {err}"))?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(stderr.trim().to_string());
+    }
+    written.map_err(|err| format!("failed to run {AGE}: {err}"))?;
+    Ok(output)
+}
@@ -0,0 +1,338 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    entry::is_temporary,
+    manifest::{hash, Manifest},
+    utils::sanitize_name,
+    Config, Error,
+};
+
+/// First line of every archive.
+const HEADER: &str = "libset-archive 1";
+/// Last line of every complete archive.
+const TRAILER: &str = "end";
+/// The largest manifest or file accepted in an archive, in bytes.
+const MAX_SECTION_LENGTH: u64 = 1 << 30;
+
+/// A verified export of a store, read with [`Archive::read`].
+///
+/// Archives record which application and version they were exported from, when, and the
+/// [`Manifest`] of the exported files. Reading an archive checks that every file it lists is
+/// present and matches its hash, so a damaged or incomplete backup is rejected before
+/// anything is restored.
+///
+/// The format is a few text header lines followed by the length-prefixed manifest and file
+/// contents:
+///
+/// ```text
+/// libset-archive 1
+/// app org.example.Demo
+/// version 1
+/// created 1700000000
+/// manifest <length>
+/// <manifest>
+/// blob <sha256> <length>
+/// <contents>
+/// end
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Archive {
+    app: String,
+    version: u64,
+    created: SystemTime,
+    manifest: Manifest,
+    blobs: HashMap<String, Vec<u8>>,
+}
+
+impl Archive {
+    /// Reads and verifies an archive written by [`Config::export_archive`].
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source of the archive.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Archive` or an `Error` if it is malformed, incomplete or a
+    /// file does not match its hash.
+    pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut reader = BufReader::new(reader);
+        if read_line(&mut reader)? != HEADER {
+            return Err(invalid("unsupported header"));
+        }
+        let app = field(&mut reader, "app")?;
+        let version = field(&mut reader, "version")?
+            .parse()
+            .map_err(|_| invalid("invalid version"))?;
+        let created = field(&mut reader, "created")?
+            .parse()
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+            .map_err(|_| invalid("invalid creation time"))?;
+        let length = field(&mut reader, "manifest")?;
+        let manifest = String::from_utf8(read_data(&mut reader, &length)?)
+            .map_err(|_| invalid("manifest is not valid UTF-8"))?
+            .parse::<Manifest>()?;
+
+        let mut blobs = HashMap::new();
+        loop {
+            let line = read_line(&mut reader)?;
+            if line == TRAILER {
+                break;
+            }
+            let Some((digest, length)) = line
+                .strip_prefix("blob ")
+                .and_then(|blob| blob.split_once(' '))
+            else {
+                return Err(invalid(&format!("invalid line {line:?}")));
+            };
+            let data = read_data(&mut reader, length)?;
+            if hash(&data) != digest {
+                return Err(invalid(&format!("blob {digest} is corrupted")));
+            }
+            blobs.insert(digest.to_string(), data);
+        }
+        if let Some((path, _)) = manifest
+            .files()
+            .find(|(_, digest)| !blobs.contains_key(*digest))
+        {
+            return Err(invalid_file(path, "missing from the archive"));
+        }
+
+        Ok(Self {
+            app,
+            version,
+            created,
+            manifest,
+            blobs,
+        })
+    }
+
+    /// Returns the name of the application the archive was exported from.
+    pub fn app(&self) -> &str {
+        &self.app
+    }
+
+    /// Returns the configuration version the archive was exported from.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns when the archive was exported, with a precision of one second.
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// Returns the manifest of the archived files.
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+}
+
+impl Config {
+    /// Writes every file in the version root and its scopes to an archive.
+    ///
+    /// Files are archived as they are stored on disk, so encrypted files stay encrypted.
+    ///
+    /// ```no_run
+    /// # use libset::{Archive, Config};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let mut backup = Vec::new();
+    /// config.export_archive(&mut backup)?;
+    ///
+    /// let archive = Archive::read(backup.as_slice())?;
+    /// println!("{} files", archive.manifest().files().count());
+    /// config.import_archive(&archive)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the archive.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Manifest` of the archived files or an `Error` if an error
+    /// occurred.
+    pub fn export_archive<W: Write>(&self, mut writer: W) -> Result<Manifest, Error> {
+        let mut blobs = HashMap::new();
+        let manifest = self.manifest_with(|digest, data| {
+            blobs.insert(digest.to_string(), data.to_vec());
+            Ok(())
+        })?;
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let manifest_text = manifest.to_string();
+
+        let mut write = || -> std::io::Result<()> {
+            writeln!(writer, "{HEADER}")?;
+            writeln!(writer, "app {}", self.name)?;
+            writeln!(writer, "version {}", self.version)?;
+            writeln!(writer, "created {created}")?;
+            writeln!(writer, "manifest {}", manifest_text.len())?;
+            writer.write_all(manifest_text.as_bytes())?;
+            writeln!(writer)?;
+            let mut digests: Vec<_> = blobs.keys().collect();
+            digests.sort();
+            for digest in digests {
+                let data = &blobs[digest];
+                writeln!(writer, "blob {digest} {}", data.len())?;
+                writer.write_all(data)?;
+                writeln!(writer)?;
+            }
+            writeln!(writer, "{TRAILER}")?;
+            writer.flush()
+        };
+        write().map_err(|source| Error::ArchiveIo { source })?;
+        Ok(manifest)
+    }
+
+    /// Replaces the contents of the store with the files of an archive.
+    ///
+    /// The archive must have been exported from the same application and version. Files that
+    /// are not part of the archive are removed, like [`Config::restore`] does for snapshots.
+    ///
+    /// # Arguments
+    ///
+    /// * `archive` - The archive read with [`Archive::read`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the archive belongs to another store,
+    /// the store is [sealed](Config::seal) or an error occurred.
+    pub fn import_archive(&self, archive: &Archive) -> Result<(), Error> {
+        self.check_sealed()?;
+        if archive.app != self.name || archive.version != self.version {
+            return Err(invalid(&format!(
+                "exported from {} v{}, expected {} v{}",
+                archive.app, archive.version, self.name, self.version
+            )));
+        }
+        self.flush()?;
+        let files = archive
+            .manifest
+            .files()
+            .map(|(path, digest)| {
+                if is_temporary(Path::new(path)) {
+                    return Err(invalid_file(path, "internal files can't be imported"));
+                }
+                Ok((
+                    self.root.join(sanitize_name(path)?),
+                    archive.blobs[digest].clone(),
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.replace_files(&files)
+    }
+}
+
+/// Builds the error for a malformed archive.
+fn invalid(reason: &str) -> Error {
+    Error::InvalidArchive {
+        file: None,
+        reason: reason.to_string(),
+    }
+}
+
+/// Builds the error for an archived file that can't be imported.
+fn invalid_file(file: &str, reason: &str) -> Error {
+    Error::InvalidArchive {
+        file: Some(file.to_string()),
+        reason: reason.to_string(),
+    }
+}
+
+/// Reads a line without its line break, failing at the end of the archive.
+fn read_line(reader: &mut impl BufRead) -> Result<String, Error> {
+    let mut line = String::new();
+    let read = reader
+        .read_line(&mut line)
+        .map_err(|source| Error::ArchiveIo { source })?;
+    if read == 0 || !line.ends_with('\n') {
+        return Err(invalid("unexpected end of archive"));
+    }
+    line.pop();
+    Ok(line)
+}
+
+/// Reads a header line starting with `name`, returning its value.
+fn field(reader: &mut impl BufRead, name: &str) -> Result<String, Error> {
+    let line = read_line(reader)?;
+    line.strip_prefix(name)
+        .and_then(|value| value.strip_prefix(' '))
+        .map(str::to_string)
+        .ok_or_else(|| invalid(&format!("expected {name}, found {line:?}")))
+}
+
+/// Reads a section of the given length followed by a line break.
+fn read_data(reader: &mut impl BufRead, length: &str) -> Result<Vec<u8>, Error> {
+    let length: u64 = length.parse().map_err(|_| invalid("invalid length"))?;
+    let limit = length
+        .checked_add(1)
+        .filter(|_| length <= MAX_SECTION_LENGTH)
+        .ok_or_else(|| invalid(&format!("section of {length} bytes is too large")))?;
+    let mut data = Vec::new();
+    reader
+        .take(limit)
+        .read_to_end(&mut data)
+        .map_err(|source| Error::ArchiveIo { source })?;
+    if data.pop() != Some(b'\n') || data.len() as u64 != length {
+        return Err(invalid("unexpected end of archive"));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{manifest::hash, Archive, Config, Error};
+
+    /// Serializes an archive of the test store holding one file.
+    fn archive(path: &str, data: &str) -> String {
+        let manifest = format!("libset-manifest 1\n{} {path}\n", hash(data.as_bytes()));
+        format!(
+            "libset-archive 1\napp org.example.Test\nversion 1\ncreated 0\nmanifest {}\n{manifest}\nblob {} {}\n{data}\nend\n",
+            manifest.len(),
+            hash(data.as_bytes()),
+            data.len()
+        )
+    }
+
+    #[test]
+    fn rejects_oversized_sections() {
+        let text = format!(
+            "libset-archive 1\napp a\nversion 1\ncreated 0\nmanifest {}\n",
+            u64::MAX
+        );
+        assert!(matches!(
+            Archive::read(text.as_bytes()),
+            Err(Error::InvalidArchive { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_internal_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        let valid = Archive::read(archive("session", "42").as_bytes()).unwrap();
+        config.import_archive(&valid).unwrap();
+        assert_eq!(config.get_plain("session").unwrap(), "42");
+
+        for path in [".sealed", ".locks/session.lock", ".atomicwrite-session.tmp"] {
+            let archive = Archive::read(archive(path, "").as_bytes()).unwrap();
+            assert!(matches!(
+                config.import_archive(&archive),
+                Err(Error::InvalidArchive { .. })
+            ));
+        }
+        assert!(!config.is_sealed());
+        assert_eq!(config.get_plain("session").unwrap(), "42");
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde::Serialize;
+
+use crate::{
+    entry::temporary_name, filesystem::Filesystem, hooks::EventKind, utils::FileType, Config,
+    Error, OverwriteBehavior, WriteStrategy,
+};
+
+/// Counter used to give temporary files unique names within a process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A serialized value waiting to be committed.
+struct Staged {
+    key: String,
+    file_type: FileType,
+    path: PathBuf,
+    data: Vec<u8>,
+}
+
+/// A group of writes committed together.
+///
+/// Values are serialized when they are added. On [`Batch::commit`] every file is first
+/// written and synced to a temporary file, then all of them are renamed into place and each
+/// affected directory is synced once. With [`Config::with_journal`] the renames are recorded
+/// first, so a commit interrupted by a crash is completed on the next start. The write
+/// strategy and overwrite behavior of the `Config` apply to every file, see
+/// [`Batch::commit`].
+pub struct Batch<'a> {
+    config: &'a Config,
+    staged: Vec<Staged>,
+}
+
+impl<'a> Batch<'a> {
+    pub(crate) fn new(config: &'a Config) -> Self {
+        Self {
+            config,
+            staged: Vec::new(),
+        }
+    }
+
+    /// Serializes a value and adds it to the batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the batch or an `Error` if the value could not be serialized.
+    /// Plain files hold text rather than values, add them with [`Batch::set_plain`].
+    pub fn set<T: Serialize + ?Sized>(
+        &mut self,
+        key: &str,
+        file_type: FileType,
+        value: &T,
+    ) -> Result<&mut Self, Error> {
+        let path = self.config.path(key, file_type)?;
+        let data = self.config.serialize_value(key, file_type, &path, value)?;
+        let data = self.config.encode(key, file_type, &path, data.as_bytes())?;
+        self.staged.push(Staged {
+            key: key.to_string(),
+            file_type,
+            path,
+            data,
+        });
+        Ok(self)
+    }
+
+    /// Adds the content of a plain file to the batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - String to write.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the batch or an `Error` if an error occurred.
+    pub fn set_plain(&mut self, key: &str, value: impl ToString) -> Result<&mut Self, Error> {
+        let path = self.config.path(key, FileType::Plain)?;
+        let data = self
+            .config
+            .encode(key, FileType::Plain, &path, value.to_string().as_bytes())?;
+        self.staged.push(Staged {
+            key: key.to_string(),
+            file_type: FileType::Plain,
+            path,
+            data,
+        });
+        Ok(self)
+    }
+
+    /// Writes every staged value to the filesystem.
+    ///
+    /// With [`WriteStrategy::Atomic`], the default, files are renamed into place together
+    /// once all of them are written. The other strategies overwrite each file in turn, so a
+    /// failure or crash can leave the batch partially applied. With
+    /// [`OverwriteBehavior::DisallowOverwrite`] no file is written if any of them exists, and
+    /// with [`OverwriteBehavior::BackupThenOverwrite`] existing files are copied first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred. When writing the
+    /// temporary files fails no stored file is modified.
+    pub fn commit(self) -> Result<(), Error> {
+        self.config.check_sealed()?;
+        let existing: Vec<&Staged> = self
+            .staged
+            .iter()
+            .filter(|staged| self.config.exists(&staged.path))
+            .collect();
+        match self.config.overwrite {
+            OverwriteBehavior::AllowOverwrite => {}
+            OverwriteBehavior::DisallowOverwrite => {
+                if let Some(staged) = existing.first() {
+                    return Err(Error::AlreadyExists {
+                        key: staged.key.clone(),
+                        file_type: staged.file_type,
+                        path: staged.path.clone(),
+                    });
+                }
+            }
+            OverwriteBehavior::BackupThenOverwrite => {
+                for staged in existing {
+                    self.config
+                        .back_up(&staged.key, staged.file_type, &staged.path)?;
+                }
+            }
+        }
+        for staged in &self.staged {
+            self.config.emit(
+                EventKind::BeforeWrite,
+                &staged.key,
+                staged.file_type,
+                &staged.path,
+            );
+        }
+
+        match self.config.write_strategy {
+            WriteStrategy::Atomic => self.rename_into_place()?,
+            strategy => self.write_each(strategy)?,
+        }
+
+        for staged in &self.staged {
+            self.config.emit(
+                EventKind::AfterWrite,
+                &staged.key,
+                staged.file_type,
+                &staged.path,
+            );
+            self.config.log("wrote file", &staged.key, &staged.path);
+        }
+        Ok(())
+    }
+
+    /// Writes every file to a temporary file, then renames all of them into place.
+    fn rename_into_place(&self) -> Result<(), Error> {
+        let mut temporary = Vec::with_capacity(self.staged.len());
+        for staged in &self.staged {
+            match write_temporary(self.config.fs.as_ref(), &staged.path, &staged.data) {
+                Ok(path) => temporary.push(path),
+                Err(err) => {
+                    for path in &temporary {
+                        let _ = self.config.fs.remove(path);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        let renames: Vec<(&Path, &Path)> = temporary
+            .iter()
+            .zip(&self.staged)
+            .map(|(temporary, staged)| (temporary.as_path(), staged.path.as_path()))
+            .collect();
+        let journal = match self.config.begin_journal(&renames) {
+            Ok(journal) => journal,
+            Err(err) => {
+                for path in &temporary {
+                    let _ = self.config.fs.remove(path);
+                }
+                return Err(err);
+            }
+        };
+
+        let mut directories: Vec<&Path> = Vec::new();
+        for (staged, temporary) in self.staged.iter().zip(&temporary) {
+            if let Some(flusher) = &self.config.flusher {
+                flusher.cancel(&staged.path);
+            }
+            self.config.cache.invalidate(&staged.path);
+            self.config
+                .fs
+                .rename(temporary, &staged.path)
+                .map_err(|err| Error::io(&staged.path, err))?;
+            if let Some(parent) = staged.path.parent() {
+                if !directories.contains(&parent) {
+                    directories.push(parent);
+                }
+            }
+        }
+        for directory in directories {
+            self.config
+                .fs
+                .sync_dir(directory)
+                .map_err(|err| Error::io(directory, err))?;
+        }
+        if let Some(journal) = journal {
+            journal.finish(self.config.fs.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Writes every file in turn with a non-atomic strategy.
+    fn write_each(&self, strategy: WriteStrategy) -> Result<(), Error> {
+        for staged in &self.staged {
+            if let Some(flusher) = &self.config.flusher {
+                flusher.cancel(&staged.path);
+            }
+            self.config.cache.invalidate(&staged.path);
+            strategy.write(self.config.fs.as_ref(), &staged.path, &staged.data, false)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes data to a synced temporary file next to the destination.
+fn write_temporary(fs: &dyn Filesystem, path: &Path, data: &[u8]) -> Result<PathBuf, Error> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let temporary = path.with_file_name(temporary_name(
+        &name,
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed),
+    ));
+    if let Err(err) = fs.write_in_place(&temporary, data, true) {
+        let _ = fs.remove(&temporary);
+        return Err(Error::io(&temporary, err));
+    }
+    Ok(temporary)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Config, Error, FileType, OverwriteBehavior, WriteStrategy};
+
+    #[test]
+    fn rejects_values_for_plain_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+
+        let mut batch = config.batch();
+        assert!(matches!(
+            batch.set("session", FileType::Plain, "42"),
+            Err(Error::UnsupportedByFormat { .. })
+        ));
+        assert!(matches!(
+            config.set_many(&[("session", FileType::Plain, "42")]),
+            Err(Error::UnsupportedByFormat { .. })
+        ));
+        assert!(!config.path.join("session").exists());
+
+        batch.set_plain("session", 42).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(config.get_plain("session").unwrap(), "42");
+    }
+
+    #[test]
+    fn follows_the_write_strategy_and_overwrite_behavior() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None)
+            .unwrap()
+            .with_write_strategy(WriteStrategy::Direct)
+            .with_overwrite_behavior(OverwriteBehavior::DisallowOverwrite);
+        let mut batch = config.batch();
+        batch.set_plain("theme", "dark").unwrap();
+        batch.commit().unwrap();
+        assert_eq!(config.get_plain("theme").unwrap(), "dark");
+
+        let mut batch = config.batch();
+        batch.set_plain("font", "serif").unwrap();
+        batch.set_plain("theme", "light").unwrap();
+        assert!(matches!(batch.commit(), Err(Error::AlreadyExists { .. })));
+        assert!(!config.path.join("font").exists());
+        assert_eq!(config.get_plain("theme").unwrap(), "dark");
+
+        let config = config.with_overwrite_behavior(OverwriteBehavior::BackupThenOverwrite);
+        let mut batch = config.batch();
+        batch.set_plain("theme", "light").unwrap();
+        batch.commit().unwrap();
+        config.restore_backup("theme", FileType::Plain).unwrap();
+        assert_eq!(config.get_plain("theme").unwrap(), "dark");
+    }
+}
@@ -0,0 +1,451 @@
+//! Command line interface to inspect and edit libset configuration stores.
+
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use libset::{Archive, Config, FileType};
+use serde_json::Value;
+
+#[cfg(feature = "tui")]
+mod tui;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Inspect and edit libset configuration stores.
+#[derive(Parser)]
+#[command(name = "libset", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the value of a key.
+    Get {
+        /// The store, as `name/vVERSION` optionally followed by `/scope`.
+        store: Store,
+        /// The key to read.
+        key: String,
+        /// The format of the file.
+        #[arg(short, long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+    /// Sets the value of a key.
+    Set {
+        /// The store, as `name/vVERSION` optionally followed by `/scope`.
+        store: Store,
+        /// The key to write.
+        key: String,
+        /// The value to write, in the given format, or `-` to read it from stdin.
+        value: String,
+        /// The format of the file.
+        #[arg(short, long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+    /// Lists the keys of a store.
+    Ls {
+        /// The store, as `name/vVERSION` optionally followed by `/scope`.
+        store: Store,
+        /// Include the keys of every scope.
+        #[arg(short, long)]
+        recursive: bool,
+    },
+    /// Removes a key.
+    Rm {
+        /// The store, as `name/vVERSION` optionally followed by `/scope`.
+        store: Store,
+        /// The key to remove.
+        key: String,
+        /// The format of the file.
+        #[arg(short, long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+    /// Prints every key of a store and its scopes as a single JSON document.
+    Export {
+        /// The store, as `name/vVERSION` optionally followed by `/scope`.
+        store: Store,
+        /// Writes a verifiable archive to this file instead, see `import`.
+        #[arg(long)]
+        archive: Option<PathBuf>,
+    },
+    /// Replaces the contents of a store with an archive written by `export --archive`.
+    Import {
+        /// The store, as `name/vVERSION` optionally followed by `/scope`.
+        store: Store,
+        /// The archive to restore.
+        archive: PathBuf,
+    },
+    /// Browses every store interactively.
+    #[cfg(feature = "tui")]
+    Tui,
+    /// Checks that a store is writable and that every file can be parsed.
+    Doctor {
+        /// The store, as `name/vVERSION` optionally followed by `/scope`.
+        store: Store,
+        /// Prints the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// The format of a stored file.
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Plain,
+    Json,
+    #[cfg(feature = "toml")]
+    Toml,
+    #[cfg(feature = "ron")]
+    Ron,
+    #[cfg(feature = "yaml")]
+    Yaml,
+    #[cfg(feature = "ini")]
+    Ini,
+    #[cfg(feature = "json5")]
+    Json5,
+}
+
+impl From<Format> for FileType {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Plain => FileType::Plain,
+            Format::Json => FileType::Json,
+            #[cfg(feature = "toml")]
+            Format::Toml => FileType::Toml,
+            #[cfg(feature = "ron")]
+            Format::Ron => FileType::Ron,
+            #[cfg(feature = "yaml")]
+            Format::Yaml => FileType::Yaml,
+            #[cfg(feature = "ini")]
+            Format::Ini => FileType::Ini,
+            #[cfg(feature = "json5")]
+            Format::Json5 => FileType::Json5,
+        }
+    }
+}
+
+impl From<FileType> for Format {
+    fn from(file_type: FileType) -> Self {
+        match file_type {
+            FileType::Plain => Format::Plain,
+            FileType::Json => Format::Json,
+            #[cfg(feature = "toml")]
+            FileType::Toml => Format::Toml,
+            #[cfg(feature = "ron")]
+            FileType::Ron => Format::Ron,
+            #[cfg(feature = "yaml")]
+            FileType::Yaml => Format::Yaml,
+            #[cfg(feature = "ini")]
+            FileType::Ini => Format::Ini,
+            #[cfg(feature = "json5")]
+            FileType::Json5 => Format::Json5,
+        }
+    }
+}
+
+/// A configuration store, such as `org.example.App/v1/profile`.
+#[derive(Clone)]
+struct Store {
+    name: String,
+    version: u64,
+    scope: Option<String>,
+}
+
+impl std::str::FromStr for Store {
+    type Err = String;
+
+    fn from_str(store: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("expected `name/vVERSION[/scope]`, found `{store}`");
+        let mut parts = store.splitn(3, '/');
+        let name = parts
+            .next()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(invalid)?;
+        let version = parts
+            .next()
+            .and_then(|version| version.strip_prefix('v'))
+            .and_then(|version| version.parse().ok())
+            .ok_or_else(invalid)?;
+        Ok(Self {
+            name: name.to_string(),
+            version,
+            scope: parts.next().map(str::to_string),
+        })
+    }
+}
+
+impl Store {
+    fn open(&self) -> Result<Config> {
+        Ok(Config::new(
+            &self.name,
+            self.version,
+            self.scope.as_deref(),
+        )?)
+    }
+
+    /// Opens the store with a scope relative to the version root.
+    fn open_scope(&self, scope: Option<&Path>) -> Result<Config> {
+        let scope = scope.map(|scope| scope.to_string_lossy());
+        Ok(Config::new(&self.name, self.version, scope.as_deref())?)
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("libset: {}", describe(err.as_ref()));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<ExitCode> {
+    match command {
+        Command::Get { store, key, format } => {
+            let value = read(&store.open()?, &key, format.into())?;
+            println!("{}", render(&value, format)?);
+        }
+        Command::Set {
+            store,
+            key,
+            value,
+            format,
+        } => {
+            let value = if value == "-" {
+                let mut input = String::new();
+                std::io::stdin().read_to_string(&mut input)?;
+                input
+            } else {
+                value
+            };
+            write(&store.open()?, &key, format, &value)?;
+        }
+        Command::Ls { store, recursive } => {
+            let config = store.open()?;
+            let entries = if recursive {
+                config.walk()?.collect::<std::result::Result<Vec<_>, _>>()?
+            } else {
+                config
+                    .entries()?
+                    .collect::<std::result::Result<Vec<_>, _>>()?
+            };
+            let mut names: Vec<_> = entries
+                .iter()
+                .map(|entry| name(entry.scope.as_deref(), &entry.key, entry.file_type))
+                .collect();
+            names.sort();
+            for name in names {
+                println!("{name}");
+            }
+        }
+        Command::Rm { store, key, format } => store.open()?.remove(&key, format.into())?,
+        Command::Export {
+            store,
+            archive: Some(archive),
+        } => {
+            let file = std::fs::File::create(&archive)?;
+            let manifest = store
+                .open()?
+                .export_archive(std::io::BufWriter::new(file))?;
+            println!(
+                "Exported {} files to {}",
+                manifest.files().count(),
+                archive.display()
+            );
+        }
+        Command::Import { store, archive } => {
+            let archive = Archive::read(std::fs::File::open(archive)?)?;
+            store.open()?.import_archive(&archive)?;
+            println!("Imported {} files", archive.manifest().files().count());
+        }
+        Command::Export {
+            store,
+            archive: None,
+        } => {
+            let mut document = BTreeMap::new();
+            for entry in store.open()?.walk()? {
+                let entry = entry?;
+                let config = store.open_scope(entry.scope.as_deref())?;
+                let value = read(&config, &entry.key, entry.file_type)?;
+                document.insert(
+                    name(entry.scope.as_deref(), &entry.key, entry.file_type),
+                    value,
+                );
+            }
+            println!("{}", serde_json::to_string_pretty(&document)?);
+        }
+        Command::Doctor { store, json } => return doctor(&store, json),
+        #[cfg(feature = "tui")]
+        Command::Tui => tui::run()?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Reads a key into a format-independent value.
+fn read(config: &Config, key: &str, file_type: FileType) -> Result<Value> {
+    Ok(match file_type {
+        FileType::Plain => Value::String(config.get_plain(key)?),
+        FileType::Json => config.get_json(key)?,
+        #[cfg(feature = "toml")]
+        FileType::Toml => config.get_toml(key)?,
+        #[cfg(feature = "ron")]
+        FileType::Ron => config.get_ron(key)?,
+        #[cfg(feature = "yaml")]
+        FileType::Yaml => config.get_yaml(key)?,
+        #[cfg(feature = "ini")]
+        FileType::Ini => config.get_ini(key)?,
+        #[cfg(feature = "json5")]
+        FileType::Json5 => config.get_json5(key)?,
+    })
+}
+
+/// Parses a value in the given format and stores it.
+fn write(config: &Config, key: &str, format: Format, value: &str) -> Result<()> {
+    match format {
+        Format::Plain => config.set_plain(key, value)?,
+        Format::Json => config.set_json(key, serde_json::from_str::<Value>(value)?)?,
+        #[cfg(feature = "toml")]
+        Format::Toml => config.set_toml(key, toml::from_str::<toml::Value>(value)?)?,
+        #[cfg(feature = "ron")]
+        Format::Ron => config.set_ron(key, ron::from_str::<ron::Value>(value)?)?,
+        #[cfg(feature = "yaml")]
+        Format::Yaml => config.set_yaml(key, serde_yaml::from_str::<Value>(value)?)?,
+        #[cfg(feature = "ini")]
+        Format::Ini => config.set_ini(key, ini_to_value(value)?)?,
+        #[cfg(feature = "json5")]
+        Format::Json5 => config.set_json5(key, json5::from_str::<Value>(value)?)?,
+    }
+    Ok(())
+}
+
+/// Formats a value for display.
+fn render(value: &Value, format: Format) -> Result<String> {
+    Ok(match (format, value) {
+        (Format::Plain, Value::String(value)) => value.clone(),
+        (Format::Plain, value) => value.to_string(),
+        #[cfg(feature = "toml")]
+        (Format::Toml, value) => toml::to_string_pretty(value)?,
+        #[cfg(feature = "ron")]
+        (Format::Ron, value) => ron::ser::to_string_pretty(value, Default::default())?,
+        #[cfg(feature = "yaml")]
+        (Format::Yaml, value) => serde_yaml::to_string(value)?,
+        #[cfg(feature = "ini")]
+        (Format::Ini, value) => value_to_ini(value)?,
+        #[cfg(feature = "json5")]
+        (Format::Json5, value) => serde_json::to_string_pretty(value)?,
+        (Format::Json, value) => serde_json::to_string_pretty(value)?,
+    })
+}
+
+/// Parses ini text into sections of string values.
+#[cfg(feature = "ini")]
+fn ini_to_value(text: &str) -> Result<Value> {
+    let mut document = serde_json::Map::new();
+    for (section, properties) in &ini::Ini::load_from_str(text)? {
+        let properties = properties
+            .iter()
+            .map(|(name, value)| (name.to_string(), Value::String(value.to_string())));
+        match section {
+            None => document.extend(properties),
+            Some(section) => {
+                document.insert(section.to_string(), Value::Object(properties.collect()));
+            }
+        }
+    }
+    Ok(Value::Object(document))
+}
+
+/// Formats a value as ini, with nested objects as sections.
+#[cfg(feature = "ini")]
+fn value_to_ini(value: &Value) -> Result<String> {
+    let text = |value: &Value| match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    };
+    let mut ini = ini::Ini::new();
+    for (name, value) in value.as_object().into_iter().flatten() {
+        match value {
+            Value::Object(properties) => {
+                let mut section = ini.with_section(Some(name.as_str()));
+                for (property, value) in properties {
+                    section.set(property.as_str(), text(value));
+                }
+            }
+            value => {
+                ini.with_general_section().set(name.as_str(), text(value));
+            }
+        }
+    }
+    let mut data = Vec::new();
+    ini.write_to(&mut data)?;
+    Ok(String::from_utf8(data)?)
+}
+
+/// Checks a store for problems, printing each one.
+fn doctor(store: &Store, json: bool) -> Result<ExitCode> {
+    let config = store.open()?;
+
+    let probe = ".libset-doctor";
+    let writable = config
+        .set_plain(probe, "")
+        .and_then(|_| config.remove(probe, FileType::Plain));
+    let report = config.check()?;
+
+    if json {
+        let document = serde_json::json!({
+            "writable": writable.is_ok(),
+            "checked": report.checked,
+            "issues": report.issues,
+        });
+        println!("{}", serde_json::to_string_pretty(&document)?);
+    } else {
+        match &writable {
+            Ok(()) => println!("ok: store is writable"),
+            Err(err) => println!("error: store is not writable: {err}"),
+        }
+        for issue in &report.issues {
+            println!("error: {issue}");
+        }
+        let problems = report.issues.len() + usize::from(writable.is_err());
+        println!(
+            "{} files checked, {problems} problems found",
+            report.checked
+        );
+    }
+
+    Ok(if writable.is_ok() && report.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Formats an error followed by its causes.
+fn describe(err: &dyn std::error::Error) -> String {
+    let mut text = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        text = format!("{text}: {cause}");
+        source = cause.source();
+    }
+    text
+}
+
+/// Returns the file name of a key, prefixed with its scope.
+fn name(scope: Option<&Path>, key: &str, file_type: FileType) -> String {
+    let file = match file_type {
+        FileType::Plain => key.to_string(),
+        file_type => format!("{key}.{file_type}"),
+    };
+    match scope {
+        Some(scope) => PathBuf::from(scope).join(file).display().to_string(),
+        None => file,
+    }
+}
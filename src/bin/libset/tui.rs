@@ -0,0 +1,450 @@
+//! Interactive browser for configuration stores.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use libset::FileType;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+use crate::{describe, name, read, render, write, Format, Result, Store};
+
+const HELP: &str = "↑↓ move  ⏎ open  ← back  e edit  d delete  r refresh  q quit";
+
+/// The location being browsed.
+enum Level {
+    /// Every application with a configuration store.
+    Apps,
+    /// The versions stored for an application.
+    Versions(String),
+    /// The scopes and keys of a store.
+    Store(Store),
+}
+
+/// An entry listed at the current level.
+enum Item {
+    /// An application, version or scope that can be opened.
+    Dir(String),
+    /// A stored key.
+    Key { key: String, file_type: FileType },
+}
+
+impl Item {
+    fn label(&self) -> String {
+        match self {
+            Item::Dir(name) => format!("{name}/"),
+            Item::Key { key, file_type } => name(None, key, *file_type),
+        }
+    }
+}
+
+/// State of the browser.
+struct App {
+    root: PathBuf,
+    level: Level,
+    items: Vec<Item>,
+    state: ListState,
+    preview: Option<(Format, String)>,
+    status: Option<String>,
+    confirm_delete: bool,
+}
+
+/// Runs the browser until the user quits.
+pub(crate) fn run() -> Result<()> {
+    let root = dirs::config_dir().ok_or(libset::Error::NoConfigDirectory)?;
+    let mut app = App {
+        root,
+        level: Level::Apps,
+        items: Vec::new(),
+        state: ListState::default(),
+        preview: None,
+        status: None,
+        confirm_delete: false,
+    };
+    app.refresh();
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if self.confirm_delete {
+                self.confirm_delete = false;
+                self.status = None;
+                if key.code == KeyCode::Char('y') {
+                    self.delete();
+                }
+                continue;
+            }
+            self.status = None;
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => self.select(self.state.selected(), -1),
+                KeyCode::Down | KeyCode::Char('j') => self.select(self.state.selected(), 1),
+                KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => self.open(),
+                KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => self.back(),
+                KeyCode::Char('r') => self.refresh(),
+                KeyCode::Char('e') => self.edit(terminal)?,
+                KeyCode::Char('d') => {
+                    if let Some(Item::Key { key, file_type }) = self.selected() {
+                        self.status =
+                            Some(format!("Delete {}? (y/n)", name(None, key, *file_type)));
+                        self.confirm_delete = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [body, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list, preview] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(body);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|item| ListItem::new(item.label()))
+            .collect();
+        let list_widget = List::new(items)
+            .block(Block::bordered().title(self.title()))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list_widget, list, &mut self.state);
+
+        let text = match &self.preview {
+            Some((format, contents)) => highlight(contents, *format),
+            None => Text::default(),
+        };
+        frame.render_widget(
+            Paragraph::new(text).block(Block::bordered().title("Preview")),
+            preview,
+        );
+
+        let line = match &self.status {
+            Some(status) => Line::styled(status.as_str(), Style::new().fg(Color::Yellow)),
+            None => Line::styled(HELP, Style::new().fg(Color::DarkGray)),
+        };
+        frame.render_widget(line, status);
+    }
+
+    fn title(&self) -> String {
+        match &self.level {
+            Level::Apps => self.root.display().to_string(),
+            Level::Versions(app) => app.clone(),
+            Level::Store(store) => match &store.scope {
+                Some(scope) => format!("{}/v{}/{scope}", store.name, store.version),
+                None => format!("{}/v{}", store.name, store.version),
+            },
+        }
+    }
+
+    fn selected(&self) -> Option<&Item> {
+        self.state
+            .selected()
+            .and_then(|index| self.items.get(index))
+    }
+
+    /// Moves the selection and refreshes the preview.
+    fn select(&mut self, current: Option<usize>, offset: isize) {
+        let selected = match (current, self.items.len()) {
+            (_, 0) => None,
+            (None, _) => Some(0),
+            (Some(index), len) => Some(index.saturating_add_signed(offset).min(len - 1)),
+        };
+        self.state.select(selected);
+        self.preview = match (&self.level, self.selected()) {
+            (Level::Store(store), Some(Item::Key { key, file_type })) => {
+                let format = Format::from(*file_type);
+                let contents = store
+                    .open()
+                    .and_then(|config| read(&config, key, *file_type))
+                    .and_then(|value| render(&value, format))
+                    .unwrap_or_else(|err| format!("Error: {}", describe(err.as_ref())));
+                Some((format, contents))
+            }
+            _ => None,
+        };
+    }
+
+    /// Reloads the items of the current level.
+    fn refresh(&mut self) {
+        let items = match &self.level {
+            Level::Apps => Ok(subdirs(&self.root)
+                .into_iter()
+                .filter(|app| !versions(&self.root.join(app)).is_empty())
+                .map(Item::Dir)
+                .collect()),
+            Level::Versions(app) => Ok(versions(&self.root.join(app))
+                .into_iter()
+                .map(|version| Item::Dir(format!("v{version}")))
+                .collect()),
+            Level::Store(store) => store_items(&self.root, store),
+        };
+        match items {
+            Ok(items) => self.items = items,
+            Err(err) => {
+                self.items = Vec::new();
+                self.status = Some(err.to_string());
+            }
+        }
+        let current = self.state.selected().filter(|_| !self.items.is_empty());
+        self.select(current, 0);
+    }
+
+    /// Opens the selected application, version or scope.
+    fn open(&mut self) {
+        let Some(Item::Dir(dir)) = self.selected() else {
+            return;
+        };
+        let dir = dir.clone();
+        self.level = match &self.level {
+            Level::Apps => Level::Versions(dir),
+            Level::Versions(app) => Level::Store(Store {
+                name: app.clone(),
+                version: dir.trim_start_matches('v').parse().unwrap_or_default(),
+                scope: None,
+            }),
+            Level::Store(store) => Level::Store(Store {
+                scope: Some(match &store.scope {
+                    Some(scope) => format!("{scope}/{dir}"),
+                    None => dir,
+                }),
+                ..store.clone()
+            }),
+        };
+        self.state.select(None);
+        self.refresh();
+    }
+
+    /// Goes up one level.
+    fn back(&mut self) {
+        self.level = match &self.level {
+            Level::Apps => return,
+            Level::Versions(_) => Level::Apps,
+            Level::Store(store) => match &store.scope {
+                None => Level::Versions(store.name.clone()),
+                Some(scope) => Level::Store(Store {
+                    scope: scope.rsplit_once('/').map(|(parent, _)| parent.to_string()),
+                    ..store.clone()
+                }),
+            },
+        };
+        self.state.select(None);
+        self.refresh();
+    }
+
+    /// Removes the selected key.
+    fn delete(&mut self) {
+        let (Level::Store(store), Some(Item::Key { key, file_type })) =
+            (&self.level, self.selected())
+        else {
+            return;
+        };
+        let result = store
+            .open()
+            .and_then(|config| Ok(config.remove(key, *file_type)?));
+        if let Err(err) = result {
+            self.status = Some(err.to_string());
+        }
+        self.refresh();
+    }
+
+    /// Edits the selected key in the user's editor.
+    ///
+    /// The edited text is parsed in the key's format and written back through the library, so
+    /// invalid documents are rejected and never reach the disk.
+    fn edit(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let (Level::Store(store), Some(Item::Key { key, file_type })) =
+            (&self.level, self.selected())
+        else {
+            return Ok(());
+        };
+        let (key, file_type, format) = (key.clone(), *file_type, Format::from(*file_type));
+        let config = store.open()?;
+        // Files that can't be parsed are opened as they are on disk so they can be fixed.
+        let contents = match read(&config, &key, file_type).and_then(|value| render(&value, format))
+        {
+            Ok(contents) => contents,
+            Err(_) => std::fs::read_to_string(config.path(&key, file_type)?)?,
+        };
+
+        let file = std::env::temp_dir().join(format!(
+            "libset-{}-{}",
+            std::process::id(),
+            name(None, &key, file_type)
+        ));
+        std::fs::write(&file, &contents)?;
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+
+        ratatui::restore();
+        let status = Command::new(&editor).arg(&file).status();
+        *terminal = ratatui::init();
+
+        let edited = std::fs::read_to_string(&file);
+        std::fs::remove_file(&file).ok();
+        self.status = match (status, edited) {
+            (Err(err), _) => Some(format!("Failed to run {editor}: {err}")),
+            (Ok(status), _) if !status.success() => Some(format!("{editor} exited with {status}")),
+            (Ok(_), Err(err)) => Some(err.to_string()),
+            (Ok(_), Ok(edited)) if edited == contents => None,
+            (Ok(_), Ok(mut edited)) => {
+                if !contents.ends_with('\n') && edited.ends_with('\n') {
+                    edited.pop();
+                }
+                match write(&config, &key, format, &edited) {
+                    Ok(()) => Some(format!("Saved {}", name(None, &key, file_type))),
+                    Err(err) => Some(format!("Not saved: {err}")),
+                }
+            }
+        };
+        self.refresh();
+        Ok(())
+    }
+}
+
+/// Lists the scopes and keys of a store, scopes first.
+fn store_items(root: &Path, store: &Store) -> Result<Vec<Item>> {
+    let config = store.open()?;
+    let mut dir = root.join(&store.name).join(format!("v{}", store.version));
+    if let Some(scope) = &store.scope {
+        dir.push(scope);
+    }
+    let mut items: Vec<Item> = subdirs(&dir).into_iter().map(Item::Dir).collect();
+    let mut keys = config
+        .entries()?
+        .map(|entry| {
+            entry.map(|entry| Item::Key {
+                key: entry.key,
+                file_type: entry.file_type,
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    keys.sort_by_key(Item::label);
+    items.append(&mut keys);
+    Ok(items)
+}
+
+/// Returns the names of the visible subdirectories of a directory, sorted.
+fn subdirs(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Returns the configuration versions stored for an application, sorted.
+fn versions(app: &Path) -> Vec<u64> {
+    let mut versions: Vec<u64> = subdirs(app)
+        .iter()
+        .filter_map(|name| name.strip_prefix('v')?.parse().ok())
+        .collect();
+    versions.sort();
+    versions
+}
+
+/// Colors the keys, strings, numbers and literals of a document.
+fn highlight(text: &str, format: Format) -> Text<'static> {
+    if matches!(format, Format::Plain) {
+        return Text::raw(text.to_string());
+    }
+    text.lines().map(highlight_line).collect()
+}
+
+fn highlight_line(line: &str) -> Line<'static> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') || trimmed.starts_with("//") {
+        return Line::styled(line.to_string(), Style::new().fg(Color::DarkGray));
+    }
+    if trimmed.starts_with('[') && trimmed.trim_end().ends_with(']') && !trimmed.contains('"') {
+        return Line::styled(line.to_string(), Style::new().fg(Color::Magenta));
+    }
+
+    let chars: Vec<char> = line.chars().collect();
+    let is_key = |end: usize| {
+        chars[end..]
+            .iter()
+            .find(|c| !c.is_whitespace())
+            .is_some_and(|c| *c == ':' || *c == '=')
+    };
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let style = if c == '"' || c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            Some(if is_key(i) { Color::Blue } else { Color::Green })
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || "._+-".contains(chars[i]))
+            {
+                i += 1;
+            }
+            Some(Color::Cyan)
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || "_-.".contains(chars[i])) {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            match word.as_str() {
+                "true" | "false" | "null" | "None" | "Some" => Some(Color::Yellow),
+                _ if is_key(i) => Some(Color::Blue),
+                _ => None,
+            }
+        } else {
+            i += 1;
+            None
+        };
+        let token: String = chars[start..i].iter().collect();
+        match style {
+            Some(color) => {
+                if !plain.is_empty() {
+                    spans.push(Span::raw(std::mem::take(&mut plain)));
+                }
+                spans.push(Span::styled(token, Style::new().fg(color)));
+            }
+            None => plain.push_str(&token),
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    Line::from(spans)
+}
@@ -0,0 +1,257 @@
+use std::{
+    io::ErrorKind,
+    os::unix::{fs::DirBuilderExt, net::UnixDatagram},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use tracing::warn;
+
+use crate::{
+    entry::{scope_of, split_name},
+    hooks::EventKind,
+    utils::FileType,
+    Config, Error,
+};
+
+/// Extension of the sockets subscribers listen on.
+const SOCKET_EXTENSION: &str = "sock";
+/// How often a subscription checks whether it was dropped.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Distinguishes the sockets of several subscriptions in the same process.
+static SUBSCRIPTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// A change announced by another running instance of the application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Broadcast {
+    /// The key of the changed file.
+    pub key: String,
+    /// The scope of the changed file, relative to the version root.
+    pub scope: Option<PathBuf>,
+    /// The format of the changed file.
+    pub file_type: FileType,
+    /// Either [`EventKind::AfterWrite`] or [`EventKind::Remove`].
+    pub kind: EventKind,
+}
+
+/// Handle to an active subscription, broadcasts stop being received once it is dropped.
+pub struct Subscription {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+impl Config {
+    /// Enables or disables announcing changes to other running instances of the application.
+    ///
+    /// Every write and removal made through this `Config` is sent to the instances that called
+    /// [`Config::subscribe`] for the same application and version. Messages travel over Unix
+    /// sockets created next to the version directory, so they arrive immediately even where
+    /// filesystem watching is slow or drops events. Enable this before
+    /// [`Config::with_write_behind`] so background writes are announced as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether changes should be broadcast.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_broadcast(mut self, enabled: bool) -> Self {
+        if enabled {
+            let root = self.root.clone();
+            let dir = self.broadcast_dir();
+            self.on_event(move |event| {
+                if matches!(event.kind, EventKind::AfterWrite | EventKind::Remove) {
+                    broadcast(&dir, &root, event.kind, event.path);
+                }
+            });
+        }
+        self
+    }
+
+    /// Receives the changes broadcast by other running instances of the application.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?.with_broadcast(true);
+    /// let _subscription = config.subscribe(|change| {
+    ///     println!("{} was changed by another instance", change.key);
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Function invoked on a background thread with each change.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Subscription` handle or an `Error` if an error occurred.
+    pub fn subscribe<F>(&self, callback: F) -> Result<Subscription, Error>
+    where
+        F: Fn(Broadcast) + Send + 'static,
+    {
+        let dir = self.broadcast_dir();
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&dir)
+            .map_err(|err| Error::io(&dir, err))?;
+        let id = SUBSCRIPTIONS.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("{}-{id}.{SOCKET_EXTENSION}", std::process::id()));
+        // A socket left behind by a process with the same id is no longer in use.
+        std::fs::remove_file(&path).ok();
+        let socket = UnixDatagram::bind(&path).map_err(|err| Error::io(&path, err))?;
+        socket
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|err| Error::io(&path, err))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let root = self.root.clone();
+        let thread = std::thread::spawn(move || {
+            let mut buffer = vec![0; 4096];
+            while !stopped.load(Ordering::Relaxed) {
+                match socket.recv(&mut buffer) {
+                    Ok(len) => {
+                        if let Some(change) = parse(&root, &buffer[..len]) {
+                            callback(change);
+                        }
+                    }
+                    Err(err)
+                        if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                    Err(err) => {
+                        warn!(error = %err, "failed to receive broadcast");
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Subscription {
+            path,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Removes the sockets of subscribers whose process exited without cleaning up.
+    pub(crate) fn remove_stale_sockets(&self, removed: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(self.broadcast_dir()) else {
+            return;
+        };
+        let Ok(probe) = UnixDatagram::unbound() else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == SOCKET_EXTENSION)
+                && probe.connect(&path).is_err()
+                && std::fs::remove_file(&path).is_ok()
+            {
+                removed.push(path);
+            }
+        }
+    }
+
+    /// Returns the directory holding the sockets of the subscribers of this version.
+    fn broadcast_dir(&self) -> PathBuf {
+        self.root
+            .with_file_name(".broadcast")
+            .join(format!("v{}", self.version))
+    }
+}
+
+/// Sends a change to every subscriber in other processes, removing sockets nobody listens on.
+fn broadcast(dir: &Path, root: &Path, kind: EventKind, path: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let Ok(relative) = path.strip_prefix(root) else {
+        return;
+    };
+    let operation = if kind == EventKind::Remove { 'r' } else { 'w' };
+    let own = format!("{}-", std::process::id());
+    let message = format!(
+        "{operation} {}",
+        relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    );
+    let socket = match UnixDatagram::unbound() {
+        Ok(socket) => socket,
+        Err(err) => {
+            warn!(error = %err, "failed to broadcast change");
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let target = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if target
+            .extension()
+            .is_none_or(|extension| extension != SOCKET_EXTENSION)
+            || name.starts_with(&own)
+        {
+            continue;
+        }
+        match socket.send_to(message.as_bytes(), &target) {
+            Ok(_) => {}
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    ErrorKind::ConnectionRefused | ErrorKind::NotFound
+                ) =>
+            {
+                std::fs::remove_file(&target).ok();
+            }
+            Err(err) => warn!(path = %target.display(), error = %err, "failed to broadcast change"),
+        }
+    }
+}
+
+/// Parses a message sent by [`broadcast`].
+fn parse(root: &Path, message: &[u8]) -> Option<Broadcast> {
+    let message = std::str::from_utf8(message).ok()?;
+    let (kind, relative) = match message.split_once(' ')? {
+        ("w", relative) => (EventKind::AfterWrite, relative),
+        ("r", relative) => (EventKind::Remove, relative),
+        _ => return None,
+    };
+    if relative
+        .split('/')
+        .any(|part| part.is_empty() || part == "." || part == "..")
+    {
+        return None;
+    }
+    let path = root.join(relative);
+    let (key, file_type) = split_name(path.file_name()?.to_str()?);
+    Some(Broadcast {
+        key,
+        scope: scope_of(root, &path),
+        file_type,
+        kind,
+    })
+}
@@ -0,0 +1,297 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::SystemTime,
+};
+
+use crate::{filesystem::Filesystem, utils::FileType};
+
+/// How many deserialized values and preloaded files are kept by default.
+pub(crate) const DEFAULT_CAPACITY: usize = 256;
+
+/// How many resolved paths are kept before they are forgotten all at once.
+const PATH_CAPACITY: usize = 4096;
+
+/// A deserialized value shared between callers.
+struct Cached {
+    value: Arc<dyn Any + Send + Sync>,
+    stamp: Option<Stamp>,
+    /// The tick of the last lookup returning the value.
+    used: AtomicU64,
+}
+
+/// The contents of a file read ahead of time, as stored before any middleware runs.
+struct Raw {
+    data: Vec<u8>,
+    stamp: Option<Stamp>,
+}
+
+/// Identifies the version of a file a cached value was read from.
+///
+/// Stamps are taken before a file is read, so a value is never recorded as fresher than the
+/// data it was decoded from when another thread or process writes concurrently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Stamp {
+    pub(crate) fn of(fs: &dyn Filesystem, path: &Path) -> Option<Self> {
+        let metadata = fs.metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified,
+            len: metadata.len,
+        })
+    }
+}
+
+/// Read-through cache of deserialized values, keyed by file path.
+///
+/// Entries are invalidated when the file is written or removed through the `Config`, and
+/// when its modification time or size changes in the filesystem.
+///
+/// Readers share the locks and only hold them for a map lookup: files are inspected before
+/// locking, so a thread writing a value never waits behind readers doing I/O.
+///
+/// At most `capacity` values are kept, the least recently used one is evicted to make room.
+/// Preloaded files are kept as stored, so decrypted contents are never cached, and are
+/// dropped once read or when the capacity is reached.
+pub(crate) struct Cache {
+    entries: RwLock<HashMap<PathBuf, Cached>>,
+    raw: RwLock<HashMap<PathBuf, Raw>>,
+    capacity: usize,
+    /// Incremented on every lookup, orders values by their last use.
+    clock: AtomicU64,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Cache {
+    /// Creates a cache keeping at most `capacity` values.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: RwLock::default(),
+            raw: RwLock::default(),
+            capacity,
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the cached value for a path if it is still fresh and has the requested type.
+    pub(crate) fn get<T: Send + Sync + 'static>(
+        &self,
+        fs: &dyn Filesystem,
+        path: &Path,
+    ) -> Option<Arc<T>> {
+        let stamp = Stamp::of(fs, path);
+        let value = {
+            let entries = self.entries.read().unwrap();
+            let cached = entries.get(path).filter(|cached| cached.stamp == stamp)?;
+            cached.used.store(self.tick(), Ordering::Relaxed);
+            cached.value.clone()
+        };
+        value.downcast::<T>().ok()
+    }
+
+    /// Stores a value read from a path, with the stamp taken before reading it.
+    pub(crate) fn insert<T: Send + Sync + 'static>(
+        &self,
+        path: &Path,
+        stamp: Option<Stamp>,
+        value: Arc<T>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let cached = Cached {
+            value,
+            stamp,
+            used: AtomicU64::new(self.tick()),
+        };
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(path) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.used.load(Ordering::Relaxed))
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(path.to_path_buf(), cached);
+    }
+
+    /// Takes the preloaded contents of a path if the file did not change since.
+    pub(crate) fn take_raw(&self, fs: &dyn Filesystem, path: &Path) -> Option<Vec<u8>> {
+        let stamp = Stamp::of(fs, path);
+        let raw = self.raw.write().unwrap().remove(path)?;
+        (raw.stamp == stamp).then_some(raw.data)
+    }
+
+    /// Stores the raw contents of a path, with the stamp taken before reading it.
+    ///
+    /// The contents are dropped if the cache is full.
+    pub(crate) fn insert_raw(&self, path: &Path, stamp: Option<Stamp>, data: Vec<u8>) {
+        let mut raw = self.raw.write().unwrap();
+        if raw.len() < self.capacity || raw.contains_key(path) {
+            raw.insert(path.to_path_buf(), Raw { data, stamp });
+        }
+    }
+
+    /// Drops the values cached for a path.
+    pub(crate) fn invalidate(&self, path: &Path) {
+        self.entries.write().unwrap().remove(path);
+        self.raw.write().unwrap().remove(path);
+    }
+
+    /// Drops every cached value and preloaded file.
+    pub(crate) fn clear(&self) {
+        self.entries.write().unwrap().clear();
+        self.raw.write().unwrap().clear();
+    }
+
+    /// Advances the clock ordering lookups.
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+/// Resolved file paths of keys that already passed normalization and validation.
+#[derive(Default)]
+pub(crate) struct PathCache {
+    paths: RwLock<HashMap<FileType, HashMap<String, PathBuf>>>,
+}
+
+impl PathCache {
+    /// Returns the resolved path of a key, if it was resolved before.
+    pub(crate) fn get(&self, key: &str, file_type: FileType) -> Option<PathBuf> {
+        let paths = self.paths.read().unwrap();
+        paths.get(&file_type)?.get(key).cloned()
+    }
+
+    /// Stores the resolved path of a key.
+    ///
+    /// Every path is forgotten once [`PATH_CAPACITY`] are stored, resolving them again is cheap.
+    pub(crate) fn insert(&self, key: &str, file_type: FileType, path: PathBuf) {
+        let mut paths = self.paths.write().unwrap();
+        if paths.values().map(HashMap::len).sum::<usize>() >= PATH_CAPACITY {
+            paths.clear();
+        }
+        paths
+            .entry(file_type)
+            .or_default()
+            .insert(key.to_string(), path);
+    }
+
+    /// Forgets every resolved path, used when the key policy changes.
+    pub(crate) fn clear(&self) {
+        self.paths.write().unwrap().clear();
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use std::sync::Arc;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::Config;
+
+    const THREADS: usize = 8;
+    const ROUNDS: usize = 100;
+
+    /// A value whose fields are only consistent if it was read in one piece.
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Value {
+        writer: usize,
+        round: usize,
+        padding: String,
+    }
+
+    impl Value {
+        fn new(writer: usize, round: usize) -> Self {
+            Self {
+                writer,
+                round,
+                padding: "x".repeat(round * 64),
+            }
+        }
+
+        fn assert_whole(&self) {
+            assert!(self.writer < THREADS, "unknown writer {}", self.writer);
+            assert_eq!(self.padding.len(), self.round * 64, "torn read");
+        }
+    }
+
+    #[test]
+    fn concurrent_reads_and_writes() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Config>();
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        config.set_json("shared", Value::new(0, 0)).unwrap();
+        std::thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let config = &config;
+                scope.spawn(move || {
+                    let own = format!("thread-{thread}");
+                    for round in 0..ROUNDS {
+                        config
+                            .set_json("shared", Value::new(thread, round))
+                            .unwrap();
+                        config.get_json::<Value>("shared").unwrap().assert_whole();
+                        let cached: Arc<Value> = config.cached_json("shared").unwrap();
+                        cached.assert_whole();
+
+                        config.set_json(&own, Value::new(thread, round)).unwrap();
+                        let value: Value = config.get_json(&own).unwrap();
+                        assert_eq!((value.writer, value.round), (thread, round));
+                    }
+                });
+            }
+        });
+
+        let shared: Value = config.get_json("shared").unwrap();
+        shared.assert_whole();
+        assert_eq!(shared.round, ROUNDS - 1);
+        for thread in 0..THREADS {
+            let value: Value = config.get_json(&format!("thread-{thread}")).unwrap();
+            assert_eq!((value.writer, value.round), (thread, ROUNDS - 1));
+            let cached: Arc<Value> = config.cached_json(&format!("thread-{thread}")).unwrap();
+            assert_eq!(cached.round, ROUNDS - 1);
+        }
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None)
+            .unwrap()
+            .with_cache_capacity(2);
+        for key in ["a", "b", "c"] {
+            config.set_json(key, key).unwrap();
+        }
+        let a: Arc<String> = config.cached_json("a").unwrap();
+        let b: Arc<String> = config.cached_json("b").unwrap();
+        assert!(Arc::ptr_eq(&a, &config.cached_json("a").unwrap()));
+        let _: Arc<String> = config.cached_json("c").unwrap();
+
+        assert!(Arc::ptr_eq(&a, &config.cached_json("a").unwrap()));
+        assert!(!Arc::ptr_eq(&b, &config.cached_json("b").unwrap()));
+        assert_eq!(config.cache.entries.read().unwrap().len(), 2);
+
+        config.clear_cache();
+        assert!(!Arc::ptr_eq(&a, &config.cached_json("a").unwrap()));
+    }
+}
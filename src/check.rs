@@ -0,0 +1,216 @@
+use std::{fmt, path::Path, sync::Arc};
+
+use serde::{de::DeserializeOwned, de::IgnoredAny, Serialize};
+
+use crate::{deserialize, parse::ParseOptions, utils::FileType, Config, Error};
+
+/// Deserializes the contents of a file into a registered type.
+type Validate = Arc<dyn Fn(&str, &Path, &str, &ParseOptions) -> Result<(), Error> + Send + Sync>;
+
+/// A check run against a file by [`Config::check`].
+#[derive(Clone)]
+pub(crate) struct Schema {
+    key: String,
+    file_type: FileType,
+    validate: Validate,
+}
+
+/// The result of checking every file in a store.
+///
+/// The report serializes to a stable structure, so it can be logged or handed to other tools.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+    /// How many files were checked.
+    pub checked: usize,
+    /// The problems found, sorted by path.
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    /// Determines if no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// A problem found in a stored file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+    /// The path of the file relative to the version root, using `/` as separator.
+    pub path: String,
+    /// The key of the file.
+    pub key: String,
+    /// What is wrong with the file.
+    #[serde(flatten)]
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match &self.kind {
+            IssueKind::Unreadable(reason) => write!(f, "could not be read: {reason}"),
+            IssueKind::Truncated => write!(f, "is truncated"),
+            IssueKind::Invalid(reason) => write!(f, "could not be parsed: {reason}"),
+            IssueKind::Schema(reason) => write!(f, "does not match its schema: {reason}"),
+            IssueKind::Permissions(mode) => write!(f, "is writable by other users ({mode:o})"),
+            IssueKind::Owner(uid) => write!(f, "is owned by another user ({uid})"),
+        }
+    }
+}
+
+/// The kind of problem found in a stored file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum IssueKind {
+    /// The file could not be read or decoded.
+    Unreadable(String),
+    /// The file ends before its contents are complete.
+    Truncated,
+    /// The file is not valid in its format.
+    Invalid(String),
+    /// The file could not be deserialized into the type registered with
+    /// [`Config::with_schema`].
+    Schema(String),
+    /// The file can be modified by other users, holding its permission bits.
+    Permissions(u32),
+    /// The file belongs to a different user than the store, holding its owner's id.
+    Owner(u32),
+}
+
+impl Config {
+    /// Registers the type a file must deserialize into for [`Config::check`] to accept it.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType};
+    /// # use serde::Deserialize;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// #[derive(Deserialize)]
+    /// struct Theme { accent: String }
+    ///
+    /// let config = Config::new("org.example.Demo", 1, None)?
+    ///     .with_schema::<Theme>("theme", FileType::Json);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_schema<T: DeserializeOwned>(mut self, key: &str, file_type: FileType) -> Self {
+        self.schemas.push(Schema {
+            key: key.to_string(),
+            file_type,
+            validate: Arc::new(move |key, path, data, options| {
+                deserialize::<T>(key, file_type, path, data, options).map(drop)
+            }),
+        });
+        self
+    }
+
+    /// Checks every file in the version root and its scopes.
+    ///
+    /// Files in a known format are parsed and, when a type was registered with
+    /// [`Config::with_schema`], deserialized into it. On Unix, files writable by other users
+    /// or owned by a different user than the store are reported as well. Nothing is modified,
+    /// so this is safe to run on startup before deciding whether to trust the store.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let report = config.check()?;
+    /// for issue in &report.issues {
+    ///     eprintln!("{issue}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Report` or an `Error` if the store could not be traversed.
+    pub fn check(&self) -> Result<Report, Error> {
+        self.flush()?;
+        #[cfg(unix)]
+        let owner = {
+            use std::os::unix::fs::MetadataExt;
+            std::fs::metadata(&self.root)
+                .map_err(|err| Error::io(&self.root, err))?
+                .uid()
+        };
+
+        let mut report = Report::default();
+        for entry in self.walk()? {
+            let entry = entry?;
+            report.checked += 1;
+            let mut issue = |kind| {
+                report.issues.push(Issue {
+                    path: self.relative(&entry.path),
+                    key: entry.key.clone(),
+                    kind,
+                })
+            };
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                if let Ok(metadata) = std::fs::symlink_metadata(&entry.path) {
+                    if metadata.mode() & 0o002 != 0 {
+                        issue(IssueKind::Permissions(metadata.mode() & 0o7777));
+                    }
+                    if metadata.uid() != owner {
+                        issue(IssueKind::Owner(metadata.uid()));
+                    }
+                }
+            }
+
+            if entry.file_type == FileType::Plain {
+                continue;
+            }
+            let data = match self.read_file(&entry.key, entry.file_type, &entry.path) {
+                Ok(data) => data,
+                Err(err) => {
+                    issue(IssueKind::Unreadable(err.to_string()));
+                    continue;
+                }
+            };
+            match deserialize::<IgnoredAny>(
+                &entry.key,
+                entry.file_type,
+                &entry.path,
+                &data,
+                &self.parse,
+            ) {
+                Ok(_) => {}
+                Err(Error::Truncated { .. }) => {
+                    issue(IssueKind::Truncated);
+                    continue;
+                }
+                Err(err) => {
+                    issue(IssueKind::Invalid(err.to_string()));
+                    continue;
+                }
+            }
+            for schema in &self.schemas {
+                let matches = schema.file_type == entry.file_type
+                    && self
+                        .path(&schema.key, schema.file_type)
+                        .is_ok_and(|path| path == entry.path);
+                if matches {
+                    if let Err(err) = (schema.validate)(&entry.key, &entry.path, &data, &self.parse)
+                    {
+                        issue(IssueKind::Schema(err.to_string()));
+                    }
+                }
+            }
+        }
+        report.issues.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(report)
+    }
+}
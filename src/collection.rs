@@ -0,0 +1,605 @@
+#[cfg(feature = "json")]
+use std::collections::BTreeMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    cache::Stamp,
+    entry::{is_temporary, split_name},
+    formats,
+    traits::{Get, Set},
+    utils::FileType,
+    Config, Error, LibsetResultExt,
+};
+
+/// How [`Collection::import_json`] combines imported items with the stored ones.
+#[cfg(feature = "json")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Imported items replace the stored items with the same id, other stored items are
+    /// kept.
+    #[default]
+    Overwrite,
+    /// Stored items are kept, only items with a new id are imported.
+    KeepExisting,
+    /// The collection ends up holding exactly the imported items, stored items missing from
+    /// the document are removed.
+    Replace,
+}
+
+/// How many items [`Collection::cached`] keeps parsed unless set otherwise.
+pub const COLLECTION_CACHE_CAPACITY: usize = 64;
+
+/// The id of the index file within the directory of a collection.
+#[cfg(feature = "json")]
+const INDEX_ID: &str = ".index";
+
+/// Function computing the summary of an item kept in the index.
+#[cfg(feature = "json")]
+type Summarize<'a, T> = Box<dyn Fn(&T) -> Result<serde_json::Value, serde_json::Error> + 'a>;
+
+/// A directory holding one file per item, created with [`Config::collection`].
+///
+/// Each item is stored as `<name>/<id>.<extension>`, such as `connections/<uuid>.toml`, so
+/// items can be added, edited and removed by hand or by other tools one file at a time.
+/// Files starting with a dot are not items.
+pub struct Collection<'a, T> {
+    config: &'a Config,
+    name: String,
+    file_type: FileType,
+    #[cfg(feature = "json")]
+    index: Option<Summarize<'a, T>>,
+    cache: ItemCache<T>,
+}
+
+/// Items parsed by [`Collection::cached`].
+///
+/// The most recently used items are kept alive, older ones are only found again while a
+/// caller still holds them.
+struct ItemCache<T> {
+    capacity: usize,
+    state: Mutex<CacheState<T>>,
+}
+
+struct CacheState<T> {
+    /// Every item handed out, with the version of the file it was parsed from.
+    items: HashMap<String, (Weak<T>, Option<Stamp>)>,
+    /// The most recently used items, most recent first.
+    recent: VecDeque<(String, Arc<T>)>,
+}
+
+impl<T> ItemCache<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState {
+                items: HashMap::new(),
+                recent: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Returns an item if it is still alive and its file didn't change.
+    fn get(&self, id: &str, stamp: Option<Stamp>) -> Option<Arc<T>> {
+        let mut state = self.state.lock().unwrap();
+        let item = match state.items.get(id) {
+            Some((item, cached)) if *cached == stamp => item.upgrade()?,
+            _ => return None,
+        };
+        state.touch(id, item.clone(), self.capacity);
+        Some(item)
+    }
+
+    /// Stores an item parsed from a file, with the stamp taken before reading it.
+    fn insert(&self, id: &str, stamp: Option<Stamp>, item: Arc<T>) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .items
+            .insert(id.to_string(), (Arc::downgrade(&item), stamp));
+        state.touch(id, item, self.capacity);
+        // Forget items dropped by every caller once they outnumber the live ones.
+        if state.items.len() > 2 * self.capacity.max(16) {
+            state.items.retain(|_, (item, _)| item.strong_count() > 0);
+        }
+    }
+
+    /// Drops an item written or removed through the collection.
+    fn invalidate(&self, id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.items.remove(id);
+        state.recent.retain(|(recent, _)| recent != id);
+    }
+}
+
+impl<T> CacheState<T> {
+    /// Moves an item to the front of the recently used items.
+    fn touch(&mut self, id: &str, item: Arc<T>, capacity: usize) {
+        self.recent.retain(|(recent, _)| recent != id);
+        self.recent.push_front((id.to_string(), item));
+        self.recent.truncate(capacity);
+    }
+}
+
+impl Config {
+    /// Opens a collection of items stored one file per item.
+    ///
+    /// Items are stored in the first format of the resolution order, toml when it is
+    /// enabled, see [`Config::with_format_order`] and [`Collection::with_file_type`].
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # use serde::{Deserialize, Serialize};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Connection { ssid: String, autoconnect: bool }
+    ///
+    /// let config = Config::new("org.example.Network", 1, None)?;
+    /// let connections = config.collection::<Connection>("connections");
+    /// connections.insert(
+    ///     "7d0f3a52",
+    ///     &Connection { ssid: "home".to_string(), autoconnect: true },
+    /// )?;
+    /// for connection in connections.iter()? {
+    ///     let (id, connection) = connection?;
+    ///     println!("{id}: {}", connection.ssid);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the directory holding the items.
+    ///
+    /// # Returns
+    ///
+    /// The `Collection` handle, files are only read and written when items are used.
+    pub fn collection<T>(&self, name: &str) -> Collection<'_, T> {
+        let file_type = self
+            .format_order
+            .first()
+            .copied()
+            .unwrap_or_else(|| formats::default_order()[0]);
+        Collection {
+            config: self,
+            name: name.to_string(),
+            file_type,
+            #[cfg(feature = "json")]
+            index: None,
+            cache: ItemCache::new(COLLECTION_CACHE_CAPACITY),
+        }
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> Collection<'a, T> {
+    /// Sets the format items are stored in.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_type` - The format of the item files, plain files are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Collection`.
+    pub fn with_file_type(mut self, file_type: FileType) -> Self {
+        if file_type != FileType::Plain {
+            self.file_type = file_type;
+        }
+        self
+    }
+
+    /// Sets how many recently used items [`Collection::cached`] keeps parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of items kept, [`COLLECTION_CACHE_CAPACITY`] by default. With
+    ///   zero, items are only shared while a caller holds them.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Collection`.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache = ItemCache::new(capacity);
+        self
+    }
+
+    /// Stores an item, replacing the item with the same id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The identifier of the item, used as its file name.
+    /// * `value` - The item to store.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the id is not a valid file name or the
+    /// item could not be written.
+    pub fn insert(&self, id: &str, value: &T) -> Result<(), Error> {
+        self.write(&[(id, value)])
+    }
+
+    /// Returns an item.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The identifier of the item.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the item, `None` if it isn't stored, or an `Error` if it could
+    /// not be read.
+    pub fn get(&self, id: &str) -> Result<Option<T>, Error> {
+        self.config
+            .get(&self.key(id)?, self.file_type)
+            .ignore_missing()
+    }
+
+    /// Returns a shared instance of an item, parsing its file only when needed.
+    ///
+    /// Callers receive the same instance until the file is written through the collection
+    /// or changes on disk. The most recently used items stay parsed, see
+    /// [`Collection::with_cache_capacity`], so large collections can be browsed from their
+    /// summaries while only the items opened are read.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The identifier of the item.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the item, `None` if it isn't stored, or an `Error` if it could
+    /// not be read.
+    pub fn cached(&self, id: &str) -> Result<Option<Arc<T>>, Error> {
+        let key = self.key(id)?;
+        let path = self.config.resolve(&key, self.file_type)?;
+        let stamp = Stamp::of(self.config.fs.as_ref(), &path);
+        if let Some(item) = self.cache.get(id, stamp) {
+            return Ok(Some(item));
+        }
+        let Some(item) = self.get(id)? else {
+            return Ok(None);
+        };
+        let item = Arc::new(item);
+        self.cache.insert(id, stamp, item.clone());
+        Ok(Some(item))
+    }
+
+    /// Removes an item.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The identifier of the item.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `true` if the item was stored, or an `Error` if it could not be
+    /// removed.
+    pub fn remove(&self, id: &str) -> Result<bool, Error> {
+        let key = self.key(id)?;
+        self.cache.invalidate(id);
+        #[cfg(feature = "json")]
+        if self.index.is_some() {
+            let index_key = self.index_key();
+            let _guard = self.config.write_guard(&index_key)?;
+            let removed = self
+                .config
+                .remove(&key, self.file_type)
+                .ignore_missing()?
+                .is_some();
+            if let Some(mut index) = self.read_index()? {
+                if index.remove(id).is_some() {
+                    self.config.set_json(&index_key, &index)?;
+                }
+            }
+            return Ok(removed);
+        }
+        self.config
+            .remove(&key, self.file_type)
+            .ignore_missing()
+            .map(|removed| removed.is_some())
+    }
+
+    /// Lists the identifiers of the stored items.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the identifiers sorted by name, empty if the collection was never
+    /// stored, or an `Error` if its directory could not be listed.
+    pub fn ids(&self) -> Result<Vec<String>, Error> {
+        let dir = self.dir()?;
+        let paths = match self.config.fs.list(&dir) {
+            Ok(paths) => paths,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(Error::io(&dir, err)),
+        };
+        let mut ids: Vec<String> = paths
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str())
+            .filter(|name| !name.starts_with('.') && !is_temporary(Path::new(name)))
+            .map(split_name)
+            .filter(|(_, file_type)| *file_type == self.file_type)
+            .map(|(id, _)| id)
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Iterates over the stored items, sorted by identifier.
+    ///
+    /// Items are read lazily, an item that can't be read is reported without ending the
+    /// iteration.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an iterator over the identifiers and items, or an `Error` if the
+    /// directory could not be listed.
+    pub fn iter(&self) -> Result<impl Iterator<Item = Result<(String, T), Error>> + 'a, Error> {
+        let config = self.config;
+        let name = self.name.clone();
+        let file_type = self.file_type;
+        Ok(self.ids()?.into_iter().map(move |id| {
+            let value = config.get(&format!("{name}/{id}"), file_type)?;
+            Ok((id, value))
+        }))
+    }
+
+    /// Stores items, together with their summaries when the collection keeps an index.
+    ///
+    /// A single item without an index goes through the write strategy of the `Config`, any
+    /// other write is committed as one batch.
+    fn write(&self, items: &[(&str, &T)]) -> Result<(), Error> {
+        let keys = items
+            .iter()
+            .map(|(id, _)| self.key(id))
+            .collect::<Result<Vec<_>, Error>>()?;
+        for (id, _) in items {
+            self.cache.invalidate(id);
+        }
+        let dir = self.dir()?;
+        self.config
+            .fs
+            .create_dir_all(&dir)
+            .map_err(|err| Error::io(&dir, err))?;
+        #[cfg(feature = "json")]
+        if let Some(summarize) = &self.index {
+            let index_key = self.index_key();
+            let _guard = self.config.write_guard(&index_key)?;
+            let mut index = self.read_index()?.unwrap_or_default();
+            let mut batch = self.config.batch();
+            for ((id, value), key) in items.iter().zip(&keys) {
+                let summary = summarize(value).map_err(|source| self.index_error(source))?;
+                index.insert(id.to_string(), summary);
+                batch.set(key, self.file_type, *value)?;
+            }
+            batch.set(&index_key, FileType::Json, &index)?;
+            return batch.commit();
+        }
+        if let [(_, value)] = items {
+            return self.config.set(&keys[0], self.file_type, *value);
+        }
+        let mut batch = self.config.batch();
+        for ((_, value), key) in items.iter().zip(&keys) {
+            batch.set(key, self.file_type, *value)?;
+        }
+        batch.commit()
+    }
+
+    /// Returns the directory holding the items.
+    fn dir(&self) -> Result<PathBuf, Error> {
+        Ok(self
+            .config
+            .path
+            .join(self.config.validator.validate(&self.name)?))
+    }
+
+    /// Returns the key an item is stored under.
+    fn key(&self, id: &str) -> Result<String, Error> {
+        if id.is_empty() || id.starts_with('.') || id.contains(['/', '\\']) {
+            return Err(Error::InvalidKey {
+                key: id.to_string(),
+                reason: "item ids must be a single file name".to_string(),
+            });
+        }
+        Ok(format!("{}/{id}", self.name))
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'a, T: Serialize + DeserializeOwned> Collection<'a, T> {
+    /// Combines every item into a single json document, such as for sharing or backups.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Network", 1, None)?;
+    /// let connections = config.collection::<serde_json::Value>("connections");
+    /// let backup = connections.export_json()?;
+    /// std::fs::write("connections.json", backup.to_string()).unwrap();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an object with every item by id, or an `Error` if an item could
+    /// not be read or converted.
+    pub fn export_json(&self) -> Result<serde_json::Value, Error> {
+        let mut document = serde_json::Map::new();
+        for item in self.iter()? {
+            let (id, item) = item?;
+            let value = serde_json::to_value(item).map_err(|source| self.json_error(source))?;
+            document.insert(id, value);
+        }
+        Ok(serde_json::Value::Object(document))
+    }
+
+    /// Stores the items of a document made by [`Collection::export_json`].
+    ///
+    /// Every item is checked before anything is written, and the items are written in a
+    /// single [`Batch`](crate::Batch), so a document with an invalid item leaves the
+    /// collection untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - An object holding the items by id.
+    /// * `policy` - How the items are combined with the stored ones.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of items written, or an `Error` if the document isn't
+    /// an object of items, an id is invalid or the items could not be written.
+    pub fn import_json(
+        &self,
+        document: serde_json::Value,
+        policy: MergePolicy,
+    ) -> Result<usize, Error> {
+        let items: BTreeMap<String, T> =
+            serde_json::from_value(document).map_err(|source| self.json_error(source))?;
+        for id in items.keys() {
+            self.key(id)?;
+        }
+        let stored = self.ids()?;
+        let written: Vec<(&str, &T)> = items
+            .iter()
+            .filter(|(id, _)| policy != MergePolicy::KeepExisting || !stored.contains(id))
+            .map(|(id, item)| (id.as_str(), item))
+            .collect();
+        if !written.is_empty() {
+            self.write(&written)?;
+        }
+        if policy == MergePolicy::Replace {
+            for id in stored.iter().filter(|id| !items.contains_key(*id)) {
+                self.remove(id)?;
+            }
+        }
+        Ok(written.len())
+    }
+
+    /// Keeps an index of the items in `<name>/.index.json`, holding a summary of each item.
+    ///
+    /// List views can then show every item from [`Collection::summaries`] without opening
+    /// each file. The index is written together with the item in a single
+    /// [`Batch`](crate::Batch), under the write guard of the index, so it stays in step with
+    /// the items written through the collection. Rebuild it with [`Collection::rebuild_index`]
+    /// after items are edited by other means.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # use serde::{Deserialize, Serialize};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Connection { ssid: String, password: String }
+    ///
+    /// let config = Config::new("org.example.Network", 1, None)?;
+    /// let connections = config
+    ///     .collection::<Connection>("connections")
+    ///     .with_index(|connection| connection.ssid.clone());
+    /// for (id, ssid) in connections.summaries::<String>()? {
+    ///     println!("{id}: {ssid}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `summarize` - Function computing the summary kept for an item.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Collection`.
+    pub fn with_index<S: Serialize>(mut self, summarize: impl Fn(&T) -> S + 'a) -> Self {
+        self.index = Some(Box::new(move |item| serde_json::to_value(summarize(item))));
+        self
+    }
+
+    /// Returns the summary of every item, by id, from the index.
+    ///
+    /// The index is built from the items when it doesn't exist yet.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the summaries sorted by id, or an `Error` if the index could not
+    /// be read or built, or if a summary doesn't match the type.
+    pub fn summaries<S: DeserializeOwned>(&self) -> Result<BTreeMap<String, S>, Error> {
+        let index = match self.read_index()? {
+            Some(index) => index,
+            None => self.rebuild_index()?,
+        };
+        index
+            .into_iter()
+            .map(|(id, summary)| {
+                let summary =
+                    serde_json::from_value(summary).map_err(|source| self.index_error(source))?;
+                Ok((id, summary))
+            })
+            .collect()
+    }
+
+    /// Rebuilds the index from the stored items.
+    ///
+    /// Does nothing unless the collection keeps an index, see [`Collection::with_index`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new index, or an `Error` if an item could not be read or the
+    /// index could not be written.
+    pub fn rebuild_index(&self) -> Result<BTreeMap<String, serde_json::Value>, Error> {
+        let Some(summarize) = &self.index else {
+            return Ok(BTreeMap::new());
+        };
+        let index_key = self.index_key();
+        let _guard = self.config.write_guard(&index_key)?;
+        let mut index = BTreeMap::new();
+        for item in self.iter()? {
+            let (id, item) = item?;
+            let summary = summarize(&item).map_err(|source| self.index_error(source))?;
+            index.insert(id, summary);
+        }
+        let dir = self.dir()?;
+        self.config
+            .fs
+            .create_dir_all(&dir)
+            .map_err(|err| Error::io(&dir, err))?;
+        self.config.set_json(&index_key, &index)?;
+        Ok(index)
+    }
+
+    /// Returns the key the index is stored under.
+    fn index_key(&self) -> String {
+        format!("{}/{INDEX_ID}", self.name)
+    }
+
+    /// Builds the error for items that could not be converted to or from json.
+    fn json_error(&self, source: serde_json::Error) -> Error {
+        Error::Json {
+            key: self.name.clone(),
+            path: self.dir().unwrap_or_default(),
+            source,
+            #[cfg(feature = "miette")]
+            source_code: None,
+        }
+    }
+
+    /// Builds the error for a summary that could not be converted.
+    fn index_error(&self, source: serde_json::Error) -> Error {
+        let key = self.index_key();
+        Error::Json {
+            path: self.config.path(&key, FileType::Json).unwrap_or_default(),
+            key,
+            source,
+            #[cfg(feature = "miette")]
+            source_code: None,
+        }
+    }
+
+    /// Reads the index, `None` if it wasn't written yet.
+    fn read_index(&self) -> Result<Option<BTreeMap<String, serde_json::Value>>, Error> {
+        self.config.get_json(&self.index_key()).ignore_missing()
+    }
+}
@@ -0,0 +1,217 @@
+use std::{fmt, str::FromStr};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::units::ParseValueError;
+
+/// The CSS basic colors, accepted by name in any case.
+const NAMED_COLORS: &[(&str, Color)] = &[
+    ("black", Color::rgb(0x00, 0x00, 0x00)),
+    ("silver", Color::rgb(0xc0, 0xc0, 0xc0)),
+    ("gray", Color::rgb(0x80, 0x80, 0x80)),
+    ("grey", Color::rgb(0x80, 0x80, 0x80)),
+    ("white", Color::rgb(0xff, 0xff, 0xff)),
+    ("maroon", Color::rgb(0x80, 0x00, 0x00)),
+    ("red", Color::rgb(0xff, 0x00, 0x00)),
+    ("purple", Color::rgb(0x80, 0x00, 0x80)),
+    ("fuchsia", Color::rgb(0xff, 0x00, 0xff)),
+    ("magenta", Color::rgb(0xff, 0x00, 0xff)),
+    ("green", Color::rgb(0x00, 0x80, 0x00)),
+    ("lime", Color::rgb(0x00, 0xff, 0x00)),
+    ("olive", Color::rgb(0x80, 0x80, 0x00)),
+    ("yellow", Color::rgb(0xff, 0xff, 0x00)),
+    ("navy", Color::rgb(0x00, 0x00, 0x80)),
+    ("blue", Color::rgb(0x00, 0x00, 0xff)),
+    ("teal", Color::rgb(0x00, 0x80, 0x80)),
+    ("aqua", Color::rgb(0x00, 0xff, 0xff)),
+    ("cyan", Color::rgb(0x00, 0xff, 0xff)),
+    ("orange", Color::rgb(0xff, 0xa5, 0x00)),
+    ("transparent", Color::rgba(0x00, 0x00, 0x00, 0x00)),
+];
+
+/// An sRGB color with an alpha channel, written in CSS form such as `#7a7af9`.
+///
+/// Hex colors with 3, 4, 6 or 8 digits, `rgb(r, g, b)` and `rgba(r, g, b, a)` with the
+/// alpha as a number from 0 to 1 or a percentage, and the CSS basic color names are
+/// accepted. Colors are written as `#rrggbb`, or `#rrggbbaa` when they are not opaque.
+///
+/// ```
+/// use libset::Color;
+///
+/// let accent: Color = "rgb(122, 122, 249)".parse().unwrap();
+/// assert_eq!(accent, Color::rgb(0x7a, 0x7a, 0xf9));
+/// assert_eq!(accent.to_string(), "#7a7af9");
+/// assert_eq!("#fff8".parse::<Color>().unwrap().to_string(), "#ffffff88");
+/// assert_eq!("Orange".parse::<Color>().unwrap(), Color::rgb(255, 165, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    /// The red channel.
+    pub r: u8,
+    /// The green channel.
+    pub g: u8,
+    /// The blue channel.
+    pub b: u8,
+    /// The alpha channel, 255 is opaque.
+    pub a: u8,
+}
+
+impl Color {
+    /// Creates an opaque color.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Creates a color with an alpha channel.
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Returns whether the color has no transparency.
+    pub fn is_opaque(self) -> bool {
+        self.a == 255
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::rgb(0, 0, 0)
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<[u8; 4]> for Color {
+    fn from([r, g, b, a]: [u8; 4]) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<Color> for [u8; 4] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if !self.is_opaque() {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseValueError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let failed = |reason: &str| ParseValueError::new("color", input, reason);
+        let text = input.trim();
+        if let Some(hex) = text.strip_prefix('#') {
+            return parse_hex(hex)
+                .ok_or_else(|| failed("expected 3, 4, 6 or 8 hexadecimal digits after '#'"));
+        }
+        let lower = text.to_ascii_lowercase();
+        let arguments = lower
+            .strip_prefix("rgba(")
+            .or_else(|| lower.strip_prefix("rgb("));
+        if let Some(arguments) = arguments {
+            return arguments
+                .strip_suffix(')')
+                .and_then(parse_functional)
+                .ok_or_else(|| {
+                    failed("expected rgb(r, g, b) or rgba(r, g, b, a) with channels from 0 to 255 and alpha from 0 to 1")
+                });
+        }
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == lower)
+            .map(|(_, color)| *color)
+            .ok_or_else(|| failed("expected a hex color, rgb(), rgba() or a CSS basic color name"))
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ColorVisitor)
+    }
+}
+
+/// Deserializes a [`Color`] from any of its string forms.
+struct ColorVisitor;
+
+impl Visitor<'_> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a color such as \"#7a7af9\", \"rgb(122, 122, 249)\" or \"orange\"")
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<Color, E> {
+        text.parse().map_err(E::custom)
+    }
+}
+
+/// Parses the digits of a hex color, expanding the short forms.
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channels: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|digit| digit as u8 * 0x11))
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some(match channels[..] {
+        [r, g, b] => Color::rgb(r, g, b),
+        [r, g, b, a] => Color::rgba(r, g, b, a),
+        _ => return None,
+    })
+}
+
+/// Parses the comma-separated arguments of `rgb()` or `rgba()`.
+fn parse_functional(arguments: &str) -> Option<Color> {
+    let arguments: Vec<&str> = arguments.split(',').map(str::trim).collect();
+    let (channels, alpha) = match arguments[..] {
+        [r, g, b] => ([r, g, b], None),
+        [r, g, b, a] => ([r, g, b], Some(a)),
+        _ => return None,
+    };
+    let [r, g, b] = channels.map(|channel| channel.parse::<u8>().ok());
+    let a = match alpha {
+        None => 255,
+        Some(alpha) => {
+            let (alpha, scale) = match alpha.strip_suffix('%') {
+                Some(percent) => (percent, 100.0),
+                None => (alpha, 1.0),
+            };
+            let alpha: f64 = alpha.parse().ok()?;
+            if !(0.0..=scale).contains(&alpha) {
+                return None;
+            }
+            (alpha / scale * 255.0).round() as u8
+        }
+    };
+    Some(Color::rgba(r?, g?, b?, a))
+}
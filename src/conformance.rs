@@ -0,0 +1,251 @@
+//! A test suite for [`Filesystem`] implementations.
+//!
+//! Storages backed by a database, a registry or a remote service must behave like the local
+//! filesystem for `Config` to work on top of them. [`run`] checks reads, writes, removals,
+//! listings, the reported errors and the atomicity of overwrites, panicking on the first
+//! difference so it can be called from a test.
+//!
+//! ```no_run
+//! # use libset::MemoryFilesystem;
+//! # use std::path::Path;
+//! #[test]
+//! fn conforms() {
+//!     libset::conformance::run(&MemoryFilesystem::default(), Path::new("/store"));
+//! }
+//! ```
+
+use std::{fmt::Debug, io, path::Path};
+
+use crate::Filesystem;
+
+/// How many times a file is overwritten while it is being read in the atomicity check.
+const OVERWRITES: usize = 200;
+
+/// Checks that a storage behaves like the local filesystem, panicking if it does not.
+///
+/// # Arguments
+///
+/// * `fs` - The storage to check.
+/// * `dir` - An empty directory of the storage the checks can write to, created if missing.
+pub fn run(fs: &dyn Filesystem, dir: &Path) {
+    fs.create_dir_all(dir)
+        .expect("creating the directory failed");
+    missing_files(fs, dir);
+    write(fs, dir);
+    write_in_place(fs, dir);
+    remove(fs, dir);
+    list(fs, dir);
+    rename(fs, dir);
+    atomic_overwrites(fs, dir);
+}
+
+/// Files that were never written are absent and fail with `NotFound`.
+fn missing_files(fs: &dyn Filesystem, dir: &Path) {
+    let path = dir.join("missing");
+    assert!(!fs.exists(&path), "a missing file exists");
+    expect_kind(
+        fs.read(&path),
+        io::ErrorKind::NotFound,
+        "reading a missing file",
+    );
+    expect_kind(
+        fs.remove(&path),
+        io::ErrorKind::NotFound,
+        "removing a missing file",
+    );
+    expect_kind(
+        fs.metadata(&path),
+        io::ErrorKind::NotFound,
+        "the metadata of a missing file",
+    );
+    expect_kind(
+        fs.rename(&path, &dir.join("renamed")),
+        io::ErrorKind::NotFound,
+        "renaming a missing file",
+    );
+}
+
+/// Written contents are read back, replaced by later writes and kept by `create_new`.
+fn write(fs: &dyn Filesystem, dir: &Path) {
+    let path = dir.join("write");
+    fs.write(&path, b"first contents", true)
+        .expect("creating a file failed");
+    assert!(fs.exists(&path), "a written file does not exist");
+    assert_eq!(read(fs, &path), b"first contents");
+    assert_eq!(
+        fs.metadata(&path).expect("reading metadata failed").len,
+        14,
+        "the reported size differs from the contents"
+    );
+
+    fs.write(&path, b"second", false)
+        .expect("overwriting a file failed");
+    assert_eq!(read(fs, &path), b"second", "an overwrite kept old contents");
+
+    expect_kind(
+        fs.write(&path, b"third", true),
+        io::ErrorKind::AlreadyExists,
+        "creating a file that exists",
+    );
+    assert_eq!(
+        read(fs, &path),
+        b"second",
+        "a failed create changed the file"
+    );
+    fs.remove(&path).expect("removing a file failed");
+}
+
+/// In-place writes replace the whole contents, like atomic ones.
+fn write_in_place(fs: &dyn Filesystem, dir: &Path) {
+    let path = dir.join("write_in_place");
+    fs.write_in_place(&path, b"first contents", true)
+        .expect("creating a file in place failed");
+    assert_eq!(read(fs, &path), b"first contents");
+
+    fs.write_in_place(&path, b"second", false)
+        .expect("overwriting a file in place failed");
+    assert_eq!(
+        read(fs, &path),
+        b"second",
+        "an in-place overwrite kept old contents"
+    );
+
+    expect_kind(
+        fs.write_in_place(&path, b"third", true),
+        io::ErrorKind::AlreadyExists,
+        "creating a file in place that exists",
+    );
+    fs.remove(&path).expect("removing a file failed");
+}
+
+/// Removed files are absent and can be written again.
+fn remove(fs: &dyn Filesystem, dir: &Path) {
+    let path = dir.join("remove");
+    fs.write(&path, b"contents", false)
+        .expect("writing a file failed");
+    fs.remove(&path).expect("removing a file failed");
+    assert!(!fs.exists(&path), "a removed file exists");
+    expect_kind(
+        fs.read(&path),
+        io::ErrorKind::NotFound,
+        "reading a removed file",
+    );
+    fs.write(&path, b"again", true)
+        .expect("creating a removed file failed");
+    fs.remove(&path).expect("removing a file failed");
+}
+
+/// Listings hold the files directly inside a directory, nested directories apart.
+fn list(fs: &dyn Filesystem, dir: &Path) {
+    let nested = dir.join("nested");
+    fs.create_dir_all(&nested)
+        .expect("creating a directory failed");
+    for name in ["a", "b"] {
+        fs.write(&dir.join(name), name.as_bytes(), false)
+            .expect("writing a file failed");
+    }
+    fs.write(&nested.join("c"), b"c", false)
+        .expect("writing a nested file failed");
+
+    let mut files = fs.list(dir).expect("listing a directory failed");
+    files.sort();
+    assert_eq!(files, vec![dir.join("a"), dir.join("b")]);
+    let dirs = fs.list_dirs(dir).expect("listing directories failed");
+    assert!(
+        dirs.is_empty() || dirs == vec![nested.clone()],
+        "unexpected directories {dirs:?}"
+    );
+    assert_eq!(
+        fs.list(&nested).expect("listing a nested directory failed"),
+        vec![nested.join("c")]
+    );
+
+    fs.remove_dir_all(&nested)
+        .expect("removing a directory failed");
+    assert!(
+        !fs.exists(&nested.join("c")),
+        "a removed directory kept files"
+    );
+    for name in ["a", "b"] {
+        fs.remove(&dir.join(name)).expect("removing a file failed");
+    }
+    assert!(
+        fs.list(dir).expect("listing a directory failed").is_empty(),
+        "removed files are still listed"
+    );
+}
+
+/// Renames move the contents and replace the destination.
+fn rename(fs: &dyn Filesystem, dir: &Path) {
+    let from = dir.join("from");
+    let to = dir.join("to");
+    fs.write(&from, b"moved", false)
+        .expect("writing a file failed");
+    fs.write(&to, b"replaced", false)
+        .expect("writing a file failed");
+    fs.rename(&from, &to).expect("renaming a file failed");
+    assert!(!fs.exists(&from), "a renamed file exists");
+    assert_eq!(read(fs, &to), b"moved", "a rename kept the destination");
+    fs.remove(&to).expect("removing a file failed");
+}
+
+/// Readers see either the old or the new contents while a file is overwritten, never a mix.
+fn atomic_overwrites(fs: &dyn Filesystem, dir: &Path) {
+    let path = dir.join("atomic");
+    let short = b"short".to_vec();
+    let long = vec![b'x'; 64 * 1024];
+    fs.write(&path, &short, false)
+        .expect("writing a file failed");
+
+    std::thread::scope(|scope| {
+        let writer = scope.spawn(|| {
+            for i in 0..OVERWRITES {
+                let data = if i % 2 == 0 { &long } else { &short };
+                fs.write(&path, data, false)
+                    .expect("overwriting a file failed");
+            }
+        });
+        while !writer.is_finished() {
+            let data = read(fs, &path);
+            assert!(
+                data == short || data == long,
+                "a reader saw {} bytes of a partial overwrite",
+                data.len()
+            );
+        }
+    });
+    fs.remove(&path).expect("removing a file failed");
+}
+
+/// Reads a file that must be present.
+fn read(fs: &dyn Filesystem, path: &Path) -> Vec<u8> {
+    fs.read(path)
+        .unwrap_or_else(|err| panic!("reading {} failed: {err}", path.display()))
+}
+
+/// Asserts that an operation failed with an error kind.
+fn expect_kind<T: Debug>(result: io::Result<T>, kind: io::ErrorKind, what: &str) {
+    match result {
+        Err(err) => assert_eq!(err.kind(), kind, "{what} failed with {err}"),
+        Ok(value) => panic!("{what} succeeded with {value:?}, expected {kind:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::MemoryFilesystem;
+
+    #[cfg(feature = "std-fs")]
+    #[test]
+    fn local_filesystem_conforms() {
+        let dir = tempfile::tempdir().unwrap();
+        super::run(&crate::StdFilesystem, &dir.path().join("store"));
+    }
+
+    #[test]
+    fn memory_filesystem_conforms() {
+        super::run(&MemoryFilesystem::default(), Path::new("/store"));
+    }
+}
@@ -0,0 +1,110 @@
+use std::{io::Read, path::PathBuf};
+
+use zeroize::Zeroizing;
+
+use crate::{utils::FileType, Config, Error};
+
+/// Environment variable systemd sets when a service is started with `LoadCredential=`.
+const CREDENTIALS_DIRECTORY: &str = "CREDENTIALS_DIRECTORY";
+
+/// Returns the credentials directory passed by the service manager, if any.
+pub(crate) fn credentials_directory() -> Option<PathBuf> {
+    std::env::var_os(CREDENTIALS_DIRECTORY)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+impl Config {
+    /// Sets the directory plain keys are looked up in before the configuration store.
+    ///
+    /// By default this is the directory in `$CREDENTIALS_DIRECTORY`, which systemd sets for
+    /// services using `LoadCredential=` or `SetCredential=`. A credential named after the key
+    /// takes precedence over the stored file in [`Config::get_plain`] and
+    /// [`Config::get_secret`], so services don't need a separate code path for secrets
+    /// provided by the service manager. Writes always go to the configuration store.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The credentials directory, or `None` to disable the lookup.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_credentials_directory(mut self, dir: Option<PathBuf>) -> Self {
+        self.credentials = dir;
+        self
+    }
+
+    /// Gets a secret stored as a plain file with the given key.
+    ///
+    /// The secret is read from the credentials directory first, see
+    /// [`Config::with_credentials_directory`], and from the configuration store otherwise.
+    /// Unlike [`Config::get_plain`], a single trailing line break is removed, since files
+    /// holding a secret usually end with one. The secret is wiped from memory when the returned
+    /// value is dropped.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// // With `LoadCredential=db-password:/etc/example/db-password` in the unit file.
+    /// let config = Config::new("org.example.Service", 1, None)?;
+    /// let password = config.get_secret("db-password")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file, which is also the name of the credential.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the secret or an `Error` if an error occurred.
+    pub fn get_secret(&self, key: &str) -> Result<Zeroizing<String>, Error> {
+        let mut secret = match self.credential(key)? {
+            Some(secret) => secret,
+            None => {
+                let key_path = self.resolve(key, FileType::Plain)?;
+                Zeroizing::new(self.read_file(key, FileType::Plain, &key_path)?)
+            }
+        };
+        if secret.ends_with('\n') {
+            secret.pop();
+            if secret.ends_with('\r') {
+                secret.pop();
+            }
+        }
+        Ok(secret)
+    }
+
+    /// Reads the credential named after a key, if the service manager provided one.
+    ///
+    /// The value is wiped from memory when dropped.
+    pub(crate) fn credential(&self, key: &str) -> Result<Option<Zeroizing<String>>, Error> {
+        let Some(path) = self.credential_path(key) else {
+            return Ok(None);
+        };
+        let read = |value: &mut String| {
+            let mut file = std::fs::File::open(&path)?;
+            // Reserving the whole file up front avoids leaving copies behind when growing.
+            let len = file.metadata()?.len();
+            value.reserve(usize::try_from(len).unwrap_or_default() + 1);
+            file.read_to_string(value)
+        };
+        let mut value = Zeroizing::new(String::new());
+        match read(&mut value) {
+            Ok(_) => Ok(Some(value)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::for_key(key, FileType::Plain, &path, err)),
+        }
+    }
+
+    /// Returns the path of the credential named after a key.
+    ///
+    /// Credential names are flat, so keys that can't name a single file never match.
+    pub(crate) fn credential_path(&self, key: &str) -> Option<PathBuf> {
+        let dir = self.credentials.as_ref()?;
+        let valid = !key.is_empty() && !key.starts_with('.') && !key.contains(['/', '\\', '\0']);
+        valid.then(|| dir.join(key))
+    }
+}
@@ -0,0 +1,506 @@
+//! Helpers for long-running services that reload their settings while running.
+
+use std::{
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex, RwLock, Weak,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use serde::de::DeserializeOwned;
+use tracing::{info, warn};
+
+use crate::{traits::Get, utils::FileType, Config, Error, Watcher};
+
+/// How long a file must stay unchanged before it is reloaded.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Handle to a value kept up to date with a stored file.
+///
+/// The file stops being watched once the handle is dropped.
+pub struct Reload<T> {
+    current: Arc<RwLock<Option<T>>>,
+    trigger: Option<Arc<Sender<()>>>,
+    thread: Option<JoinHandle<()>>,
+    watcher: Option<Watcher>,
+}
+
+impl<T: Clone> Reload<T> {
+    /// Returns the last value that was loaded successfully.
+    ///
+    /// # Returns
+    ///
+    /// The value, or `None` if the file has never been loaded successfully.
+    pub fn current(&self) -> Option<T> {
+        self.current.read().unwrap().clone()
+    }
+}
+
+impl<T> Reload<T> {
+    /// Re-reads the file even if no change was detected, invoking the callback on success.
+    pub fn reload(&self) {
+        if let Some(trigger) = &self.trigger {
+            trigger.send(()).ok();
+        }
+    }
+
+    /// Returns a handle that triggers [`Reload::reload`] from elsewhere, for example from a
+    /// signal handler.
+    pub fn reloader(&self) -> Reloader {
+        Reloader {
+            trigger: self
+                .trigger
+                .as_ref()
+                .map(Arc::downgrade)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl<T> Drop for Reload<T> {
+    fn drop(&mut self) {
+        // Both the watcher and the trigger feed the reload thread, which exits once they are gone.
+        self.watcher.take();
+        self.trigger.take();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+/// Watches a file and invokes a callback with every new version of its value.
+///
+/// Bursts of changes are debounced with [`DEFAULT_DEBOUNCE`]. When the file can't be read or
+/// parsed, for example while an editor is halfway through saving it, the failure is logged
+/// and the last good value is kept. The callback only runs for successful reloads, the value
+/// loaded at startup is available from [`Reload::current`].
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use libset::{Config, FileType};
+/// # use serde::Deserialize;
+/// # fn main() -> Result<(), libset::Error> {
+/// #[derive(Clone, Deserialize)]
+/// struct Settings { workers: usize }
+///
+/// let config = Arc::new(Config::new("org.example.Daemon", 1, None)?);
+/// let settings = libset::daemon::watch_and_reload(config, "settings", FileType::Json, |new: Settings| {
+///     println!("now using {} workers", new.workers);
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Arguments
+///
+/// * `config` - The configuration the file is stored in.
+/// * `key` - The key used to store the file.
+/// * `file_type` - The file extension.
+/// * `on_change` - Function invoked on a background thread with each new value.
+///
+/// # Returns
+///
+/// A `Result` containing the `Reload` handle or an `Error` if the file could not be watched.
+pub fn watch_and_reload<T, F>(
+    config: Arc<Config>,
+    key: &str,
+    file_type: FileType,
+    on_change: F,
+) -> Result<Reload<T>, Error>
+where
+    T: DeserializeOwned + Clone + Send + Sync + 'static,
+    F: FnMut(T) + Send + 'static,
+{
+    watch_and_reload_debounced(config, key, file_type, DEFAULT_DEBOUNCE, on_change)
+}
+
+/// Watches a file and invokes a callback with every new version of its value.
+///
+/// Same as [`watch_and_reload`], waiting for `debounce` without changes before reloading.
+///
+/// # Arguments
+///
+/// * `config` - The configuration the file is stored in.
+/// * `key` - The key used to store the file.
+/// * `file_type` - The file extension.
+/// * `debounce` - How long the file must stay unchanged before it is reloaded.
+/// * `on_change` - Function invoked on a background thread with each new value.
+///
+/// # Returns
+///
+/// A `Result` containing the `Reload` handle or an `Error` if the file could not be watched.
+pub fn watch_and_reload_debounced<T, F>(
+    config: Arc<Config>,
+    key: &str,
+    file_type: FileType,
+    debounce: Duration,
+    on_change: F,
+) -> Result<Reload<T>, Error>
+where
+    T: DeserializeOwned + Clone + Send + Sync + 'static,
+    F: FnMut(T) + Send + 'static,
+{
+    let initial = match config.get::<T>(key, file_type) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!(key, error = %err, "failed to load settings");
+            None
+        }
+    };
+    let loaded = config.clone();
+    let owned_key = key.to_string();
+    let load = move || loaded.get::<T>(&owned_key, file_type);
+    start(
+        &config,
+        key,
+        file_type,
+        debounce,
+        initial,
+        load,
+        on_change,
+        |_: &Error| {},
+    )
+}
+
+/// A callback registered with [`LiveSettings::on_error`].
+type ErrorHook = Arc<dyn Fn(&Error) + Send + Sync>;
+
+/// Typed settings kept up to date with a stored file, serving the last good value.
+///
+/// Changes to the file are parsed on a background thread and swapped in atomically once they
+/// parse, so readers always get a complete value. When an edit fails to parse, the previous
+/// value keeps being served and the error callbacks are invoked, a bad edit never takes the
+/// service down. The file stops being watched once the value is dropped.
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use libset::{daemon::LiveSettings, Config, FileType};
+/// # use serde::Deserialize;
+/// # fn main() -> Result<(), libset::Error> {
+/// #[derive(Deserialize)]
+/// struct Settings { workers: usize }
+///
+/// let config = Arc::new(Config::new("org.example.Daemon", 1, None)?);
+/// let mut settings = LiveSettings::<Settings>::new(config, "settings", FileType::Json)?;
+/// settings.on_error(|err| eprintln!("ignoring broken settings: {err}"));
+/// println!("using {} workers", settings.get().workers);
+/// # Ok(())
+/// # }
+/// ```
+pub struct LiveSettings<T> {
+    reload: Reload<Arc<T>>,
+    error_hooks: Arc<RwLock<Vec<ErrorHook>>>,
+}
+
+impl<T: DeserializeOwned + Send + Sync + 'static> LiveSettings<T> {
+    /// Loads a file and starts watching it for changes.
+    ///
+    /// Bursts of changes are debounced with [`DEFAULT_DEBOUNCE`].
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration the file is stored in.
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `LiveSettings` or an `Error` if the file could not be loaded
+    /// or watched.
+    pub fn new(config: Arc<Config>, key: &str, file_type: FileType) -> Result<Self, Error> {
+        Self::with_debounce(config, key, file_type, DEFAULT_DEBOUNCE)
+    }
+
+    /// Loads a file and starts watching it for changes, waiting for `debounce` without
+    /// changes before reloading.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration the file is stored in.
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    /// * `debounce` - How long the file must stay unchanged before it is reloaded.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `LiveSettings` or an `Error` if the file could not be loaded
+    /// or watched.
+    pub fn with_debounce(
+        config: Arc<Config>,
+        key: &str,
+        file_type: FileType,
+        debounce: Duration,
+    ) -> Result<Self, Error> {
+        let initial = Arc::new(config.get::<T>(key, file_type)?);
+        let error_hooks: Arc<RwLock<Vec<ErrorHook>>> = Arc::default();
+        let hooks = error_hooks.clone();
+        let loaded = config.clone();
+        let owned_key = key.to_string();
+        let reload = start(
+            &config,
+            key,
+            file_type,
+            debounce,
+            Some(initial),
+            move || loaded.get::<T>(&owned_key, file_type).map(Arc::new),
+            |_| {},
+            move |err: &Error| {
+                for hook in hooks.read().unwrap().iter() {
+                    hook(err);
+                }
+            },
+        )?;
+        Ok(Self {
+            reload,
+            error_hooks,
+        })
+    }
+}
+
+impl<T> LiveSettings<T> {
+    /// Returns the last value that was loaded successfully.
+    ///
+    /// The value is shared, not copied, holding on to it doesn't block reloads.
+    pub fn get(&self) -> Arc<T> {
+        // Always set, the file is loaded before it is watched.
+        self.reload.current.read().unwrap().clone().unwrap()
+    }
+
+    /// Registers a callback invoked on the reload thread when a change fails to load.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - Function invoked with the failure, the last good value is kept.
+    pub fn on_error<F>(&mut self, hook: F)
+    where
+        F: Fn(&Error) + Send + Sync + 'static,
+    {
+        self.error_hooks.write().unwrap().push(Arc::new(hook));
+    }
+
+    /// Re-reads the file even if no change was detected.
+    pub fn reload(&self) {
+        self.reload.reload();
+    }
+
+    /// Returns a handle that triggers [`LiveSettings::reload`] from elsewhere, for example
+    /// from a signal handler.
+    pub fn reloader(&self) -> Reloader {
+        self.reload.reloader()
+    }
+}
+
+/// Watches a file and keeps the last value loaded successfully, reporting every reload.
+///
+/// # Arguments
+///
+/// * `config` - The configuration the file is stored in.
+/// * `key` - The key used to store the file.
+/// * `file_type` - The file extension.
+/// * `debounce` - How long the file must stay unchanged before it is reloaded.
+/// * `initial` - The value loaded at startup, if any.
+/// * `load` - Function reading the current value of the file.
+/// * `on_change` - Function invoked with each value loaded successfully.
+/// * `on_error` - Function invoked with each failed reload.
+///
+/// # Returns
+///
+/// A `Result` containing the `Reload` handle or an `Error` if the file could not be watched.
+#[allow(clippy::too_many_arguments)]
+fn start<T, L, F, E>(
+    config: &Arc<Config>,
+    key: &str,
+    file_type: FileType,
+    debounce: Duration,
+    initial: Option<T>,
+    load: L,
+    mut on_change: F,
+    on_error: E,
+) -> Result<Reload<T>, Error>
+where
+    T: Clone + Send + Sync + 'static,
+    L: Fn() -> Result<T, Error> + Send + 'static,
+    F: FnMut(T) + Send + 'static,
+    E: Fn(&Error) + Send + 'static,
+{
+    let current = Arc::new(RwLock::new(initial));
+
+    let (trigger, events) = mpsc::channel();
+    let changes = trigger.clone();
+    // Fallback scopes live next to the current one, so the whole root is watched when the
+    // file may be read from them.
+    let scope = config
+        .scope
+        .as_ref()
+        .filter(|_| config.fallbacks.is_empty())
+        .map(|scope| scope.to_string_lossy().into_owned());
+    let watched = config.clone();
+    let watched_key = key.to_string();
+    let resolved = Mutex::new(config.resolve(key, file_type).ok());
+    let watcher = config.watch_scope(scope.as_deref(), move |change| {
+        // The file read may move between scopes, so changes to the file read before and to
+        // the one read now both trigger a reload.
+        let current = watched.resolve(&watched_key, file_type).ok();
+        let mut previous = resolved.lock().unwrap();
+        if previous.as_ref() == Some(&change.path) || current.as_ref() == Some(&change.path) {
+            changes.send(()).ok();
+        }
+        *previous = current;
+    })?;
+
+    let key = key.to_string();
+    let value = current.clone();
+    let thread = std::thread::spawn(move || {
+        while events.recv().is_ok() {
+            loop {
+                match events.recv_timeout(debounce) {
+                    Ok(()) => continue,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            match load() {
+                Ok(new) => {
+                    info!(key, %file_type, "reloaded settings");
+                    *value.write().unwrap() = Some(new.clone());
+                    on_change(new);
+                }
+                Err(err) => {
+                    warn!(key, error = %err, "failed to reload settings, keeping the last good value");
+                    on_error(&err);
+                }
+            }
+        }
+    });
+
+    Ok(Reload {
+        current,
+        trigger: Some(Arc::new(trigger)),
+        thread: Some(thread),
+        watcher: Some(watcher),
+    })
+}
+
+/// Handle that forces a [`Reload`] to re-read its file.
+///
+/// It doesn't keep the [`Reload`] alive, reloading does nothing once the `Reload` is dropped.
+#[derive(Debug, Clone)]
+pub struct Reloader {
+    trigger: Weak<Sender<()>>,
+}
+
+impl Reloader {
+    /// Re-reads the file even if no change was detected, invoking the callback on success.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the `Reload` was dropped.
+    pub fn reload(&self) -> bool {
+        match self.trigger.upgrade() {
+            Some(trigger) => trigger.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Reloads every value each time `signals` yields an item.
+///
+/// This lets applications that already handle signals, or receive reload requests some other
+/// way, drive the conventional reload workflow. The returned thread finishes when `signals`
+/// is exhausted.
+///
+/// # Arguments
+///
+/// * `signals` - Iterator yielding an item for every reload request.
+/// * `reloaders` - The values to reload, obtained from [`Reload::reloader`].
+///
+/// # Returns
+///
+/// The handle of the thread waiting for `signals`.
+pub fn reload_on<S, I>(signals: S, reloaders: I) -> JoinHandle<()>
+where
+    S: IntoIterator + Send + 'static,
+    I: IntoIterator<Item = Reloader>,
+{
+    let reloaders: Vec<Reloader> = reloaders.into_iter().collect();
+    std::thread::spawn(move || {
+        for _ in signals {
+            reload_all(&reloaders);
+        }
+    })
+}
+
+/// Forces every value to be re-read.
+fn reload_all(reloaders: &[Reloader]) {
+    info!(count = reloaders.len(), "reload requested");
+    for reloader in reloaders {
+        reloader.reload();
+    }
+}
+
+/// Handle to an installed `SIGHUP` handler.
+///
+/// The handler is removed once the handle is dropped.
+#[cfg(all(unix, feature = "sighup"))]
+pub struct SighupHandler {
+    handle: signal_hook::iterator::Handle,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[cfg(all(unix, feature = "sighup"))]
+impl Drop for SighupHandler {
+    fn drop(&mut self) {
+        self.handle.close();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+/// Reloads every value when the process receives `SIGHUP`.
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use libset::{Config, FileType};
+/// # fn main() -> Result<(), libset::Error> {
+/// let config = Arc::new(Config::new("org.example.Daemon", 1, None)?);
+/// let workers = libset::daemon::watch_and_reload(config.clone(), "workers", FileType::Json, |n: usize| {
+///     println!("now using {n} workers");
+/// })?;
+/// let hosts = libset::daemon::watch_and_reload(config, "hosts", FileType::Json, |_: Vec<String>| {})?;
+/// let _sighup = libset::daemon::reload_on_sighup([workers.reloader(), hosts.reloader()])?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Arguments
+///
+/// * `reloaders` - The values to reload, obtained from [`Reload::reloader`].
+///
+/// # Returns
+///
+/// A `Result` containing the `SighupHandler` or an `Error` if the handler could not be
+/// installed.
+#[cfg(all(unix, feature = "sighup"))]
+pub fn reload_on_sighup<I>(reloaders: I) -> Result<SighupHandler, Error>
+where
+    I: IntoIterator<Item = Reloader>,
+{
+    use signal_hook::{consts::SIGHUP, iterator::Signals};
+
+    let mut signals = Signals::new([SIGHUP])
+        .map_err(|err| Error::from(format!("failed to install SIGHUP handler: {err}")))?;
+    let handle = signals.handle();
+    let reloaders: Vec<Reloader> = reloaders.into_iter().collect();
+    let thread = std::thread::spawn(move || {
+        for _ in signals.forever() {
+            reload_all(&reloaders);
+        }
+    });
+    Ok(SighupHandler {
+        handle,
+        thread: Some(thread),
+    })
+}
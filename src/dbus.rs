@@ -0,0 +1,233 @@
+//! Exposes a configuration to other processes over D-Bus.
+//!
+//! The [`Settings1`](INTERFACE) interface is served at [`OBJECT_PATH`] and stores every value
+//! as a JSON file, so other processes and scripts go through the same key validation,
+//! middleware and atomic writes as the application itself:
+//!
+//! * `Get(key: s) -> s` returns the value of a key as JSON text.
+//! * `Set(key: s, value: s)` parses JSON text and stores it under the key.
+//! * `Watch(key: s)` starts emitting `Changed(key: s, value: s)` when the key is written and
+//!   `Removed(key: s)` when it is removed, `Unwatch(key: s)` stops it again.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use libset::Config;
+//! # fn main() -> Result<(), libset::Error> {
+//! let config = Arc::new(Config::new("org.example.Demo", 1, None)?);
+//! let _server = libset::dbus::serve(config, "org.example.Demo.Settings")?;
+//! // busctl --user call org.example.Demo.Settings /org/libset/Settings \
+//! //     org.libset.Settings1 Set ss theme '"dark"'
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tracing::warn;
+use zbus::{
+    blocking::{connection, Connection},
+    fdo,
+    object_server::SignalEmitter,
+};
+
+use crate::{utils::FileType, watch::ChangeKind, Config, Error, Watcher};
+
+/// Name of the D-Bus interface.
+pub const INTERFACE: &str = "org.libset.Settings1";
+/// Path the interface is served at.
+pub const OBJECT_PATH: &str = "/org/libset/Settings";
+
+/// Handle to a configuration served over D-Bus.
+///
+/// The interface is removed once the handle is dropped.
+pub struct Server {
+    connection: Connection,
+    _watcher: Watcher,
+}
+
+impl Server {
+    /// Returns the connection the interface is served on.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.connection
+            .object_server()
+            .remove::<Settings, _>(OBJECT_PATH)
+            .ok();
+    }
+}
+
+/// Serves a configuration on the session bus under a well-known name.
+///
+/// # Arguments
+///
+/// * `config` - The configuration to expose.
+/// * `bus_name` - The well-known name to request, such as `org.example.Demo.Settings`.
+///
+/// # Returns
+///
+/// A `Result` containing the `Server` handle or an `Error` if the interface could not be
+/// served.
+pub fn serve(config: Arc<Config>, bus_name: &str) -> Result<Server, Error> {
+    let connection = connection::Builder::session()
+        .and_then(|builder| builder.name(bus_name.to_string()))
+        .and_then(|builder| builder.build())
+        .map_err(|source| Error::Dbus { source })?;
+    serve_on(config, connection)
+}
+
+/// Serves a configuration on an existing connection.
+///
+/// Use this to serve on the system bus or to request names yourself.
+///
+/// # Arguments
+///
+/// * `config` - The configuration to expose.
+/// * `connection` - The connection to serve the interface on.
+///
+/// # Returns
+///
+/// A `Result` containing the `Server` handle or an `Error` if the interface could not be
+/// served.
+pub fn serve_on(config: Arc<Config>, connection: Connection) -> Result<Server, Error> {
+    let watched = Arc::new(Mutex::new(HashMap::new()));
+    let watcher = watch(&config, &connection, &watched)?;
+    connection
+        .object_server()
+        .at(OBJECT_PATH, Settings { config, watched })
+        .map_err(|source| Error::Dbus { source })?;
+    Ok(Server {
+        connection,
+        _watcher: watcher,
+    })
+}
+
+/// The watched keys and the last value a signal was emitted with.
+type Watched = HashMap<String, Option<String>>;
+
+/// Emits the signals for watched keys when their files change.
+fn watch(
+    config: &Arc<Config>,
+    connection: &Connection,
+    watched: &Arc<Mutex<Watched>>,
+) -> Result<Watcher, Error> {
+    let scope = config
+        .scope
+        .as_ref()
+        .map(|scope| scope.to_string_lossy().into_owned());
+    let served = config.clone();
+    let connection = connection.clone();
+    let watched = watched.clone();
+    config.watch_scope(scope.as_deref(), move |change| {
+        if change.file_type != FileType::Json || change.scope != served.scope {
+            return;
+        }
+        let value = match change.kind {
+            ChangeKind::Removed => None,
+            _ => match served.get_json::<serde_json::Value>(&change.key) {
+                Ok(value) => Some(value.to_string()),
+                Err(err) => {
+                    warn!(key = change.key, error = %err, "failed to read changed key");
+                    return;
+                }
+            },
+        };
+        // A single write can be reported several times, only signal actual changes.
+        match watched.lock().unwrap().get_mut(&change.key) {
+            Some(last) if *last != value => *last = value.clone(),
+            _ => return,
+        }
+        let emitted = match value {
+            Some(value) => connection.emit_signal(
+                None::<()>,
+                OBJECT_PATH,
+                INTERFACE,
+                "Changed",
+                &(&change.key, value),
+            ),
+            None => {
+                connection.emit_signal(None::<()>, OBJECT_PATH, INTERFACE, "Removed", &change.key)
+            }
+        };
+        if let Err(err) = emitted {
+            warn!(key = change.key, error = %err, "failed to emit change signal");
+        }
+    })
+}
+
+/// Converts an error into the D-Bus error returned to callers, describing its causes too.
+fn to_fdo(err: Error) -> fdo::Error {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(&err);
+    while let Some(cause) = source {
+        message = format!("{message}: {cause}");
+        source = cause.source();
+    }
+    if err.is_not_found() {
+        return fdo::Error::FileNotFound(message);
+    }
+    match err {
+        Error::InvalidKey { .. } | Error::InvalidName(_) | Error::ReservedName(_) => {
+            fdo::Error::InvalidArgs(message)
+        }
+        _ => fdo::Error::Failed(message),
+    }
+}
+
+/// The object served at [`OBJECT_PATH`].
+struct Settings {
+    config: Arc<Config>,
+    watched: Arc<Mutex<Watched>>,
+}
+
+#[zbus::interface(name = "org.libset.Settings1")]
+impl Settings {
+    /// Returns the value of a key as JSON text.
+    fn get(&self, key: &str) -> fdo::Result<String> {
+        let value: serde_json::Value = self.config.get_json(key).map_err(to_fdo)?;
+        Ok(value.to_string())
+    }
+
+    /// Parses JSON text and stores it under a key.
+    fn set(&self, key: &str, value: &str) -> fdo::Result<()> {
+        let value: serde_json::Value = serde_json::from_str(value)
+            .map_err(|err| fdo::Error::InvalidArgs(format!("invalid JSON value: {err}")))?;
+        self.config.set_json(key, value).map_err(to_fdo)
+    }
+
+    /// Starts emitting signals when a key changes.
+    fn watch(&self, key: &str) -> fdo::Result<()> {
+        self.config.path(key, FileType::Json).map_err(to_fdo)?;
+        let current = self
+            .config
+            .get_json::<serde_json::Value>(key)
+            .ok()
+            .map(|value| value.to_string());
+        self.watched
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert(current);
+        Ok(())
+    }
+
+    /// Stops emitting signals when a key changes.
+    fn unwatch(&self, key: &str) {
+        self.watched.lock().unwrap().remove(key);
+    }
+
+    /// Emitted when a watched key is written.
+    #[zbus(signal)]
+    async fn changed(emitter: &SignalEmitter<'_>, key: &str, value: &str) -> zbus::Result<()>;
+
+    /// Emitted when a watched key is removed.
+    #[zbus(signal)]
+    async fn removed(emitter: &SignalEmitter<'_>, key: &str) -> zbus::Result<()>;
+}
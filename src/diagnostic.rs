@@ -0,0 +1,166 @@
+use std::{fmt::Display, path::Path};
+
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceCode};
+
+use crate::Error;
+
+/// Keeps the contents of a malformed file so reports can point into it.
+pub(crate) fn source_code(path: &Path, data: &str) -> Option<Box<NamedSource<String>>> {
+    Some(Box::new(NamedSource::new(
+        path.display().to_string(),
+        data.to_string(),
+    )))
+}
+
+/// Renders parse errors with the offending part of the file highlighted.
+///
+/// ```no_run
+/// # use libset::Config;
+/// fn main() -> miette::Result<()> {
+///     let config = Config::new("org.example.Demo", 1, None)?;
+///     let colors: serde_json::Value = config.get_json("colors")?;
+///     Ok(())
+/// }
+/// ```
+impl Diagnostic for Error {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let code = match self {
+            #[cfg(feature = "toml")]
+            Self::TomlDeserialize { .. } => "libset::toml",
+            #[cfg(feature = "json")]
+            Self::Json { .. } => "libset::json",
+            #[cfg(feature = "yaml")]
+            Self::Yaml { .. } => "libset::yaml",
+            #[cfg(feature = "ini")]
+            Self::Ini { .. } => "libset::ini",
+            #[cfg(feature = "json5")]
+            Self::Json5 { .. } => "libset::json5",
+            #[cfg(feature = "ron")]
+            Self::RonSpanned { .. } => "libset::ron",
+            Self::Truncated { .. } => "libset::truncated",
+            Self::KeyNotFound { .. } => "libset::not_found",
+            Self::UnsupportedByFormat { .. } => "libset::unsupported_by_format",
+            _ => return None,
+        };
+        Some(Box::new(code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            Self::Truncated { .. } => Some(Box::new(
+                "the file was cut short, restore it from a snapshot or write it again",
+            )),
+            _ => None,
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        let source_code: &Option<Box<NamedSource<String>>> = match self {
+            #[cfg(feature = "toml")]
+            Self::TomlDeserialize { source_code, .. } => source_code,
+            #[cfg(feature = "json")]
+            Self::Json { source_code, .. } => source_code,
+            #[cfg(feature = "yaml")]
+            Self::Yaml { source_code, .. } => source_code,
+            #[cfg(feature = "ini")]
+            Self::Ini { source_code, .. } => source_code,
+            #[cfg(feature = "json5")]
+            Self::Json5 { source_code, .. } => source_code,
+            #[cfg(feature = "ron")]
+            Self::RonSpanned { source_code, .. } => source_code,
+            _ => return None,
+        };
+        source_code
+            .as_deref()
+            .map(|source_code| source_code as &dyn SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let label = match self {
+            #[cfg(feature = "toml")]
+            Self::TomlDeserialize {
+                source,
+                source_code: Some(_),
+                ..
+            } => {
+                let span = source.span()?;
+                LabeledSpan::new(Some(source.message().to_string()), span.start, span.len())
+            }
+            #[cfg(feature = "json")]
+            Self::Json {
+                source,
+                source_code: Some(source_code),
+                ..
+            } => {
+                let message = source.to_string();
+                // The position is reported by the span, keep only the reason.
+                let message = match message.rsplit_once(" at line ") {
+                    Some((message, _)) => message.to_string(),
+                    None => message,
+                };
+                let offset = offset(source_code.inner(), source.line(), source.column());
+                LabeledSpan::at_offset(offset, message)
+            }
+            #[cfg(feature = "yaml")]
+            Self::Yaml {
+                source,
+                source_code: Some(_),
+                ..
+            } => {
+                let location = source.location()?;
+                let message = source.to_string();
+                let message = match message.rsplit_once(" at line ") {
+                    Some((message, _)) => message.to_string(),
+                    None => message,
+                };
+                LabeledSpan::at_offset(location.index(), message)
+            }
+            #[cfg(feature = "ini")]
+            Self::Ini {
+                reason,
+                position: Some(position),
+                source_code: Some(source_code),
+                ..
+            } => {
+                let offset = offset(source_code.inner(), position.line, position.column);
+                LabeledSpan::at_offset(offset, reason.clone())
+            }
+            #[cfg(feature = "json5")]
+            Self::Json5 {
+                source: json5::Error::Message { msg, location },
+                source_code: Some(source_code),
+                ..
+            } => {
+                let location = location.as_ref()?;
+                let offset = offset(source_code.inner(), location.line, location.column);
+                LabeledSpan::at_offset(offset, msg.clone())
+            }
+            #[cfg(feature = "ron")]
+            Self::RonSpanned {
+                source,
+                source_code: Some(source_code),
+                ..
+            } => {
+                let position = source.position;
+                let offset = offset(source_code.inner(), position.line, position.col);
+                LabeledSpan::at_offset(offset, source.code.to_string())
+            }
+            _ => return None,
+        };
+        Some(Box::new(std::iter::once(label)))
+    }
+}
+
+/// Converts a 1-based line and byte column into an offset within `text`.
+fn offset(text: &str, line: usize, column: usize) -> usize {
+    let start: usize = text
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let mut offset = (start + column.saturating_sub(1)).min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
@@ -0,0 +1,321 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::{
+    deserialize, middleware, parse::ParseOptions, utils::decode, utils::FileType, Change,
+    ChangeKind, Config, Direction, EntryInfo, Error, Filesystem, Middleware, Watcher,
+};
+
+/// A value that changed within a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// The location of the value within the document, such as `network.proxy` or
+    /// `profiles[1].name`. Empty when the whole document changed.
+    pub path: String,
+    /// The previous value, `None` if it was added.
+    pub old: Option<Value>,
+    /// The new value, `None` if it was removed.
+    pub new: Option<Value>,
+}
+
+/// A change to a stored file along with the values that changed in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff {
+    /// The change detected on the file.
+    pub change: Change,
+    /// The document before the change, `None` if the file didn't exist.
+    pub old: Option<Value>,
+    /// The document after the change, `None` if the file was removed.
+    pub new: Option<Value>,
+    /// The values that changed, in the order they appear in the document.
+    pub fields: Vec<FieldChange>,
+}
+
+impl Diff {
+    /// Determines if the value at a location changed, including any value nested in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The location of the value, such as `network.proxy`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the value or one of its fields changed, or if it was replaced along with a
+    /// value containing it.
+    pub fn changed(&self, path: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|field| contains(&field.path, path) || contains(path, &field.path))
+    }
+}
+
+/// Determines if a location is equal to or nested in another one.
+fn contains(outer: &str, inner: &str) -> bool {
+    match inner.strip_prefix(outer) {
+        Some(rest) => outer.is_empty() || rest.is_empty() || rest.starts_with(['.', '[']),
+        None => false,
+    }
+}
+
+/// Finds the value at a location within a document.
+///
+/// # Arguments
+///
+/// * `document` - The document to look into.
+/// * `path` - The location of the value, such as `network.proxy` or `profiles[1].name`.
+///
+/// # Returns
+///
+/// The value, or `None` if the document doesn't contain it.
+pub(crate) fn lookup<'a>(document: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut value = document;
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let (name, mut indices) = match part.find('[') {
+            Some(start) => part.split_at(start),
+            None => (part, ""),
+        };
+        if !name.is_empty() {
+            value = value.get(name)?;
+        }
+        while let Some(rest) = indices.strip_prefix('[') {
+            let (index, rest) = rest.split_once(']')?;
+            value = value.get(index.parse::<usize>().ok()?)?;
+            indices = rest;
+        }
+        if !indices.is_empty() {
+            return None;
+        }
+    }
+    Some(value)
+}
+
+/// Lists the values that differ between two versions of a document.
+///
+/// Objects and arrays are compared field by field, any other value is compared as a whole.
+pub(crate) fn diff(old: Option<&Value>, new: Option<&Value>) -> Vec<FieldChange> {
+    let mut fields = Vec::new();
+    compare(String::new(), old, new, &mut fields);
+    fields
+}
+
+fn compare(path: String, old: Option<&Value>, new: Option<&Value>, fields: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            for (name, value) in old {
+                compare(field(&path, name), Some(value), new.get(name), fields);
+            }
+            for (name, value) in new {
+                if !old.contains_key(name) {
+                    compare(field(&path, name), None, Some(value), fields);
+                }
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for index in 0..old.len().max(new.len()) {
+                compare(
+                    format!("{path}[{index}]"),
+                    old.get(index),
+                    new.get(index),
+                    fields,
+                );
+            }
+        }
+        (old, new) if old != new => fields.push(FieldChange {
+            path,
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+/// Returns the location of a field of an object.
+fn field(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+/// Reads stored files into documents from the watcher thread.
+struct Reader {
+    fs: Arc<dyn Filesystem>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    parse: ParseOptions,
+    dir: PathBuf,
+}
+
+impl Reader {
+    /// Reads the current document of a key, `None` if it can't be read or parsed.
+    fn read(&self, key: &str, file_type: FileType) -> Option<Value> {
+        let path = match file_type {
+            FileType::Plain => self.dir.join(key),
+            file_type => self.dir.join(format!("{key}.{file_type}")),
+        };
+        self.try_read(key, file_type, &path).ok()
+    }
+
+    fn try_read(&self, key: &str, file_type: FileType, path: &Path) -> Result<Value, Error> {
+        let mut bytes = self
+            .fs
+            .read(path)
+            .map_err(|err| Error::for_key(key, file_type, path, err))?;
+        let entry = EntryInfo {
+            key,
+            file_type,
+            path,
+        };
+        middleware::run(&self.middleware, &mut bytes, Direction::Read, &entry)?;
+        let data = decode(key, path, bytes)?;
+        match file_type {
+            FileType::Plain => Ok(Value::String(data)),
+            file_type => deserialize(key, file_type, path, &data, &self.parse),
+        }
+    }
+}
+
+impl Config {
+    /// Watches the files of the current scope and reports the values that changed in them.
+    ///
+    /// The previous version of every file is kept in memory, so each modification can be
+    /// compared with it field by field. Subscribers can react only to the settings they care
+    /// about, such as restarting networking when `proxy` changed. Changes that leave every
+    /// value as it was, and versions that can't be parsed, are not reported.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Daemon", 1, None)?;
+    /// let _watcher = config.watch_diffs(|diff| {
+    ///     if diff.change.key == "network" && diff.changed("proxy") {
+    ///         println!("restarting networking");
+    ///     }
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Function invoked on a background thread with each diff.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Watcher` handle or an `Error` if the scope could not be
+    /// listed or watched.
+    pub fn watch_diffs<F>(&self, callback: F) -> Result<Watcher, Error>
+    where
+        F: Fn(Diff) + Send + 'static,
+    {
+        let reader = Reader {
+            fs: self.fs.clone(),
+            middleware: self.middleware.clone(),
+            parse: self.parse.clone(),
+            dir: self.path.clone(),
+        };
+        let mut documents = HashMap::new();
+        for entry in self.entries()? {
+            let entry = entry?;
+            if let Some(document) = reader.read(&entry.key, entry.file_type) {
+                documents.insert((entry.key, entry.file_type), document);
+            }
+        }
+        let documents = Mutex::new(documents);
+        let scope = self
+            .scope
+            .as_ref()
+            .map(|scope| scope.to_string_lossy().into_owned());
+        let watched_scope = self.scope.clone();
+        self.watch_scope(scope.as_deref(), move |change: Change| {
+            if change.scope != watched_scope {
+                return;
+            }
+            let new = match change.kind {
+                ChangeKind::Removed => None,
+                ChangeKind::Created | ChangeKind::Modified => {
+                    match reader.read(&change.key, change.file_type) {
+                        Some(document) => Some(document),
+                        // Keep the last version until the file can be parsed again.
+                        None => return,
+                    }
+                }
+            };
+            let id = (change.key.clone(), change.file_type);
+            let mut documents = documents.lock().unwrap();
+            let old = match &new {
+                Some(document) => documents.insert(id, document.clone()),
+                None => documents.remove(&id),
+            };
+            drop(documents);
+            let fields = diff(old.as_ref(), new.as_ref());
+            if !fields.is_empty() {
+                callback(Diff {
+                    change,
+                    old,
+                    new,
+                    fields,
+                });
+            }
+        })
+    }
+
+    /// Watches a single value within a file of the current scope.
+    ///
+    /// The callback only runs when the value at `path` changes, other changes to the file are
+    /// ignored, which spares subscribers of large documents from comparing them whole.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let _watcher = config.watch_path::<String, _>("settings", "ui.theme", |theme| {
+    ///     println!("theme is now {}", theme.as_deref().unwrap_or("the default"));
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `path` - The location of the value, such as `ui.theme` or `profiles[1].name`.
+    /// * `callback` - Function invoked on a background thread with the new value, `None` if it
+    ///   was removed.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Watcher` handle or an `Error` if the scope could not be
+    /// listed or watched.
+    pub fn watch_path<T, F>(&self, key: &str, path: &str, callback: F) -> Result<Watcher, Error>
+    where
+        T: DeserializeOwned,
+        F: Fn(Option<T>) + Send + 'static,
+    {
+        let key = key.to_string();
+        let path = path.to_string();
+        self.watch_diffs(move |diff| {
+            if diff.change.key != key {
+                return;
+            }
+            let old = diff.old.as_ref().and_then(|old| lookup(old, &path));
+            let new = diff.new.as_ref().and_then(|new| lookup(new, &path));
+            if old == new {
+                return;
+            }
+            match new.map(|new| T::deserialize(new)).transpose() {
+                Ok(value) => callback(value),
+                Err(err) => {
+                    warn!(key, path, error = %err, "ignoring changed value of the wrong type")
+                }
+            }
+        })
+    }
+}
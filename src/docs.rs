@@ -0,0 +1,558 @@
+//! Human-readable documentation for settings types.
+//!
+//! Types deriving [`JsonSchema`] can be turned into a list of their keys, types, defaults and
+//! descriptions, taken from the doc comments and serde attributes of the type, or into a
+//! GSettings schema for distributions that expect one.
+//!
+//! ```no_run
+//! use libset::docs::JsonSchema;
+//! use serde::{Deserialize, Serialize};
+//!
+//! /// Appearance settings.
+//! #[derive(Default, Serialize, Deserialize, JsonSchema)]
+//! #[serde(default)]
+//! struct Appearance {
+//!     /// The accent color, as a hex string.
+//!     accent: String,
+//!     /// Whether the dark theme is used.
+//!     dark: bool,
+//! }
+//!
+//! println!("{}", libset::docs::markdown::<Appearance>());
+//! ```
+
+use serde_json::{Map, Value};
+
+pub use schemars::JsonSchema;
+
+/// A documented setting.
+struct Row {
+    key: String,
+    kind: String,
+    default: Option<String>,
+    description: Option<String>,
+}
+
+/// Generates a Markdown description of a settings type.
+///
+/// Nested structures are flattened into dotted keys, such as `window.width`.
+///
+/// # Returns
+///
+/// A `String` containing a heading, the type's description and a table of its keys.
+pub fn markdown<T: JsonSchema>() -> String {
+    let schema = schemars::schema_for!(T);
+    let root = schema.as_value();
+
+    let mut rows = Vec::new();
+    collect(root, root, "", None, &mut rows);
+
+    let mut out = String::new();
+    if let Some(title) = root.get("title").and_then(Value::as_str) {
+        out.push_str(&format!("# {title}\n\n"));
+    }
+    if let Some(description) = root.get("description").and_then(Value::as_str) {
+        out.push_str(&format!("{description}\n\n"));
+    }
+    out.push_str("| Key | Type | Default | Description |\n");
+    out.push_str("| --- | --- | --- | --- |\n");
+    for row in rows {
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {} |\n",
+            row.key,
+            escape(&row.kind),
+            row.default
+                .map(|default| format!("`{}`", escape(&default)))
+                .unwrap_or_default(),
+            row.description
+                .map(|description| escape(&description))
+                .unwrap_or_default(),
+        ));
+    }
+    out
+}
+
+/// Adds a row for every leaf property of an object schema.
+fn collect(
+    root: &Value,
+    schema: &Value,
+    prefix: &str,
+    default: Option<&Value>,
+    rows: &mut Vec<Row>,
+) {
+    let Some(properties) = properties(root, schema) else {
+        return;
+    };
+    for (name, property) in properties {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}.{name}")
+        };
+        let default = property
+            .get("default")
+            .or_else(|| default.and_then(|default| default.get(name)));
+        if is_nested(root, property) {
+            collect(root, property, &key, default, rows);
+            continue;
+        }
+        rows.push(Row {
+            key,
+            kind: kind(root, property),
+            default: default.map(Value::to_string),
+            description: description(root, property),
+        });
+    }
+}
+
+/// Returns the properties of an object schema, following references.
+fn properties<'a>(root: &'a Value, schema: &'a Value) -> Option<&'a Map<String, Value>> {
+    resolve(root, schema).get("properties")?.as_object()
+}
+
+/// Determines if a schema describes a nested structure.
+fn is_nested(root: &Value, schema: &Value) -> bool {
+    properties(root, schema).is_some_and(|properties| !properties.is_empty())
+}
+
+/// Follows a local `$ref` to its definition.
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+        .unwrap_or(schema)
+}
+
+/// Describes the type of a schema in a few words.
+fn kind(root: &Value, schema: &Value) -> String {
+    if let Some(name) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.rsplit('/').next())
+    {
+        let target = resolve(root, schema);
+        return match target.get("enum").or_else(|| target.get("oneOf")) {
+            Some(_) => kind(root, target),
+            None => name.to_string(),
+        };
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        let values: Vec<String> = values.iter().map(Value::to_string).collect();
+        return format!("one of {}", values.join(", "));
+    }
+    if let Some(variants) = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+    {
+        let variants: Vec<String> = variants
+            .iter()
+            .map(|variant| match variant.get("const") {
+                Some(value) => value.to_string(),
+                None => kind(root, variant),
+            })
+            .collect();
+        return variants.join(" or ");
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    match schema.get("type") {
+        Some(Value::String(name)) => name_of(root, schema, name),
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|name| name_of(root, schema, name))
+            .collect::<Vec<_>>()
+            .join(" or "),
+        _ => "any".to_string(),
+    }
+}
+
+/// Names a primitive JSON schema type, including the element type of arrays.
+fn name_of(root: &Value, schema: &Value, name: &str) -> String {
+    match (name, schema.get("items")) {
+        ("array", Some(items)) => format!("array of {}", kind(root, items)),
+        _ => name.to_string(),
+    }
+}
+
+/// Returns the description of a property, or of the type it refers to.
+fn description(root: &Value, schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .or_else(|| resolve(root, schema).get("description"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Escapes text so it fits in a single Markdown table cell.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// A key of a GSettings schema.
+struct GKey {
+    name: String,
+    signature: String,
+    default: String,
+    choices: Vec<String>,
+    range: Option<(i64, i64)>,
+    description: Option<String>,
+}
+
+/// A GSettings schema, with the nested structures of its type as child schemas.
+struct GSchema {
+    id: String,
+    path: String,
+    keys: Vec<GKey>,
+    children: Vec<(String, GSchema)>,
+}
+
+/// Generates a GSettings XML schema describing a settings type.
+///
+/// Every field becomes a key named after it, with underscores replaced by hyphens, typed with
+/// the matching GVariant type and defaulting to the type's default or the zero value of the
+/// GVariant type. Nested structures become child schemas. Descriptions are split into the
+/// summary, taken from the first paragraph, and the description. Types GVariant can't express
+/// are stored as variants.
+///
+/// ```no_run
+/// use libset::docs::JsonSchema;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Default, Serialize, Deserialize, JsonSchema)]
+/// #[serde(default)]
+/// struct Appearance {
+///     /// The accent color, as a hex string.
+///     accent: String,
+/// }
+///
+/// std::fs::write(
+///     "org.example.Editor.gschema.xml",
+///     libset::docs::gsettings::<Appearance>("org.example.Editor"),
+/// )
+/// .unwrap();
+/// ```
+///
+/// # Arguments
+///
+/// * `id` - The schema id, usually the application id. The schema path is derived from it,
+///   so `org.example.Editor` is stored at `/org/example/Editor/`.
+///
+/// # Returns
+///
+/// A `String` containing the schema list, ready to be installed as `<id>.gschema.xml`.
+pub fn gsettings<T: JsonSchema>(id: &str) -> String {
+    let schema = schemars::schema_for!(T);
+    let root = schema.as_value();
+    let path = format!("/{}/", id.replace('.', "/"));
+    let schema = schema_of(root, root, id.to_string(), path, None);
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<schemalist>\n");
+    write_gschema(&schema, &mut out);
+    out.push_str("</schemalist>\n");
+    out
+}
+
+/// Builds the GSettings schema of an object schema and its nested structures.
+fn schema_of(
+    root: &Value,
+    schema: &Value,
+    id: String,
+    path: String,
+    default: Option<&Value>,
+) -> GSchema {
+    let mut gschema = GSchema {
+        id,
+        path,
+        keys: Vec::new(),
+        children: Vec::new(),
+    };
+    for (name, property) in properties(root, schema).into_iter().flatten() {
+        let gname = name.to_lowercase().replace('_', "-");
+        let default = property
+            .get("default")
+            .or_else(|| default.and_then(|default| default.get(name)));
+        if is_nested(root, property) {
+            let child = schema_of(
+                root,
+                property,
+                format!("{}.{gname}", gschema.id),
+                format!("{}{gname}/", gschema.path),
+                default,
+            );
+            gschema.children.push((gname, child));
+            continue;
+        }
+        let target = resolve(root, property);
+        let choices = choices(target);
+        let signature = if choices.is_empty() {
+            signature(root, property)
+        } else {
+            "s".to_string()
+        };
+        let default = default
+            .and_then(|default| literal(default, &signature))
+            .unwrap_or_else(|| match choices.first() {
+                Some(choice) => quote(choice),
+                None => zero(&signature),
+            });
+        let range = match (
+            target.get("minimum").and_then(Value::as_i64),
+            target.get("maximum").and_then(Value::as_i64),
+        ) {
+            (Some(min), Some(max))
+                if matches!(signature.as_str(), "y" | "n" | "q" | "i" | "u" | "x" | "t") =>
+            {
+                Some((min, max))
+            }
+            _ => None,
+        };
+        gschema.keys.push(GKey {
+            name: gname,
+            signature,
+            default,
+            choices,
+            range,
+            description: description(root, property),
+        });
+    }
+    gschema
+}
+
+/// Appends a schema and its children to the schema list.
+fn write_gschema(schema: &GSchema, out: &mut String) {
+    out.push_str(&format!(
+        "  <schema id=\"{}\" path=\"{}\">\n",
+        xml(&schema.id),
+        xml(&schema.path)
+    ));
+    for key in &schema.keys {
+        out.push_str(&format!(
+            "    <key name=\"{}\" type=\"{}\">\n      <default>{}</default>\n",
+            xml(&key.name),
+            xml(&key.signature),
+            xml(&key.default)
+        ));
+        if let Some(text) = &key.description {
+            let (summary, rest) = text.split_once("\n\n").unwrap_or((text, ""));
+            out.push_str(&format!(
+                "      <summary>{}</summary>\n",
+                xml(&summary.replace('\n', " "))
+            ));
+            if !rest.trim().is_empty() {
+                out.push_str(&format!(
+                    "      <description>{}</description>\n",
+                    xml(rest.trim())
+                ));
+            }
+        }
+        if let Some((min, max)) = key.range {
+            out.push_str(&format!("      <range min=\"{min}\" max=\"{max}\"/>\n"));
+        }
+        if !key.choices.is_empty() {
+            out.push_str("      <choices>\n");
+            for choice in &key.choices {
+                out.push_str(&format!("        <choice value=\"{}\"/>\n", xml(choice)));
+            }
+            out.push_str("      </choices>\n");
+        }
+        out.push_str("    </key>\n");
+    }
+    for (name, child) in &schema.children {
+        out.push_str(&format!(
+            "    <child name=\"{}\" schema=\"{}\"/>\n",
+            xml(name),
+            xml(&child.id)
+        ));
+    }
+    out.push_str("  </schema>\n");
+    for (_, child) in &schema.children {
+        write_gschema(child, out);
+    }
+}
+
+/// Returns the values of an enumeration of strings, empty for any other schema.
+fn choices(schema: &Value) -> Vec<String> {
+    let values: Vec<&Value> = match (schema.get("enum"), schema.get("oneOf")) {
+        (Some(Value::Array(values)), _) => values.iter().collect(),
+        (_, Some(Value::Array(variants))) => variants
+            .iter()
+            .map(|variant| {
+                variant
+                    .get("const")
+                    .or_else(|| variant.get("enum").and_then(|values| values.get(0)))
+                    .unwrap_or(&Value::Null)
+            })
+            .collect(),
+        _ => return Vec::new(),
+    };
+    values
+        .iter()
+        .map(|value| value.as_str().map(str::to_string))
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default()
+}
+
+/// Returns the GVariant type string matching a schema, `v` if there is none.
+fn signature(root: &Value, schema: &Value) -> String {
+    let schema = resolve(root, schema);
+    if let Some(variants) = schema.get("anyOf").and_then(Value::as_array) {
+        return match variants.as_slice() {
+            [inner, null] | [null, inner] if null.get("type") == Some(&"null".into()) => {
+                format!("m{}", signature(root, inner))
+            }
+            _ => "v".to_string(),
+        };
+    }
+    match schema.get("type") {
+        Some(Value::String(name)) => primitive(root, schema, name),
+        Some(Value::Array(names)) => match names.as_slice() {
+            [Value::String(name), Value::String(null)]
+            | [Value::String(null), Value::String(name)]
+                if null == "null" =>
+            {
+                format!("m{}", primitive(root, schema, name))
+            }
+            _ => "v".to_string(),
+        },
+        _ => "v".to_string(),
+    }
+}
+
+/// Returns the GVariant type string of a primitive JSON schema type.
+fn primitive(root: &Value, schema: &Value, name: &str) -> String {
+    let format = schema.get("format").and_then(Value::as_str);
+    let signature = match (name, format) {
+        ("boolean", _) => "b",
+        ("integer", Some("uint8")) => "y",
+        ("integer", Some("int8" | "int16")) => "n",
+        ("integer", Some("uint16")) => "q",
+        ("integer", Some("int32")) => "i",
+        ("integer", Some("uint32")) => "u",
+        ("integer", Some("uint64" | "uint" | "uint128")) => "t",
+        ("integer", _) => "x",
+        ("number", _) => "d",
+        ("string", _) => "s",
+        ("array", _) => {
+            return match schema.get("items") {
+                Some(items) => format!("a{}", signature(root, items)),
+                None => "av".to_string(),
+            }
+        }
+        ("object", _) => {
+            return match schema.get("additionalProperties") {
+                Some(Value::Object(values)) if !values.is_empty() => {
+                    format!("a{{s{}}}", signature(root, &Value::Object(values.clone())))
+                }
+                _ => "a{sv}".to_string(),
+            }
+        }
+        _ => "v",
+    };
+    signature.to_string()
+}
+
+/// Writes a JSON value in the GVariant text format, `None` if it doesn't match the type.
+fn literal(value: &Value, signature: &str) -> Option<String> {
+    let rest = &signature[1..];
+    match (signature.as_bytes()[0], value) {
+        (b'b', Value::Bool(value)) => Some(value.to_string()),
+        (b'y' | b'n' | b'q' | b'i' | b'u' | b'x' | b't', Value::Number(number))
+            if !number.is_f64() =>
+        {
+            Some(number.to_string())
+        }
+        (b'd', Value::Number(number)) => number.as_f64().map(float),
+        (b's', Value::String(text)) => Some(quote(text)),
+        (b'm', Value::Null) => Some("nothing".to_string()),
+        (b'm', value) => literal(value, rest).map(|value| format!("just {value}")),
+        (b'a', Value::Object(entries)) if rest.starts_with("{s") => {
+            let inner = &rest[2..rest.len() - 1];
+            let entries = entries
+                .iter()
+                .map(|(key, value)| Some(format!("{}: {}", quote(key), literal(value, inner)?)))
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("{{{}}}", entries.join(", ")))
+        }
+        (b'a', Value::Array(values)) => {
+            let values = values
+                .iter()
+                .map(|value| literal(value, rest))
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("[{}]", values.join(", ")))
+        }
+        (b'v', value) => Some(format!("<{}>", guess(value))),
+        _ => None,
+    }
+}
+
+/// Writes a JSON value of unknown type in the GVariant text format, with its type annotated.
+fn guess(value: &Value) -> String {
+    match value {
+        Value::Null => "@mv nothing".to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(number) => match (number.as_i64(), number.as_u64(), number.as_f64()) {
+            (Some(value), _, _) => format!("int64 {value}"),
+            (_, Some(value), _) => format!("uint64 {value}"),
+            (_, _, value) => float(value.unwrap_or_default()),
+        },
+        Value::String(text) => quote(text),
+        Value::Array(values) if values.is_empty() => "@av []".to_string(),
+        Value::Array(values) => {
+            let values: Vec<String> = values
+                .iter()
+                .map(|value| format!("<{}>", guess(value)))
+                .collect();
+            format!("[{}]", values.join(", "))
+        }
+        Value::Object(entries) if entries.is_empty() => "@a{sv} {}".to_string(),
+        Value::Object(entries) => {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(key, value)| format!("{}: <{}>", quote(key), guess(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+    }
+}
+
+/// Returns the zero value of a GVariant type, used for keys without a default.
+fn zero(signature: &str) -> String {
+    match signature.as_bytes()[0] {
+        b'b' => "false".to_string(),
+        b'd' => "0.0".to_string(),
+        b's' => "''".to_string(),
+        b'm' => "nothing".to_string(),
+        b'a' if signature.starts_with("a{") => "{}".to_string(),
+        b'a' => "[]".to_string(),
+        b'v' => "<@mv nothing>".to_string(),
+        _ => "0".to_string(),
+    }
+}
+
+/// Writes a floating point number, keeping the decimal point GVariant expects.
+fn float(value: f64) -> String {
+    let text = value.to_string();
+    if text.contains(['.', 'e', 'n', 'i']) {
+        text
+    } else {
+        format!("{text}.0")
+    }
+}
+
+/// Quotes a string in the GVariant text format.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Escapes text for XML content and attributes.
+fn xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
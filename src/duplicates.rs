@@ -0,0 +1,136 @@
+use std::{collections::HashSet, fmt, path::Path};
+
+use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
+
+use crate::utils::FileType;
+
+/// What happens when a json document repeats a key within an object.
+///
+/// Serde keeps the last value of a repeated key, silently dropping the others. Toml documents
+/// with repeated keys are always rejected by the parser.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep the last value without checking.
+    #[default]
+    Ignore,
+    /// Keep the last value, log a warning and call the hooks registered with
+    /// [`Config::on_duplicate_key`](crate::Config::on_duplicate_key).
+    Warn,
+    /// Fail with [`Error::DuplicateKey`](crate::Error::DuplicateKey).
+    Reject,
+}
+
+/// A key repeated within an object of a json document.
+#[derive(Debug, Clone, Copy)]
+pub struct DuplicateKey<'a> {
+    /// The key of the file.
+    pub key: &'a str,
+    /// The format of the file.
+    pub file_type: FileType,
+    /// The absolute path of the file.
+    pub path: &'a Path,
+    /// The location of the repeated key within the document, such as `colors.accent` or
+    /// `profiles[1].name`.
+    pub field: &'a str,
+}
+
+/// Lists the keys repeated within the objects of a json document.
+///
+/// # Arguments
+///
+/// * `data` - The json document.
+///
+/// # Returns
+///
+/// The location of every repeated key, empty if there are none or the document does not parse.
+pub(crate) fn find(data: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_str(data);
+    let fields = Fields {
+        location: String::new(),
+        found: &mut found,
+    };
+    match fields.deserialize(&mut deserializer) {
+        Ok(()) => found,
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Walks a document, recording the location of repeated keys.
+struct Fields<'a> {
+    location: String,
+    found: &'a mut Vec<String>,
+}
+
+impl<'de> DeserializeSeed<'de> for Fields<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Fields<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any json value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        loop {
+            let element = Fields {
+                location: format!("{}[{index}]", self.location),
+                found: &mut *self.found,
+            };
+            if seq.next_element_seed(element)?.is_none() {
+                return Ok(());
+            }
+            index += 1;
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut seen = HashSet::new();
+        while let Some(name) = map.next_key::<String>()? {
+            let location = if self.location.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{name}", self.location)
+            };
+            if !seen.insert(name) {
+                self.found.push(location.clone());
+            }
+            map.next_value_seed(Fields {
+                location,
+                found: &mut *self.found,
+            })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,763 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use zeroize::{Zeroize, Zeroizing};
+
+#[cfg(feature = "keyring")]
+use crate::utils::encode_hex;
+use crate::{
+    middleware::{Direction, EntryInfo, Middleware},
+    utils::{normalize_key, FileType},
+    Config, Error,
+};
+
+/// Marker prepended to encrypted files.
+const MAGIC: &[u8] = b"LIBSET-ENC1";
+/// Marker prepended to files encrypted with a passphrase-derived key.
+const PASSPHRASE_MAGIC: &[u8] = b"LIBSET-ENC2";
+/// Length of an XChaCha20-Poly1305 nonce.
+const NONCE_LEN: usize = 24;
+/// Length of the Argon2id costs and salt stored in passphrase-encrypted files.
+const PARAMS_LEN: usize = 12 + 16;
+/// How far the costs stored in a file may exceed the defaults.
+///
+/// The costs are read before the file is authenticated, larger ones are rejected so a crafted
+/// file can't make the key derivation exhaust memory or time.
+const MAX_COST_FACTOR: u32 = 4;
+
+/// A 256-bit encryption key.
+pub type EncryptionKey = [u8; 32];
+
+/// Supplies the key used by the [`Encryption`] middleware.
+pub trait KeyProvider: Send + Sync {
+    /// Returns the encryption key.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the key or an `Error` if it could not be obtained.
+    fn key(&self) -> Result<EncryptionKey, Error>;
+
+    /// Determines if the provider can supply a key on this system.
+    ///
+    /// [`KeyChain`] uses this to skip providers, such as a keyring without a running secret
+    /// service. Providers are available unless they override this.
+    fn is_available(&self) -> bool {
+        true
+    }
+}
+
+/// A key provider returning a fixed key.
+pub struct StaticKey(EncryptionKey);
+
+impl StaticKey {
+    /// Creates a provider from raw key material.
+    pub fn new(key: EncryptionKey) -> Self {
+        Self(key)
+    }
+}
+
+impl Drop for StaticKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl KeyProvider for StaticKey {
+    fn key(&self) -> Result<EncryptionKey, Error> {
+        Ok(self.0)
+    }
+}
+
+/// Returns the passphrase of a [`PassphraseKey`] when it is first needed.
+type Prompt = Box<dyn Fn() -> Result<String, Error> + Send + Sync>;
+
+/// A key provider deriving the key from a passphrase with Argon2id.
+///
+/// The key is derived on first use and reused afterwards.
+pub struct PassphraseKey {
+    passphrase: PassphraseSource,
+    salt: Vec<u8>,
+    key: OnceLock<EncryptionKey>,
+}
+
+/// Where a [`PassphraseKey`] gets its passphrase from.
+enum PassphraseSource {
+    Fixed(Zeroizing<String>),
+    Prompt(Prompt),
+}
+
+impl PassphraseKey {
+    /// Creates a provider from a passphrase and a salt of at least 8 bytes.
+    pub fn new(passphrase: impl Into<String>, salt: impl Into<Vec<u8>>) -> Self {
+        Self {
+            passphrase: PassphraseSource::Fixed(Zeroizing::new(passphrase.into())),
+            salt: salt.into(),
+            key: OnceLock::new(),
+        }
+    }
+
+    /// Creates a provider asking for the passphrase the first time the key is needed.
+    ///
+    /// Use this as the last step of a [`KeyChain`] to prompt the user only on systems where no
+    /// other provider is available.
+    ///
+    /// # Arguments
+    ///
+    /// * `prompt` - Returns the passphrase, for example by asking the user.
+    /// * `salt` - A salt of at least 8 bytes.
+    pub fn prompt<F>(prompt: F, salt: impl Into<Vec<u8>>) -> Self
+    where
+        F: Fn() -> Result<String, Error> + Send + Sync + 'static,
+    {
+        Self {
+            passphrase: PassphraseSource::Prompt(Box::new(prompt)),
+            salt: salt.into(),
+            key: OnceLock::new(),
+        }
+    }
+}
+
+impl KeyProvider for PassphraseKey {
+    fn key(&self) -> Result<EncryptionKey, Error> {
+        if let Some(key) = self.key.get() {
+            return Ok(*key);
+        }
+        let prompted;
+        let passphrase = match &self.passphrase {
+            PassphraseSource::Fixed(passphrase) => passphrase,
+            PassphraseSource::Prompt(prompt) => {
+                prompted = Zeroizing::new(prompt()?);
+                &prompted
+            }
+        };
+        let mut key = EncryptionKey::default();
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &self.salt, &mut key)
+            .map_err(|err| Error::encryption(err.to_string()))?;
+        let cached = *self.key.get_or_init(|| key);
+        key.zeroize();
+        Ok(cached)
+    }
+}
+
+impl Drop for PassphraseKey {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.get_mut() {
+            key.zeroize();
+        }
+    }
+}
+
+/// A key provider using the first available provider in a list.
+///
+/// Providers are checked in the order they were added with [`KeyProvider::is_available`].
+/// The first available one supplies the key, and if it fails the error is returned rather
+/// than trying the next one, since a different key could not decrypt existing files. This
+/// lets the same binary use the TPM on workstations, the keyring on desktops without one,
+/// and prompt for a passphrase in containers.
+///
+/// ```no_run
+/// # use libset::{Config, EncryptedKeys, Encryption, KeyChain, PassphraseKey, StaticKey};
+/// # fn main() -> Result<(), libset::Error> {
+/// let chain = KeyChain::default()
+///     .with(StaticKey::new([0; 32]))
+///     .with(PassphraseKey::prompt(
+///         || std::env::var("DEMO_PASSPHRASE").map_err(|err| libset::Error::Env {
+///             variable: "DEMO_PASSPHRASE".to_string(),
+///             reason: err.to_string(),
+///         }),
+///         "org.example.Demo",
+///     ));
+/// let mut config = Config::new("org.example.Demo", 1, None)?;
+/// config.add_middleware(Encryption::new(chain, EncryptedKeys::All));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct KeyChain {
+    providers: Vec<Box<dyn KeyProvider>>,
+}
+
+impl KeyChain {
+    /// Adds a provider, used when every provider added before it is unavailable.
+    pub fn with(mut self, provider: impl KeyProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+}
+
+impl KeyProvider for KeyChain {
+    fn key(&self) -> Result<EncryptionKey, Error> {
+        self.providers
+            .iter()
+            .find(|provider| provider.is_available())
+            .ok_or_else(|| Error::encryption("No key provider is available"))?
+            .key()
+    }
+
+    fn is_available(&self) -> bool {
+        self.providers
+            .iter()
+            .any(|provider| provider.is_available())
+    }
+}
+
+/// A key provider storing the key in the operating system keyring.
+///
+/// A random key is generated and saved the first time the entry is missing.
+#[cfg(feature = "keyring")]
+pub struct KeyringKey {
+    service: String,
+    user: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringKey {
+    /// Creates a provider for the given keyring service and user.
+    pub fn new(service: impl Into<String>, user: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            user: user.into(),
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl KeyProvider for KeyringKey {
+    fn key(&self) -> Result<EncryptionKey, Error> {
+        let keyring_error = |err: keyring::Error| Error::encryption(err.to_string());
+        let entry = keyring::Entry::new(&self.service, &self.user).map_err(keyring_error)?;
+        match entry.get_password() {
+            Ok(mut encoded) => {
+                let key = decode_hex(&encoded);
+                encoded.zeroize();
+                key.ok_or_else(|| Error::encryption("Malformed key in keyring"))
+            }
+            Err(keyring::Error::NoEntry) => {
+                let key: EncryptionKey = XChaCha20Poly1305::generate_key(&mut OsRng).into();
+                entry
+                    .set_password(&encode_hex(&key))
+                    .map_err(keyring_error)?;
+                Ok(key)
+            }
+            Err(err) => Err(keyring_error(err)),
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        keyring::Entry::new(&self.service, &self.user)
+            .is_ok_and(|entry| matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry)))
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn decode_hex(encoded: &str) -> Option<EncryptionKey> {
+    let mut key = EncryptionKey::default();
+    if encoded.len() != key.len() * 2 {
+        return None;
+    }
+    for (byte, chunk) in key.iter_mut().zip(encoded.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+    }
+    Some(key)
+}
+
+/// Selects which keys the [`Encryption`] middleware applies to.
+///
+/// Keys are compared ignoring case and Unicode normalization, so every spelling that can
+/// resolve to the same file is covered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptedKeys {
+    /// Every key is encrypted.
+    All,
+    /// Keys starting with the prefix are encrypted.
+    Prefix(String),
+    /// Only the listed keys are encrypted.
+    List(Vec<String>),
+}
+
+impl EncryptedKeys {
+    fn matches(&self, key: &str) -> bool {
+        let key = normalize_key(key, true, true);
+        match self {
+            EncryptedKeys::All => true,
+            EncryptedKeys::Prefix(prefix) => key.starts_with(&*normalize_key(prefix, true, true)),
+            EncryptedKeys::List(keys) => keys
+                .iter()
+                .any(|candidate| normalize_key(candidate, true, true) == key),
+        }
+    }
+}
+
+/// Middleware encrypting file contents with XChaCha20-Poly1305.
+///
+/// The key and format of a file are authenticated along with its contents, so an encrypted
+/// file copied over another key fails to decrypt. Files in plain text are read as they are,
+/// use [`Config::with_required_encryption`] to reject them.
+///
+/// ```no_run
+/// # use libset::{Config, EncryptedKeys, Encryption, PassphraseKey};
+/// # fn main() -> Result<(), libset::Error> {
+/// let mut config = Config::new("org.example.Demo", 1, None)?;
+/// config.add_middleware(Encryption::new(
+///     PassphraseKey::new("correct horse battery staple", "org.example.Demo"),
+///     EncryptedKeys::Prefix("secret-".to_string()),
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+pub struct Encryption {
+    source: Source,
+    keys: EncryptedKeys,
+}
+
+/// Where the [`Encryption`] middleware gets its keys from.
+enum Source {
+    Provider(Arc<dyn KeyProvider>),
+    Passphrase(Passphrase),
+}
+
+/// Derives keys from a passphrase with the parameters stored in each file.
+struct Passphrase {
+    passphrase: Zeroizing<String>,
+    /// The derivation parameters and salt used for new files.
+    params: [u8; PARAMS_LEN],
+    /// The keys derived so far, by their parameters.
+    derived: Mutex<HashMap<[u8; PARAMS_LEN], Zeroizing<EncryptionKey>>>,
+}
+
+impl Passphrase {
+    /// Derives the key of a file from its parameters.
+    fn key(
+        &self,
+        params: &[u8; PARAMS_LEN],
+        file_key: &str,
+    ) -> Result<Zeroizing<EncryptionKey>, Error> {
+        let mut derived = self.derived.lock().unwrap();
+        if let Some(key) = derived.get(params) {
+            return Ok(key.clone());
+        }
+        let cost = |index: usize| {
+            u32::from_le_bytes([
+                params[index],
+                params[index + 1],
+                params[index + 2],
+                params[index + 3],
+            ])
+        };
+        let (m_cost, t_cost, p_cost) = (cost(0), cost(4), cost(8));
+        if m_cost > Params::DEFAULT_M_COST * MAX_COST_FACTOR
+            || t_cost > Params::DEFAULT_T_COST * MAX_COST_FACTOR
+            || p_cost > Params::DEFAULT_P_COST * MAX_COST_FACTOR
+        {
+            return Err(failure(
+                file_key,
+                format!("key derivation costs {m_cost}, {t_cost} and {p_cost} exceed the limit"),
+            ));
+        }
+        let argon2 = Params::new(m_cost, t_cost, p_cost, Some(32))
+            .map(|argon2_params| Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params))
+            .map_err(|err| failure(file_key, err.to_string()))?;
+        let mut key = Zeroizing::new(EncryptionKey::default());
+        argon2
+            .hash_password_into(self.passphrase.as_bytes(), &params[12..], &mut *key)
+            .map_err(|err| failure(file_key, err.to_string()))?;
+        derived.insert(*params, key.clone());
+        Ok(key)
+    }
+}
+
+impl Encryption {
+    /// Creates the middleware from a key provider and the keys it applies to.
+    pub fn new(provider: impl KeyProvider + 'static, keys: EncryptedKeys) -> Self {
+        Self {
+            source: Source::Provider(Arc::new(provider)),
+            keys,
+        }
+    }
+
+    /// Creates the middleware from a passphrase and the keys it applies to.
+    ///
+    /// Each file stores the Argon2id parameters and the random salt its key was derived
+    /// with, so no key material or salt has to be kept anywhere else and files stay readable
+    /// if the defaults change. Derived keys are cached, only the first file read with new
+    /// parameters pays for the derivation. Use [`Config::reencrypt`] to change the passphrase.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, EncryptedKeys, Encryption};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let mut config = Config::new("org.example.Demo", 1, None)?;
+    /// config.add_middleware(Encryption::passphrase(
+    ///     "correct horse battery staple",
+    ///     EncryptedKeys::Prefix("secret-".to_string()),
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn passphrase(passphrase: impl Into<String>, keys: EncryptedKeys) -> Self {
+        let mut params = [0; PARAMS_LEN];
+        params[0..4].copy_from_slice(&Params::DEFAULT_M_COST.to_le_bytes());
+        params[4..8].copy_from_slice(&Params::DEFAULT_T_COST.to_le_bytes());
+        params[8..12].copy_from_slice(&Params::DEFAULT_P_COST.to_le_bytes());
+        OsRng.fill_bytes(&mut params[12..]);
+        Self {
+            source: Source::Passphrase(Passphrase {
+                passphrase: Zeroizing::new(passphrase.into()),
+                params,
+                derived: Mutex::default(),
+            }),
+            keys,
+        }
+    }
+
+    /// Encrypts data for the given key, regardless of the keys the middleware applies to.
+    fn encrypt(&self, data: &mut Vec<u8>, key: &str, file_type: FileType) -> Result<(), Error> {
+        let failed = |_| failure(key, "failed to encrypt");
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let encrypted = match &self.source {
+            Source::Provider(provider) => {
+                let payload = Payload {
+                    msg: data,
+                    aad: &associated_data(MAGIC, key, file_type),
+                };
+                let ciphertext = cipher(provider.key()?)
+                    .encrypt(&nonce, payload)
+                    .map_err(failed)?;
+                [MAGIC, nonce.as_slice(), &ciphertext].concat()
+            }
+            Source::Passphrase(passphrase) => {
+                // The header is authenticated, so the parameters can't be swapped.
+                let header = [PASSPHRASE_MAGIC, &passphrase.params].concat();
+                let payload = Payload {
+                    msg: data,
+                    aad: &associated_data(&header, key, file_type),
+                };
+                let ciphertext = cipher(*passphrase.key(&passphrase.params, key)?)
+                    .encrypt(&nonce, payload)
+                    .map_err(failed)?;
+                [&header, nonce.as_slice(), &ciphertext].concat()
+            }
+        };
+        data.zeroize();
+        *data = encrypted;
+        Ok(())
+    }
+
+    /// Decrypts data encrypted by this middleware, leaving other data untouched.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing whether the data was encrypted or an `Error` if it could not be
+    /// decrypted.
+    fn decrypt(&self, data: &mut Vec<u8>, key: &str, file_type: FileType) -> Result<bool, Error> {
+        let failed = |_| failure(key, "failed to decrypt");
+        let truncated = || failure(key, "the encrypted data is truncated");
+        let plaintext = if let Some(body) = data.strip_prefix(MAGIC) {
+            let Source::Provider(provider) = &self.source else {
+                return Err(failure(key, "not encrypted with a passphrase"));
+            };
+            if body.len() < NONCE_LEN {
+                return Err(truncated());
+            }
+            let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+            let payload = Payload {
+                msg: ciphertext,
+                aad: &associated_data(MAGIC, key, file_type),
+            };
+            cipher(provider.key()?)
+                .decrypt(XNonce::from_slice(nonce), payload)
+                .map_err(failed)?
+        } else if let Some(body) = data.strip_prefix(PASSPHRASE_MAGIC) {
+            let Source::Passphrase(passphrase) = &self.source else {
+                return Err(failure(key, "encrypted with a passphrase"));
+            };
+            if body.len() < PARAMS_LEN + NONCE_LEN {
+                return Err(truncated());
+            }
+            let (header, body) = data.split_at(PASSPHRASE_MAGIC.len() + PARAMS_LEN);
+            let params = header[PASSPHRASE_MAGIC.len()..].try_into().unwrap();
+            let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+            let payload = Payload {
+                msg: ciphertext,
+                aad: &associated_data(header, key, file_type),
+            };
+            cipher(*passphrase.key(params, key)?)
+                .decrypt(XNonce::from_slice(nonce), payload)
+                .map_err(failed)?
+        } else {
+            return Ok(false);
+        };
+        *data = plaintext;
+        Ok(true)
+    }
+}
+
+/// Builds the error for a failure to encrypt or decrypt a key.
+fn failure(key: &str, reason: impl Into<String>) -> Error {
+    Error::Encryption {
+        key: Some(key.to_string()),
+        reason: reason.into(),
+    }
+}
+
+/// Binds encrypted data to its header, key and format, so files can't be swapped between keys.
+fn associated_data(header: &[u8], key: &str, file_type: FileType) -> Vec<u8> {
+    let format = file_type.to_string();
+    [header, key.as_bytes(), b"\0", format.as_bytes()].concat()
+}
+
+/// Builds a cipher from a key, wiping the key afterwards.
+fn cipher(mut key: EncryptionKey) -> XChaCha20Poly1305 {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    key.zeroize();
+    cipher
+}
+
+impl Middleware for Encryption {
+    fn apply(
+        &self,
+        data: &mut Vec<u8>,
+        direction: Direction,
+        entry: &EntryInfo,
+    ) -> Result<(), Error> {
+        match direction {
+            Direction::Write if self.keys.matches(entry.key) => {
+                self.encrypt(data, entry.key, entry.file_type)
+            }
+            Direction::Write => Ok(()),
+            Direction::Read => self.decrypt(data, entry.key, entry.file_type).map(drop),
+        }
+    }
+}
+
+impl Config {
+    /// Requires the given keys to be encrypted whenever they are written and read.
+    ///
+    /// Writes to a matching key fail with [`Error::Encryption`] unless a middleware, such as
+    /// [`Encryption`], encrypted the data, so a missing or misconfigured middleware can't
+    /// silently store secrets in plain text. Reads of a matching key fail the same way when
+    /// the stored file is not encrypted, so a file replaced with plain text isn't trusted. To
+    /// cover a whole scope, set the policy on a `Config` moved into that scope with
+    /// [`EncryptedKeys::All`].
+    ///
+    /// ```no_run
+    /// # use libset::{Config, EncryptedKeys};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?
+    ///     .with_required_encryption(EncryptedKeys::List(vec!["tokens".to_string()]));
+    /// // Fails, no middleware encrypts `tokens`.
+    /// assert!(config.set_json("tokens", ["hunter2"]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys that must be encrypted.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_required_encryption(mut self, keys: EncryptedKeys) -> Self {
+        self.required_encryption = Some(keys);
+        self
+    }
+
+    /// Fails if a key must be encrypted and the data written or read for it is not.
+    pub(crate) fn check_encrypted(
+        &self,
+        key: &str,
+        data: &[u8],
+        direction: Direction,
+    ) -> Result<(), Error> {
+        let required = self
+            .required_encryption
+            .as_ref()
+            .is_some_and(|keys| keys.matches(key));
+        if required && !data.starts_with(MAGIC) && !data.starts_with(PASSPHRASE_MAGIC) {
+            let reason = match direction {
+                Direction::Write => "no middleware encrypted it",
+                Direction::Read => "it is stored in plain text",
+            };
+            return Err(failure(key, format!("must be encrypted, but {reason}")));
+        }
+        Ok(())
+    }
+
+    /// Re-encrypts every encrypted file in the version root and its scopes with another key.
+    ///
+    /// Use this to change the passphrase or rotate the key of the [`Encryption`] middleware.
+    /// Every file is decrypted before any is written, so a wrong old key leaves the store
+    /// untouched. Files that aren't encrypted are skipped. Register the new middleware on the
+    /// `Config` afterwards.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, EncryptedKeys, Encryption};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let keys = EncryptedKeys::Prefix("secret-".to_string());
+    /// let old = Encryption::passphrase("correct horse battery staple", keys.clone());
+    /// let new = Encryption::passphrase("a much better passphrase", keys);
+    ///
+    /// let mut config = Config::new("org.example.Demo", 1, None)?;
+    /// config.reencrypt(&old, &new)?;
+    /// config.add_middleware(new);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The middleware the files are currently encrypted with.
+    /// * `to` - The middleware to encrypt the files with.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the paths of the re-encrypted files or an `Error` if an error
+    /// occurred.
+    pub fn reencrypt(&self, from: &Encryption, to: &Encryption) -> Result<Vec<PathBuf>, Error> {
+        self.check_sealed()?;
+        self.flush()?;
+        let mut files = Vec::new();
+        for entry in self.walk()? {
+            let entry = entry?;
+            let mut data = self
+                .fs
+                .read(&entry.path)
+                .map_err(|err| Error::io(&entry.path, err))?;
+            let key = self.normalized_key(&entry.key);
+            if from.decrypt(&mut data, &key, entry.file_type)? {
+                to.encrypt(&mut data, &key, entry.file_type)?;
+                files.push((entry.path, data));
+            }
+        }
+        for (path, data) in &files {
+            self.cache.invalidate(path);
+            self.fs
+                .write(path, data, false)
+                .map_err(|err| Error::write(path, err))?;
+        }
+        Ok(files.into_iter().map(|(path, _)| path).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EncryptedKeys, Encryption, StaticKey, PASSPHRASE_MAGIC};
+    use crate::{utils::FileType, Config, Error};
+
+    #[test]
+    fn rejects_excessive_derivation_costs() {
+        let encryption = Encryption::passphrase("passphrase", EncryptedKeys::All);
+        let mut data = b"secret".to_vec();
+        encryption
+            .encrypt(&mut data, "tokens", FileType::Json)
+            .unwrap();
+        let m_cost = PASSPHRASE_MAGIC.len();
+        data[m_cost..m_cost + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(matches!(
+            encryption.decrypt(&mut data, "tokens", FileType::Json),
+            Err(Error::Encryption { .. })
+        ));
+    }
+
+    #[test]
+    fn binds_files_to_their_key_and_format() {
+        let encryption = Encryption::new(StaticKey::new([7; 32]), EncryptedKeys::All);
+        let mut data = b"secret".to_vec();
+        encryption
+            .encrypt(&mut data, "tokens", FileType::Json)
+            .unwrap();
+
+        for (key, file_type) in [("passwords", FileType::Json), ("tokens", FileType::Plain)] {
+            assert!(encryption
+                .decrypt(&mut data.clone(), key, file_type)
+                .is_err());
+        }
+        assert!(encryption
+            .decrypt(&mut data, "tokens", FileType::Json)
+            .unwrap());
+        assert_eq!(data, b"secret");
+    }
+
+    #[test]
+    fn required_keys_reject_plain_text_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = EncryptedKeys::List(vec!["tokens".to_string()]);
+        let mut config = Config::new_in(dir.path(), "org.example.Test", 1, None)
+            .unwrap()
+            .with_required_encryption(keys.clone());
+        config.add_middleware(Encryption::new(StaticKey::new([7; 32]), keys));
+        config.set_json("tokens", ["hunter2"]).unwrap();
+        assert_eq!(
+            config.get_json::<Vec<String>>("tokens").unwrap(),
+            ["hunter2"]
+        );
+
+        std::fs::write(config.path.join("tokens.json"), r#"["attacker"]"#).unwrap();
+        assert!(matches!(
+            config.get_json::<Vec<String>>("tokens"),
+            Err(Error::Encryption { .. })
+        ));
+    }
+
+    #[test]
+    fn policies_cover_every_spelling_of_a_case_insensitive_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = EncryptedKeys::List(vec!["Tokens".to_string()]);
+        let mut config = Config::new_in(dir.path(), "org.example.Test", 1, None)
+            .unwrap()
+            .with_case_insensitive_keys(true)
+            .with_required_encryption(keys.clone());
+        assert!(config.set_json("TOKENS", ["hunter2"]).is_err());
+
+        config.add_middleware(Encryption::new(StaticKey::new([7; 32]), keys));
+        config.set_json("TOKENS", ["hunter2"]).unwrap();
+        assert_eq!(
+            config.get_json::<Vec<String>>("tokens").unwrap(),
+            ["hunter2"]
+        );
+
+        std::fs::write(config.path.join("tokens.json"), r#"["attacker"]"#).unwrap();
+        assert!(matches!(
+            config.get_json::<Vec<String>>("ToKeNs"),
+            Err(Error::Encryption { .. })
+        ));
+    }
+
+    #[test]
+    fn normalized_keys_share_their_encryption() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = EncryptedKeys::Prefix("caf\u{e9}".to_string());
+        let mut config = Config::new_in(dir.path(), "org.example.Test", 1, None)
+            .unwrap()
+            .with_normalized_keys(true)
+            .with_required_encryption(keys.clone());
+        config.add_middleware(Encryption::new(StaticKey::new([7; 32]), keys));
+        config.set_json("cafe\u{301}-menu", ["espresso"]).unwrap();
+        assert_eq!(
+            config.get_json::<Vec<String>>("caf\u{e9}-menu").unwrap(),
+            ["espresso"]
+        );
+
+        let old = Encryption::new(StaticKey::new([7; 32]), EncryptedKeys::All);
+        let new = Encryption::new(StaticKey::new([8; 32]), EncryptedKeys::All);
+        assert_eq!(config.reencrypt(&old, &new).unwrap().len(), 1);
+        let mut config = Config::new_in(dir.path(), "org.example.Test", 1, None)
+            .unwrap()
+            .with_normalized_keys(true);
+        config.add_middleware(new);
+        assert_eq!(
+            config.get_json::<Vec<String>>("cafe\u{301}-menu").unwrap(),
+            ["espresso"]
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use crate::{filesystem::Filesystem, utils::FileType, Error};
+
+/// Prefix of the temporary directories created by the atomic writer, and of every other file
+/// libset writes next to keys. Keys starting with it are rejected by [`KeyValidator`].
+///
+/// [`KeyValidator`]: crate::KeyValidator
+pub(crate) const ATOMIC_WRITE_PREFIX: &str = ".atomicwrite";
+/// Suffix of the temporary files created by batched writes.
+pub(crate) const TEMP_SUFFIX: &str = ".tmp";
+/// Suffix of the backups kept while a file is overwritten in place, named after
+/// [`ATOMIC_WRITE_PREFIX`].
+pub(crate) const BACKUP_SUFFIX: &str = ".bak";
+/// Suffix of the files holding advisory locks.
+pub(crate) const LOCK_SUFFIX: &str = ".lock";
+/// Name of the directory holding named locks, inside the version directory.
+pub(crate) const LOCKS_DIR: &str = ".locks";
+/// Name of the marker sealing the store against writes, inside the version directory.
+pub(crate) const SEAL_MARKER: &str = ".sealed";
+/// Name of the marker recording the import of legacy files, inside the version directory.
+pub(crate) const LEGACY_MARKER: &str = ".legacy-imported";
+
+/// Splits a file name into its key and file type.
+///
+/// # Arguments
+///
+/// * `name` - The file name.
+///
+/// # Returns
+///
+/// The key and the `FileType` matching the extension, names without a known extension are
+/// treated as plain files.
+pub(crate) fn split_name(name: &str) -> (String, FileType) {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => match FileType::from_extension(extension) {
+            Some(file_type) => (stem.to_string(), file_type),
+            None => (name.to_string(), FileType::Plain),
+        },
+        _ => (name.to_string(), FileType::Plain),
+    }
+}
+
+/// Returns the scope of a path relative to the version root.
+///
+/// # Arguments
+///
+/// * `root` - The version root.
+/// * `path` - The path of a file under the root.
+///
+/// # Returns
+///
+/// The directory containing the file relative to the root, or `None` for the root itself.
+pub(crate) fn scope_of(root: &Path, path: &Path) -> Option<PathBuf> {
+    path.parent()
+        .and_then(|parent| parent.strip_prefix(root).ok())
+        .filter(|scope| !scope.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+}
+
+/// Returns the name of a temporary file holding the new contents of `name` during a batch.
+///
+/// The name is unique within the process thanks to `counter`, and across processes thanks to
+/// the process id.
+pub(crate) fn temporary_name(name: &str, counter: usize) -> String {
+    format!(
+        "{ATOMIC_WRITE_PREFIX}.{name}.{}.{counter}{TEMP_SUFFIX}",
+        std::process::id()
+    )
+}
+
+/// Checks whether a file name was created by [`temporary_name`].
+pub(crate) fn is_temporary_file(name: &str) -> bool {
+    let Some(rest) = name
+        .strip_prefix(ATOMIC_WRITE_PREFIX)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|rest| rest.strip_suffix(TEMP_SUFFIX))
+    else {
+        return false;
+    };
+    let mut parts = rest.rsplitn(3, '.');
+    let counter = parts.next().unwrap_or_default();
+    let pid = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    !target.is_empty() && is_number(pid) && is_number(counter)
+}
+
+/// Checks whether a path is, or lies inside, a temporary file, backup or lock file created by
+/// libset.
+pub(crate) fn is_temporary(path: &Path) -> bool {
+    path.components().any(|component| {
+        let name = component.as_os_str().to_string_lossy();
+        name.starts_with(ATOMIC_WRITE_PREFIX)
+            || name == LOCKS_DIR
+            || name == SEAL_MARKER
+            || name == LEGACY_MARKER
+            || (name.starts_with('.') && name.ends_with(LOCK_SUFFIX))
+    })
+}
+
+/// Represents a file stored in a configuration directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The key used to store the file.
+    pub key: String,
+    /// The scope the file belongs to, relative to the version root.
+    pub scope: Option<PathBuf>,
+    /// The format of the file, derived from its extension.
+    pub file_type: FileType,
+    /// The absolute path of the file.
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub len: u64,
+    /// The last modification time, if the platform reports it.
+    pub modified: Option<SystemTime>,
+}
+
+impl Entry {
+    /// Builds an entry from a file in the configuration directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `fs` - The filesystem the file is stored in.
+    /// * `path` - The path of the file.
+    /// * `scope` - The scope the file belongs to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the entry, `None` if the path is not a stored key, or an `Error`
+    /// if its metadata could not be read.
+    pub(crate) fn from_path(
+        fs: &dyn Filesystem,
+        path: &Path,
+        scope: Option<PathBuf>,
+    ) -> Result<Option<Self>, Error> {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return Ok(None);
+        };
+        if is_temporary(Path::new(name)) {
+            return Ok(None);
+        }
+        let metadata = fs.metadata(path).map_err(|err| Error::io(path, err))?;
+        let (key, file_type) = split_name(name);
+        Ok(Some(Self {
+            key,
+            scope,
+            file_type,
+            path: path.to_path_buf(),
+            len: metadata.len,
+            modified: metadata.modified,
+        }))
+    }
+}
+
+/// Iterator over the entries of a configuration directory.
+///
+/// The directory is listed in full when the iterator is created, so large directories are
+/// paid for before the first entry. The metadata of each entry is read lazily, directories
+/// and temporary files are skipped.
+pub struct Entries {
+    fs: Arc<dyn Filesystem>,
+    inner: std::vec::IntoIter<PathBuf>,
+    scope: Option<PathBuf>,
+}
+
+impl Entries {
+    pub(crate) fn new(
+        fs: Arc<dyn Filesystem>,
+        path: &Path,
+        scope: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        let files = fs.list(path).map_err(|err| Error::io(path, err))?;
+        Ok(Self {
+            fs,
+            inner: files.into_iter(),
+            scope,
+        })
+    }
+}
+
+impl Iterator for Entries {
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let path = self.inner.next()?;
+            match Entry::from_path(self.fs.as_ref(), &path, self.scope.clone()) {
+                Ok(Some(entry)) => return Some(Ok(entry)),
+                Ok(None) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// The files and directories of a directory that [`Walk`] has yet to visit.
+struct Pending {
+    files: std::vec::IntoIter<PathBuf>,
+    dirs: std::vec::IntoIter<PathBuf>,
+}
+
+impl Pending {
+    fn list(fs: &dyn Filesystem, dir: &Path) -> Result<Self, Error> {
+        let files = fs.list(dir).map_err(|err| Error::io(dir, err))?;
+        let dirs = fs.list_dirs(dir).map_err(|err| Error::io(dir, err))?;
+        Ok(Self {
+            files: files.into_iter(),
+            dirs: dirs.into_iter(),
+        })
+    }
+}
+
+/// Recursive iterator over the entries of a version root and all of its scopes.
+pub struct Walk {
+    fs: Arc<dyn Filesystem>,
+    root: PathBuf,
+    stack: Vec<Pending>,
+}
+
+impl Walk {
+    pub(crate) fn new(fs: Arc<dyn Filesystem>, root: &Path) -> Result<Self, Error> {
+        let pending = Pending::list(fs.as_ref(), root)?;
+        Ok(Self {
+            fs,
+            root: root.to_path_buf(),
+            stack: vec![pending],
+        })
+    }
+}
+
+impl Iterator for Walk {
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let pending = self.stack.last_mut()?;
+            if let Some(path) = pending.files.next() {
+                match Entry::from_path(self.fs.as_ref(), &path, scope_of(&self.root, &path)) {
+                    Ok(Some(entry)) => return Some(Ok(entry)),
+                    Ok(None) => continue,
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+            let Some(dir) = pending.dirs.next() else {
+                self.stack.pop();
+                continue;
+            };
+            let hidden = dir
+                .file_name()
+                .is_some_and(|name| is_temporary(Path::new(name)));
+            if hidden {
+                continue;
+            }
+            match Pending::list(self.fs.as_ref(), &dir) {
+                Ok(pending) => self.stack.push(pending),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
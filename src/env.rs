@@ -0,0 +1,595 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{
+    de::{
+        self, value::StringDeserializer, DeserializeOwned, DeserializeSeed, EnumAccess,
+        IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer, Serialize,
+};
+
+use crate::{
+    traits::{Get, Set},
+    utils::FileType,
+    Config, Error,
+};
+
+/// A document of any format, reduced to the shape needed to flatten it.
+pub(crate) enum Node {
+    Null,
+    Scalar(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Node, E> {
+        Ok(Node::Scalar(value.to_string()))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Node, E> {
+        Ok(Node::Scalar(value.to_string()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Node, E> {
+        Ok(Node::Scalar(value.to_string()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Node, E> {
+        Ok(Node::Scalar(value.to_string()))
+    }
+
+    fn visit_char<E>(self, value: char) -> Result<Node, E> {
+        Ok(Node::Scalar(value.to_string()))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Node, E> {
+        Ok(Node::Scalar(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Node, E> {
+        Ok(Node::Scalar(value))
+    }
+
+    fn visit_none<E>(self) -> Result<Node, E> {
+        Ok(Node::Null)
+    }
+
+    fn visit_unit<E>(self) -> Result<Node, E> {
+        Ok(Node::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Node, D::Error> {
+        Node::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<Node, D::Error> {
+        Node::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::Seq(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut entries = Vec::new();
+        while let Some((key, value)) = map.next_entry::<Node, Node>()? {
+            let key = match key {
+                Node::Scalar(key) => key,
+                _ => continue,
+            };
+            entries.push((key, value));
+        }
+        Ok(Node::Map(entries))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Node, A::Error> {
+        let (variant, access) = data.variant::<String>()?;
+        access.unit_variant()?;
+        Ok(Node::Scalar(variant))
+    }
+}
+
+/// Converts a name into an environment variable segment, such as `accent-color` into
+/// `ACCENT_COLOR`.
+fn segment(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Appends a segment to a variable name.
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        segment(name)
+    } else {
+        format!("{prefix}_{}", segment(name))
+    }
+}
+
+/// Adds a variable for every scalar in a document, null values are skipped.
+fn flatten(name: String, node: Node, vars: &mut Vec<(String, String)>) {
+    match node {
+        Node::Null => {}
+        Node::Scalar(value) => vars.push((name, value)),
+        Node::Seq(items) => {
+            for (index, item) in items.into_iter().enumerate() {
+                flatten(join(&name, &index.to_string()), item, vars);
+            }
+        }
+        Node::Map(entries) => {
+            for (key, value) in entries {
+                flatten(join(&name, &key), value, vars);
+            }
+        }
+    }
+}
+
+/// Failure raised while deserializing environment variables.
+#[derive(Debug)]
+struct EnvError(String);
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+impl de::Error for EnvError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// Deserializes the variable `name`, or the variables nested under it, into a value.
+///
+/// Struct fields are looked up by name, so field names containing underscores are matched
+/// exactly rather than being treated as nesting.
+struct EnvDeserializer<'a> {
+    vars: &'a BTreeMap<String, String>,
+    name: String,
+}
+
+impl<'a> EnvDeserializer<'a> {
+    fn child(&self, name: &str) -> Self {
+        Self {
+            vars: self.vars,
+            name: join(&self.name, name),
+        }
+    }
+
+    fn value(&self) -> Option<&'a String> {
+        self.vars.get(&self.name)
+    }
+
+    fn required(&self) -> Result<&'a String, EnvError> {
+        self.value()
+            .ok_or_else(|| EnvError(format!("missing variable {}", self.name)))
+    }
+
+    /// Returns the names of the variables nested under this one, without the shared prefix.
+    fn nested(&self) -> impl Iterator<Item = &'a str> + '_ {
+        let prefix = if self.name.is_empty() {
+            String::new()
+        } else {
+            format!("{}_", self.name)
+        };
+        self.vars
+            .keys()
+            .filter_map(move |name| name.strip_prefix(prefix.as_str()))
+            .filter(|name| !name.is_empty())
+    }
+
+    fn is_present(&self) -> bool {
+        self.value().is_some() || self.nested().next().is_some()
+    }
+
+    fn parse<T: std::str::FromStr>(&self) -> Result<T, EnvError>
+    where
+        T::Err: fmt::Display,
+    {
+        let value = self.required()?;
+        value
+            .trim()
+            .parse()
+            .map_err(|err| EnvError(format!("invalid value {value:?} for {}: {err}", self.name)))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for EnvDeserializer<'_> {
+    type Error = EnvError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value() {
+            Some(value) => visitor.visit_str(value),
+            None if self.is_present() => self.deserialize_map(visitor),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.required()?.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => visitor.visit_bool(true),
+            "false" | "0" | "no" | "off" | "" => visitor.visit_bool(false),
+            value => Err(EnvError(format!(
+                "invalid value {value:?} for {}: expected a boolean",
+                self.name
+            ))),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.required()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.is_present() {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Reads numbered variables such as `NAME_0` and `NAME_1`, or splits `NAME` on commas.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let items: Vec<EnvDeserializer> = (0..)
+            .map(|index| self.child(&index.to_string()))
+            .take_while(EnvDeserializer::is_present)
+            .collect();
+        if items.is_empty() {
+            let values = match self.value() {
+                Some(value) if !value.is_empty() => value.split(',').map(str::trim).collect(),
+                _ => Vec::new(),
+            };
+            let values = values
+                .into_iter()
+                .map(|value| value.to_string().into_deserializer());
+            return visitor.visit_seq(de::value::SeqDeserializer::new(values));
+        }
+        visitor.visit_seq(EnvSeq(items.into_iter()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    /// Uses the next segment of every nested variable, lower-cased, as a key.
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let mut keys: Vec<String> = self
+            .nested()
+            .map(|name| name.split('_').next().unwrap_or(name).to_ascii_lowercase())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        visitor.visit_map(EnvMap {
+            parent: self,
+            keys: keys.into_iter(),
+            current: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let keys: Vec<String> = fields
+            .iter()
+            .filter(|field| self.child(field).is_present())
+            .map(|field| field.to_string())
+            .collect();
+        visitor.visit_map(EnvMap {
+            parent: self,
+            keys: keys.into_iter(),
+            current: None,
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let value: StringDeserializer<EnvError> = self.required()?.clone().into_deserializer();
+        visitor.visit_enum(value)
+    }
+
+    forward_to_deserialize_any! {
+        bytes byte_buf unit_struct identifier ignored_any
+    }
+}
+
+/// Items of a sequence stored as numbered variables.
+struct EnvSeq<'a>(std::vec::IntoIter<EnvDeserializer<'a>>);
+
+impl<'de> SeqAccess<'de> for EnvSeq<'_> {
+    type Error = EnvError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.0.next().map(|item| seed.deserialize(item)).transpose()
+    }
+}
+
+/// Fields of a struct or map stored as nested variables.
+struct EnvMap<'a> {
+    parent: EnvDeserializer<'a>,
+    keys: std::vec::IntoIter<String>,
+    current: Option<String>,
+}
+
+impl<'de> MapAccess<'de> for EnvMap<'_> {
+    type Error = EnvError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some(key) = self.keys.next() else {
+            return Ok(None);
+        };
+        self.current = Some(key.clone());
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let key = self.current.take().unwrap_or_default();
+        seed.deserialize(self.parent.child(&key))
+    }
+}
+
+/// Quotes a value for POSIX shells.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+impl Config {
+    /// Flattens a stored document into environment variables.
+    ///
+    /// Nested fields are joined with underscores and upper-cased, so `{ "window": { "width":
+    /// 800 } }` exported with the prefix `APP` becomes `APP_WINDOW_WIDTH=800`. Array items are
+    /// numbered from zero and null values are skipped. A plain file becomes a single variable
+    /// named after the prefix.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let vars = config.export_env("server", FileType::Json, "DEMO")?;
+    /// let child = std::process::Command::new("demo-server").envs(vars).spawn();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    /// * `prefix` - The prefix of every variable name, may be empty.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the variable names and values or an `Error` if an error occurred.
+    pub fn export_env(
+        &self,
+        key: &str,
+        file_type: FileType,
+        prefix: &str,
+    ) -> Result<Vec<(String, String)>, Error> {
+        let node = match file_type {
+            FileType::Plain => Node::Scalar(self.get_plain(key)?),
+            file_type => self.get::<Node>(key, file_type)?,
+        };
+        let mut vars = Vec::new();
+        flatten(segment(prefix), node, &mut vars);
+        Ok(vars)
+    }
+
+    /// Flattens a stored document and sets the variables in the current process.
+    ///
+    /// Setting variables is only safe while no other thread reads or writes the environment,
+    /// prefer passing the result of [`Config::export_env`] to child processes when possible.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    /// * `prefix` - The prefix of every variable name, may be empty.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn apply_env(&self, key: &str, file_type: FileType, prefix: &str) -> Result<(), Error> {
+        for (name, value) in self.export_env(key, file_type, prefix)? {
+            std::env::set_var(name, value);
+        }
+        Ok(())
+    }
+
+    /// Flattens a stored document into shell `export` statements.
+    ///
+    /// Values are single-quoted, so the output can be evaluated by any POSIX shell.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    /// * `prefix` - The prefix of every variable name, may be empty.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing one `export NAME='value'` line per variable or an `Error` if an
+    /// error occurred.
+    pub fn export_env_shell(
+        &self,
+        key: &str,
+        file_type: FileType,
+        prefix: &str,
+    ) -> Result<String, Error> {
+        Ok(self
+            .export_env(key, file_type, prefix)?
+            .into_iter()
+            .map(|(name, value)| format!("export {name}={}\n", quote(&value)))
+            .collect())
+    }
+
+    /// Builds a value from environment variables.
+    ///
+    /// Variables are matched the same way [`Config::export_env`] names them: the prefix
+    /// followed by the upper-cased field names joined with underscores. Missing fields fall
+    /// back to their serde defaults, and sequences can be given as numbered variables or a
+    /// single comma-separated value.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # use serde::{Deserialize, Serialize};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Server { host: String, port: u16, allowed_origins: Vec<String> }
+    ///
+    /// // DEMO_HOST=0.0.0.0 DEMO_PORT=8080 DEMO_ALLOWED_ORIGINS=a.com,b.com
+    /// let server: Server = Config::from_env("DEMO")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The prefix of every variable name, may be empty.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value or an `Error` if the variables don't describe it.
+    pub fn from_env<T: DeserializeOwned>(prefix: &str) -> Result<T, Error> {
+        let name = segment(prefix);
+        let vars: BTreeMap<String, String> = std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .filter(|(var, _)| {
+                name.is_empty() || var == &name || var.starts_with(&format!("{name}_"))
+            })
+            .collect();
+        T::deserialize(EnvDeserializer {
+            vars: &vars,
+            name: name.clone(),
+        })
+        .map_err(|err| Error::Env {
+            variable: name,
+            reason: err.0,
+        })
+    }
+
+    /// Builds a value from environment variables and stores it under the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension, plain files are not supported.
+    /// * `prefix` - The prefix of every variable name, may be empty.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the stored value or an `Error` if an error occurred.
+    pub fn import_env<T>(&self, key: &str, file_type: FileType, prefix: &str) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        if file_type == FileType::Plain {
+            return Err(Error::UnsupportedByFormat {
+                key: key.to_string(),
+                file_type,
+                path: self.path(key, file_type)?,
+                reason: "plain files can't store structured values".to_string(),
+                suggested_format: None,
+            });
+        }
+        let value = Self::from_env(prefix)?;
+        self.set(key, file_type, &value)?;
+        Ok(value)
+    }
+}
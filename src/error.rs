@@ -1,97 +1,551 @@
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
+use crate::utils::FileType;
+#[cfg(feature = "ini")]
+use crate::Position;
+
 /// Custom error type for the library.
 #[derive(Debug, Error)]
 pub enum Error {
     /// Represents an invalid application name.
     #[error("'{0}' is not a valid application name, avoid using . or .. .")]
     InvalidName(String),
+    /// Represents a name reserved by Windows for devices, such as `con` or `nul`.
+    #[error("'{0}' is a reserved device name on Windows")]
+    ReservedName(String),
+    /// Represents a key rejected by the key validator.
+    #[error("'{key}' is not a valid key: {reason}")]
+    InvalidKey {
+        /// The rejected key.
+        key: String,
+        /// The rule the key violates.
+        reason: String,
+    },
     /// Represents a failure to write to a file.
-    #[error("Failed to write to file: {0}")]
-    Write(atomicwrites::Error<std::io::Error>),
+    #[error("Failed to write to file {}", path.display())]
+    Write {
+        /// The file that was being written.
+        path: PathBuf,
+        /// The underlying failure.
+        source: std::io::Error,
+    },
+    /// Represents a failure to overwrite a file in place, see [`WriteStrategy`].
+    ///
+    /// [`WriteStrategy`]: crate::WriteStrategy
+    #[error(
+        "Failed to write to file {} in place, it may be incomplete{}",
+        path.display(),
+        backup.as_ref().map(|backup| format!(", the previous contents are kept in {}", backup.display())).unwrap_or_default()
+    )]
+    WriteInPlace {
+        /// The file that was being written.
+        path: PathBuf,
+        /// The backup holding the previous contents, if one was made.
+        backup: Option<PathBuf>,
+        /// The underlying failure.
+        source: std::io::Error,
+    },
     /// Represents a filesystem error.
-    #[error("Filesystem error: {0}")]
-    Io(std::io::Error),
+    #[error("Filesystem error at {}", path.display())]
+    Io {
+        /// The file or directory involved.
+        path: PathBuf,
+        /// The underlying failure.
+        source: std::io::Error,
+    },
     /// Represents a missing configuration directory.
     #[error("Config directory not found")]
     NoConfigDirectory,
+    /// Represents a machine without an identifier or hostname.
+    #[error("Machine identifier not found")]
+    NoHostId,
+    /// Represents a user missing from the user database.
+    #[error("User {0} not found")]
+    UnknownUser(String),
+    /// Represents a key without a file in the filesystem.
+    #[error("Key {key} not found at {}", path.display())]
+    KeyNotFound {
+        /// The key that was requested.
+        key: String,
+        /// The format that was requested.
+        file_type: FileType,
+        /// The path where the file was expected.
+        path: PathBuf,
+    },
+    /// Represents a file that already exists when creating a new one.
+    #[error("Key {key} already exists at {}", path.display())]
+    AlreadyExists {
+        /// The key that was written.
+        key: String,
+        /// The format that was written.
+        file_type: FileType,
+        /// The path of the existing file.
+        path: PathBuf,
+    },
+    /// Represents a file that changed since its version token was issued.
+    #[error("Key {key} was modified since it was read from {}", path.display())]
+    Conflict {
+        /// The key that was written.
+        key: String,
+        /// The format that was written.
+        file_type: FileType,
+        /// The path of the file.
+        path: PathBuf,
+    },
+    /// Represents a file that ends before its contents are complete, usually because a write
+    /// was interrupted.
+    #[error("Key {key} at {} is truncated", path.display())]
+    Truncated {
+        /// The key that was requested.
+        key: String,
+        /// The format that was requested.
+        file_type: FileType,
+        /// The path of the file.
+        path: PathBuf,
+    },
     /// Represents a failure to get a key.
-    #[error("Failed to get key {0} : {1}")]
-    GetKey(String, std::io::Error),
-    /// Represents a failure to parse a ron file.
+    #[error("Failed to get key {key} from {}", path.display())]
+    GetKey {
+        /// The key that was requested.
+        key: String,
+        /// The format that was requested.
+        file_type: FileType,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: std::io::Error,
+    },
+    /// Represents a file whose contents could not be decoded as text.
+    #[error("Failed to decode key {key} from {}: {reason}", path.display())]
+    Encoding {
+        /// The key that was requested.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// Why the contents could not be decoded.
+        reason: String,
+    },
+    /// Represents a plain file whose contents could not be parsed into the requested type.
+    #[error("Failed to parse key {key} from {}: {reason}", path.display())]
+    PlainValue {
+        /// The key that was requested.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// Why the contents could not be parsed.
+        reason: String,
+    },
+    /// Represents a failure to serialize a ron file.
     #[cfg(feature = "ron")]
-    #[error("Failed to parse ron file: {0}")]
-    Ron(ron::Error),
+    #[error("Failed to serialize ron file {}", path.display())]
+    Ron {
+        /// The key being written.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: ron::Error,
+    },
     /// Represents a failure to parse a ron file with span information.
     #[cfg(feature = "ron")]
-    #[error("Failed to parse ron file: {0}")]
-    RonSpanned(ron::error::SpannedError),
-    /// Represents a failure to parse a json file.
+    #[error("Failed to parse ron file {}", path.display())]
+    RonSpanned {
+        /// The key being read.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: Box<ron::error::SpannedError>,
+        /// The contents of the file, rendered by `miette` reports.
+        #[cfg(feature = "miette")]
+        source_code: Option<Box<miette::NamedSource<String>>>,
+    },
+    /// Represents a failure to parse or serialize a json file.
     #[cfg(feature = "json")]
-    #[error("Failed to parse json file: {0}")]
-    Json(serde_json::Error),
+    #[error("Failed to parse json file {}", path.display())]
+    Json {
+        /// The key being read or written.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: serde_json::Error,
+        /// The contents of the file, rendered by `miette` reports.
+        #[cfg(feature = "miette")]
+        source_code: Option<Box<miette::NamedSource<String>>>,
+    },
+    /// Represents a failure to parse or serialize a yaml file.
+    #[cfg(feature = "yaml")]
+    #[error("Failed to parse yaml file {}", path.display())]
+    Yaml {
+        /// The key being read or written.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: serde_yaml::Error,
+        /// The contents of the file, rendered by `miette` reports.
+        #[cfg(feature = "miette")]
+        source_code: Option<Box<miette::NamedSource<String>>>,
+    },
+    /// Represents a failure to parse or serialize a json5 file.
+    #[cfg(feature = "json5")]
+    #[error("Failed to parse json5 file {}", path.display())]
+    Json5 {
+        /// The key being read or written.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: json5::Error,
+        /// The contents of the file, rendered by `miette` reports.
+        #[cfg(feature = "miette")]
+        source_code: Option<Box<miette::NamedSource<String>>>,
+    },
+    /// Represents a failure to parse an ini file or to read a value from it.
+    #[cfg(feature = "ini")]
+    #[error("Failed to parse ini file {}: {reason}", path.display())]
+    Ini {
+        /// The key being read.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// Why the file could not be read.
+        reason: String,
+        /// Where parsing failed, if the file is not valid ini.
+        position: Option<Position>,
+        /// The contents of the file, rendered by `miette` reports.
+        #[cfg(feature = "miette")]
+        source_code: Option<Box<miette::NamedSource<String>>>,
+    },
     /// Represents a failure to serialize a toml file.
     #[cfg(feature = "toml")]
-    #[error("Failed to serialize toml file: {0}")]
-    TomlSerialize(toml::ser::Error),
+    #[error("Failed to serialize toml file {}", path.display())]
+    TomlSerialize {
+        /// The key being written.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: toml::ser::Error,
+    },
+    /// Represents a write rejected because the config or store is sealed.
+    #[error("Config at {} is sealed against writes", path.display())]
+    Sealed {
+        /// The version root of the store.
+        path: PathBuf,
+    },
+    /// Represents a key repeated within an object of a json document.
+    #[error("Duplicate key {field} in {}", path.display())]
+    DuplicateKey {
+        /// The key being read.
+        key: String,
+        /// The format of the file.
+        file_type: FileType,
+        /// The path of the file.
+        path: PathBuf,
+        /// The location of the repeated key within the document.
+        field: String,
+    },
+    /// Represents layers merged into a document that doesn't match the requested type.
+    #[error("Failed to read key {key} with its layers merged over {}: {reason}", path.display())]
+    Layered {
+        /// The key being read.
+        key: String,
+        /// The format of the layers.
+        file_type: FileType,
+        /// The path of the file the layers were merged over.
+        path: PathBuf,
+        /// Why the merged document doesn't match the type.
+        reason: String,
+    },
+    /// Represents a document that could not be upgraded to its current schema version.
+    #[error("Failed to migrate key {key} at {}: {reason}", path.display())]
+    Migration {
+        /// The key of the document.
+        key: String,
+        /// The format of the document.
+        file_type: FileType,
+        /// The path of the file.
+        path: PathBuf,
+        /// Why the document could not be upgraded.
+        reason: String,
+    },
+    /// Represents a value the file format cannot store.
+    #[error("Cannot store {key} as {file_type}: {reason}{}", suggestion(.suggested_format))]
+    UnsupportedByFormat {
+        /// The key being written.
+        key: String,
+        /// The format that rejected the value.
+        file_type: FileType,
+        /// The path of the file.
+        path: PathBuf,
+        /// Why the format cannot store the value.
+        reason: String,
+        /// An enabled format able to store the value, if any.
+        suggested_format: Option<FileType>,
+    },
     /// Represents a failure to deserialize a toml file.
     #[cfg(feature = "toml")]
-    #[error("Failed to deserialize toml file: {0}")]
-    TomlDeserialize(toml::de::Error),
+    #[error("Failed to deserialize toml file {}", path.display())]
+    TomlDeserialize {
+        /// The key being read.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: Box<toml::de::Error>,
+        /// The contents of the file, rendered by `miette` reports.
+        #[cfg(feature = "miette")]
+        source_code: Option<Box<miette::NamedSource<String>>>,
+    },
+    /// Represents a failure to watch the filesystem for changes.
+    #[cfg(feature = "watch")]
+    #[error("Failed to watch {} for changes", path.display())]
+    Watch {
+        /// The directory being watched.
+        path: PathBuf,
+        /// The underlying failure.
+        source: notify::Error,
+    },
+    /// Represents a failure to build a value from environment variables.
+    #[error("Failed to read settings from the environment variable {variable}: {reason}")]
+    Env {
+        /// The variable being read, or the prefix of the variables.
+        variable: String,
+        /// Why the variables don't describe the value.
+        reason: String,
+    },
+    /// Represents a failure to encrypt or decrypt a file.
+    #[cfg(feature = "encryption")]
+    #[error("Encryption error{}: {reason}", for_key(.key))]
+    Encryption {
+        /// The key being encrypted or decrypted, unset for failures to obtain the key material.
+        key: Option<String>,
+        /// Why the operation failed.
+        reason: String,
+    },
+    /// Represents a failure to encrypt or decrypt a file with age.
+    #[cfg(feature = "age")]
+    #[error("age error for key {key} at {}: {reason}", path.display())]
+    Age {
+        /// The key being encrypted or decrypted.
+        key: String,
+        /// The path of the encrypted file.
+        path: PathBuf,
+        /// Why the operation failed.
+        reason: String,
+    },
+    /// Represents a failure to render a template.
+    #[cfg(feature = "templates")]
+    #[error("Failed to render template {key} from {}", path.display())]
+    Template {
+        /// The key of the template.
+        key: String,
+        /// The path of the template.
+        path: PathBuf,
+        /// The underlying failure.
+        source: minijinja::Error,
+    },
+    /// Represents a failure to communicate over D-Bus.
+    #[cfg(feature = "dbus")]
+    #[error("D-Bus error")]
+    Dbus {
+        /// The underlying failure.
+        source: zbus::Error,
+    },
+    /// Represents a manifest that could not be parsed.
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
+    /// Represents an archive that could not be read or imported.
+    #[error("Invalid archive{}: {reason}", in_file(.file))]
+    InvalidArchive {
+        /// The path of the archived file at fault, relative to the version root, if any.
+        file: Option<String>,
+        /// Why the archive was rejected.
+        reason: String,
+    },
+    /// Represents a failure to read or write an archive stream.
+    #[error("Failed to transfer archive")]
+    ArchiveIo {
+        /// The underlying failure.
+        source: std::io::Error,
+    },
+    /// Represents a snapshot that could not be restored.
+    #[error("Failed to restore snapshot {id}: {reason}")]
+    Snapshot {
+        /// The id of the snapshot.
+        id: String,
+        /// Why the snapshot could not be restored.
+        reason: String,
+    },
+    /// Represents a failure in code built on top of libset, see [`Error::context`].
+    #[error("Failed to process key {key} at {}", path.display())]
+    Context {
+        /// The key being processed.
+        key: String,
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying failure.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
     /// Represents a generic string error.
-    #[error("An error ocurred: {0}")]
-    Generic(String),
+    #[error("An error ocurred{}: {reason}", at_path(.path))]
+    Generic {
+        /// The file the error relates to, if any.
+        path: Option<PathBuf>,
+        /// What went wrong.
+        reason: String,
+    },
 }
 
-impl From<String> for Error {
-    fn from(f: String) -> Self {
-        Self::Generic(f)
+impl Error {
+    /// Determines if the error was caused by a missing file.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::KeyNotFound { .. } => true,
+            Self::GetKey { source, .. } | Self::Io { source, .. } => {
+                source.kind() == std::io::ErrorKind::NotFound
+            }
+            _ => false,
+        }
     }
-}
 
-impl From<atomicwrites::Error<std::io::Error>> for Error {
-    fn from(f: atomicwrites::Error<std::io::Error>) -> Self {
-        Self::Write(f)
+    /// Returns a function wrapping any error with the key and path it relates to.
+    ///
+    /// Lets code built on top of `Config` report its own failures, such as a value that
+    /// parsed but is out of range, without defining a separate error type. `Error` is
+    /// `Send + Sync + 'static`, so it converts into `anyhow::Error` and `eyre::Report` with
+    /// `?` and keeps its chain of sources.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, Error, FileType};
+    /// # fn main() -> Result<(), Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let accent: String = config.get_json("accent")?;
+    /// let path = config.path("accent", FileType::Json)?;
+    /// let rgb = u32::from_str_radix(accent.trim_start_matches('#'), 16)
+    ///     .map_err(Error::context("accent", &path))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key the failure relates to.
+    /// * `path` - The path of the file the failure relates to.
+    ///
+    /// # Returns
+    ///
+    /// A function turning an error into an `Error::Context`, for use with `map_err`.
+    pub fn context<E>(key: &str, path: impl AsRef<Path>) -> impl FnOnce(E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let key = key.to_string();
+        let path = path.as_ref().to_path_buf();
+        move |err| Self::Context {
+            key,
+            path,
+            source: err.into(),
+        }
+    }
+
+    /// Builds the error for a failed filesystem operation on a key.
+    pub(crate) fn for_key(
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+        err: std::io::Error,
+    ) -> Self {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            Self::KeyNotFound {
+                key: key.to_string(),
+                file_type,
+                path: path.to_path_buf(),
+            }
+        } else {
+            Self::GetKey {
+                key: key.to_string(),
+                file_type,
+                path: path.to_path_buf(),
+                source: err,
+            }
+        }
     }
-}
 
-impl From<std::io::Error> for Error {
-    fn from(f: std::io::Error) -> Self {
-        Self::Io(f)
+    /// Builds the error for a failed filesystem operation on a path.
+    pub(crate) fn io(path: &Path, err: std::io::Error) -> Self {
+        Self::Io {
+            path: path.to_path_buf(),
+            source: err,
+        }
+    }
+
+    /// Builds the error for a failure to obtain key material, not tied to a key.
+    #[cfg(feature = "encryption")]
+    pub(crate) fn encryption(reason: impl Into<String>) -> Self {
+        Self::Encryption {
+            key: None,
+            reason: reason.into(),
+        }
+    }
+
+    /// Builds the error for a failed atomic write.
+    pub(crate) fn write(path: &Path, err: std::io::Error) -> Self {
+        Self::Write {
+            path: path.to_path_buf(),
+            source: err,
+        }
     }
 }
 
-#[cfg(feature = "ron")]
-impl From<ron::Error> for Error {
-    fn from(f: ron::Error) -> Self {
-        Self::Ron(f)
+/// Describes the key of an `Error::Encryption`, if known.
+#[cfg(feature = "encryption")]
+fn for_key(key: &Option<String>) -> String {
+    match key {
+        Some(key) => format!(" for key {key}"),
+        None => String::new(),
     }
 }
 
-#[cfg(feature = "ron")]
-impl From<ron::error::SpannedError> for Error {
-    fn from(f: ron::error::SpannedError) -> Self {
-        Self::RonSpanned(f)
+/// Describes the archived file of an `Error::InvalidArchive`, if known.
+fn in_file(file: &Option<String>) -> String {
+    match file {
+        Some(file) => format!(" at {file}"),
+        None => String::new(),
     }
 }
 
-#[cfg(feature = "json")]
-impl From<serde_json::Error> for Error {
-    fn from(f: serde_json::Error) -> Self {
-        Self::Json(f)
+/// Describes the path of an `Error::Generic`, if known.
+fn at_path(path: &Option<PathBuf>) -> String {
+    match path {
+        Some(path) => format!(" at {}", path.display()),
+        None => String::new(),
     }
 }
 
-#[cfg(feature = "toml")]
-impl From<toml::de::Error> for Error {
-    fn from(f: toml::de::Error) -> Self {
-        Self::TomlDeserialize(f)
+/// Describes the format suggested by `Error::UnsupportedByFormat`.
+fn suggestion(suggested_format: &Option<FileType>) -> String {
+    match suggested_format {
+        Some(file_type) => format!(", store it as {file_type} instead"),
+        None => String::new(),
     }
 }
 
-#[cfg(feature = "toml")]
-impl From<toml::ser::Error> for Error {
-    fn from(f: toml::ser::Error) -> Self {
-        Self::TomlSerialize(f)
+// Keeps `Error` convertible into `anyhow::Error` and `eyre::Report`.
+const _: fn() = || {
+    fn assert_error<T: std::error::Error + Send + Sync + 'static>() {}
+    assert_error::<Error>();
+};
+
+impl From<String> for Error {
+    fn from(f: String) -> Self {
+        Self::Generic {
+            path: None,
+            reason: f,
+        }
     }
 }
@@ -0,0 +1,76 @@
+use serde::Serialize;
+
+use crate::{traits::Set, utils::FileType, Config, Error};
+
+/// Recovery helpers for results returned by `Config` getters.
+///
+/// Every helper only handles missing files, any other error is propagated unchanged.
+///
+/// ```no_run
+/// # use libset::{Config, LibsetResultExt};
+/// # use serde::{Deserialize, Serialize};
+/// # fn main() -> Result<(), libset::Error> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct Colors { accent: String }
+///
+/// let config = Config::new("org.example.Demo", 1, None)?;
+/// let colors: Colors = config.get_json("colors").or_default_value()?;
+/// let fonts: Option<Vec<String>> = config.get_json("fonts").ignore_missing()?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait LibsetResultExt<T> {
+    /// Replaces a missing file with the default value of the type.
+    fn or_default_value(self) -> Result<T, Error>
+    where
+        T: Default;
+
+    /// Replaces a missing file with a computed value and stores it under the key.
+    ///
+    /// The value is written in the format that was requested, plain files are not supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration to store the value in.
+    /// * `key` - The key to store the value under.
+    /// * `f` - Function computing the value.
+    fn or_insert_with<F>(self, config: &Config, key: &str, f: F) -> Result<T, Error>
+    where
+        T: Serialize,
+        F: FnOnce() -> T;
+
+    /// Turns a missing file into `None`.
+    fn ignore_missing(self) -> Result<Option<T>, Error>;
+}
+
+impl<T> LibsetResultExt<T> for Result<T, Error> {
+    fn or_default_value(self) -> Result<T, Error>
+    where
+        T: Default,
+    {
+        Ok(self.ignore_missing()?.unwrap_or_default())
+    }
+
+    fn or_insert_with<F>(self, config: &Config, key: &str, f: F) -> Result<T, Error>
+    where
+        T: Serialize,
+        F: FnOnce() -> T,
+    {
+        match self {
+            Err(Error::KeyNotFound { file_type, .. }) if file_type != FileType::Plain => {
+                let value = f();
+                config.set(key, file_type, &value)?;
+                Ok(value)
+            }
+            result => result,
+        }
+    }
+
+    fn ignore_missing(self) -> Result<Option<T>, Error> {
+        match self {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.is_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
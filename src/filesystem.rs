@@ -0,0 +1,391 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+#[cfg(feature = "std-fs")]
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+};
+
+#[cfg(feature = "std-fs")]
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+
+/// The size and modification time of a stored file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    /// The size of the file in bytes.
+    pub len: u64,
+    /// The last modification time, if the storage records it.
+    pub modified: Option<SystemTime>,
+}
+
+/// The storage files are read from and written to.
+///
+/// `Config` performs reads, writes, removals, key listings, batches and their journal,
+/// snapshots, archives, retention, recovery, sealing, collections, age-encrypted files and
+/// caching through this trait, set one with [`Config::new_with_filesystem`] or
+/// [`Config::with_filesystem`]. Implement it to keep
+/// settings in memory, in a database or on a remote service, and check the implementation
+/// with `libset::conformance::run`, enabled by the `conformance` feature.
+///
+/// The following features always use the local filesystem, whatever the storage:
+///
+/// * creating the configuration directory, in [`Config::new`] and its variants,
+/// * watching for changes, with the `watch`, `sighup`, `dbus` and daemon features,
+/// * change broadcasts between processes, and key or named locks, which rely on file locks,
+/// * the key file of secrets, systemd credentials, TPM-sealed keys and `.local` override files,
+/// * reading the old locations of legacy imports,
+/// * the permission checks of [`Config::check`] and the ownership changes of user scoping.
+///
+/// [`Config::new_with_filesystem`]: crate::Config::new_with_filesystem
+/// [`Config::with_filesystem`]: crate::Config::with_filesystem
+/// [`Config::new`]: crate::Config::new
+/// [`Config::check`]: crate::Config::check
+pub trait Filesystem: Send + Sync {
+    /// Reads the contents of a file.
+    ///
+    /// Fails with `io::ErrorKind::NotFound` if the file is not present.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Replaces the contents of a file, readers must observe either the old or new contents.
+    ///
+    /// When `create_new` is `true` the write fails with `io::ErrorKind::AlreadyExists` if
+    /// the file is present.
+    fn write(&self, path: &Path, data: &[u8], create_new: bool) -> io::Result<()>;
+
+    /// Overwrites the contents of a file directly, used by the non-atomic write strategies.
+    ///
+    /// Defaults to [`Filesystem::write`], implement it when replacing files is slow or
+    /// unsupported. See [`WriteStrategy`] for the tradeoffs.
+    ///
+    /// [`WriteStrategy`]: crate::WriteStrategy
+    fn write_in_place(&self, path: &Path, data: &[u8], create_new: bool) -> io::Result<()> {
+        self.write(path, data, create_new)
+    }
+
+    /// Replaces the contents of a file holding a secret, only readable by its owner.
+    ///
+    /// Defaults to [`Filesystem::write`], for storages without permissions. Implementations
+    /// with permissions must restrict the file before any data is written to it.
+    fn write_private(&self, path: &Path, data: &[u8], create_new: bool) -> io::Result<()> {
+        self.write(path, data, create_new)
+    }
+
+    /// Removes a file.
+    ///
+    /// Fails with `io::ErrorKind::NotFound` if the file is not present.
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    /// Determines if a file is present.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Lists the paths of the files directly inside a directory, nested directories excluded.
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Returns the size and modification time of a file.
+    ///
+    /// Cached values are refreshed when either changes. Defaults to reading the file and
+    /// reporting no modification time, implement it when the storage records one.
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        Ok(Metadata {
+            len: self.read(path)?.len() as u64,
+            modified: None,
+        })
+    }
+
+    /// Lists the paths of the directories directly inside a directory.
+    ///
+    /// Defaults to no directories, for storages without nested scopes.
+    fn list_dirs(&self, _dir: &Path) -> io::Result<Vec<PathBuf>> {
+        Ok(Vec::new())
+    }
+
+    /// Creates a directory and its parents, if the storage has directories.
+    fn create_dir_all(&self, _dir: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Moves a file over another one, replacing it.
+    ///
+    /// Defaults to writing a copy and removing the original, implement it when the storage
+    /// can rename atomically.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let data = self.read(from)?;
+        self.write(to, &data, false)?;
+        self.remove(from)
+    }
+
+    /// Flushes the renames made inside a directory to durable storage.
+    fn sync_dir(&self, _dir: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Removes a directory and everything inside it.
+    fn remove_dir_all(&self, dir: &Path) -> io::Result<()> {
+        for nested in self.list_dirs(dir)? {
+            self.remove_dir_all(&nested)?;
+        }
+        for file in self.list(dir)? {
+            self.remove(&file)?;
+        }
+        Ok(())
+    }
+}
+
+/// The local filesystem, used by default.
+///
+/// Files are written atomically by renaming a temporary file over the destination, unless
+/// another [`WriteStrategy`](crate::WriteStrategy) is selected.
+#[cfg(feature = "std-fs")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdFilesystem;
+
+#[cfg(feature = "std-fs")]
+impl StdFilesystem {
+    /// Atomically writes a file, preparing the temporary file before the data is written.
+    fn write_with(
+        path: &Path,
+        data: &[u8],
+        create_new: bool,
+        prepare: impl Fn(&File) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let overwrite = if create_new {
+            OverwriteBehavior::DisallowOverwrite
+        } else {
+            OverwriteBehavior::AllowOverwrite
+        };
+        AtomicFile::new(path, overwrite)
+            .write(|file| {
+                prepare(file)?;
+                file.write_all(data)
+            })
+            .map_err(|err| match err {
+                atomicwrites::Error::Internal(err) | atomicwrites::Error::User(err) => err,
+            })
+    }
+}
+
+#[cfg(feature = "std-fs")]
+impl Filesystem for StdFilesystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn write(&self, path: &Path, data: &[u8], create_new: bool) -> io::Result<()> {
+        Self::write_with(path, data, create_new, |_| Ok(()))
+    }
+
+    fn write_in_place(&self, path: &Path, data: &[u8], create_new: bool) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(!create_new)
+            .create_new(create_new)
+            .truncate(true)
+            .open(path)?;
+        file.write_all(data)?;
+        file.sync_all()
+    }
+
+    fn write_private(&self, path: &Path, data: &[u8], create_new: bool) -> io::Result<()> {
+        Self::write_with(path, data, create_new, restrict)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        list_std(dir, |file_type| file_type.is_file())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Metadata {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    fn list_dirs(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        list_std(dir, |file_type| file_type.is_dir())
+    }
+
+    fn create_dir_all(&self, dir: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(dir)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        std::fs::rename(from, to)
+    }
+
+    #[cfg(unix)]
+    fn sync_dir(&self, dir: &Path) -> io::Result<()> {
+        File::open(dir)?.sync_all()
+    }
+
+    fn remove_dir_all(&self, dir: &Path) -> io::Result<()> {
+        std::fs::remove_dir_all(dir)
+    }
+}
+
+/// Makes a file only accessible by its owner.
+#[cfg(all(feature = "std-fs", unix))]
+fn restrict(file: &File) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))
+}
+
+/// Makes a file only accessible by its owner.
+///
+/// Files in the user's configuration directory already inherit an owner-only ACL on
+/// Windows, so there is nothing to change.
+#[cfg(all(feature = "std-fs", not(unix)))]
+fn restrict(_file: &File) -> io::Result<()> {
+    Ok(())
+}
+
+/// Lists the entries of a local directory whose type matches a predicate.
+#[cfg(feature = "std-fs")]
+fn list_std(dir: &Path, keep: impl Fn(&std::fs::FileType) -> bool) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if keep(&entry.file_type()?) {
+            paths.push(entry.path());
+        }
+    }
+    Ok(paths)
+}
+
+/// A filesystem kept in memory, discarded when dropped.
+///
+/// Useful in tests and for settings that should not outlive the process.
+///
+/// ```no_run
+/// # use libset::{Config, MemoryFilesystem};
+/// # use std::path::Path;
+/// # fn main() -> Result<(), libset::Error> {
+/// let config = Config::new_with_filesystem(
+///     MemoryFilesystem::default(),
+///     Path::new("/settings"),
+///     "org.example.Demo",
+///     1,
+///     None,
+/// )?;
+/// config.set_plain("session", "42")?;
+/// assert_eq!(config.get_plain("session")?, "42");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryFilesystem {
+    files: Mutex<BTreeMap<PathBuf, MemoryFile>>,
+}
+
+/// A file kept by [`MemoryFilesystem`].
+#[derive(Debug)]
+struct MemoryFile {
+    data: Vec<u8>,
+    modified: SystemTime,
+}
+
+impl MemoryFile {
+    fn new(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            modified: SystemTime::now(),
+        }
+    }
+}
+
+impl Filesystem for MemoryFilesystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let files = self.files.lock().unwrap();
+        files
+            .get(path)
+            .map(|file| file.data.clone())
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn write(&self, path: &Path, data: &[u8], create_new: bool) -> io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        if create_new && files.contains_key(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        files.insert(path.to_path_buf(), MemoryFile::new(data.to_vec()));
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        files.remove(path).map(drop).ok_or_else(|| not_found(path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let files = self.files.lock().unwrap();
+        Ok(files
+            .keys()
+            .filter(|path| path.parent() == Some(dir))
+            .cloned()
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let files = self.files.lock().unwrap();
+        let file = files.get(path).ok_or_else(|| not_found(path))?;
+        Ok(Metadata {
+            len: file.data.len() as u64,
+            modified: Some(file.modified),
+        })
+    }
+
+    fn list_dirs(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let files = self.files.lock().unwrap();
+        let dirs: BTreeSet<PathBuf> = files
+            .keys()
+            .filter_map(|path| {
+                let nested = path.parent()?.strip_prefix(dir).ok()?;
+                Some(dir.join(nested.components().next()?))
+            })
+            .collect();
+        Ok(dirs.into_iter().collect())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        let file = files.remove(from).ok_or_else(|| not_found(from))?;
+        files.insert(to.to_path_buf(), file);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, dir: &Path) -> io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .retain(|path, _| !path.starts_with(dir));
+        Ok(())
+    }
+}
+
+/// Builds the error for a file missing from memory.
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} not found", path.display()),
+    )
+}
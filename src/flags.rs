@@ -0,0 +1,193 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use tracing::warn;
+
+use crate::{Config, Error, LibsetResultExt};
+
+/// The key feature flags are stored under.
+const FLAGS_KEY: &str = "flags";
+
+/// Feature flags stored in a single json document, created with [`Config::flags`].
+///
+/// Flags that were never set are disabled.
+pub struct Flags<'a> {
+    config: &'a Config,
+}
+
+/// Provides feature flags from a remote backend, see [`Flags::refresh`].
+///
+/// Implemented for functions returning the flags.
+pub trait FlagSource: Send + Sync {
+    /// Fetches the current flags.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flags by name or an `Error` if they could not be fetched.
+    fn fetch(&self) -> Result<BTreeMap<String, bool>, Error>;
+}
+
+impl<F> FlagSource for F
+where
+    F: Fn() -> Result<BTreeMap<String, bool>, Error> + Send + Sync,
+{
+    fn fetch(&self) -> Result<BTreeMap<String, bool>, Error> {
+        self()
+    }
+}
+
+impl Config {
+    /// Opens the feature flags of the application.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// if config.flags().is_enabled("experimental_ui")? {
+    ///     println!("using the new interface");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The `Flags` handle, the document is only read and written when flags are used.
+    pub fn flags(&self) -> Flags<'_> {
+        Flags { config: self }
+    }
+}
+
+impl Flags<'_> {
+    /// Determines if a flag is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the flag.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `true` if the flag is enabled, `false` if it is disabled or was
+    /// never set, or an `Error` if the flags could not be read.
+    pub fn is_enabled(&self, name: &str) -> Result<bool, Error> {
+        Ok(self.list()?.get(name).copied().unwrap_or(false))
+    }
+
+    /// Enables or disables a flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the flag.
+    /// * `enabled` - Whether the flag is enabled.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the flags could not be updated.
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> Result<(), Error> {
+        self.config
+            .update_json(FLAGS_KEY, |flags: &mut BTreeMap<String, bool>| {
+                flags.insert(name.to_string(), enabled);
+            })
+    }
+
+    /// Returns every flag that was set, by name.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flags or an `Error` if they could not be read.
+    pub fn list(&self) -> Result<BTreeMap<String, bool>, Error> {
+        self.config.get_json(FLAGS_KEY).or_default_value()
+    }
+
+    /// Fetches flags from a remote backend and stores them, replacing the flags of the same
+    /// name.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The backend to fetch the flags from.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the flags could not be fetched or
+    /// stored. Stored flags are kept when fetching fails.
+    pub fn refresh(&self, source: &dyn FlagSource) -> Result<(), Error> {
+        let fetched = source.fetch()?;
+        self.config
+            .update_json(FLAGS_KEY, |flags: &mut BTreeMap<String, bool>| {
+                flags.extend(fetched);
+            })
+    }
+}
+
+/// Handle to flags refreshed from a remote backend at an interval.
+///
+/// Refreshing stops once the handle is dropped.
+pub struct FlagRefresh {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FlagRefresh {
+    /// Refreshes the flags of a configuration now and then at every interval.
+    ///
+    /// Failures are logged and the stored flags are kept until the next refresh succeeds, so
+    /// an unreachable backend never disables features.
+    ///
+    /// ```no_run
+    /// # use std::{collections::BTreeMap, sync::Arc, time::Duration};
+    /// # use libset::{Config, FlagRefresh};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Arc::new(Config::new("org.example.Demo", 1, None)?);
+    /// let _refresh = FlagRefresh::start(config.clone(), Duration::from_secs(3600), || {
+    ///     Ok(BTreeMap::from([("experimental_ui".to_string(), true)]))
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration the flags are stored in.
+    /// * `interval` - How long to wait between refreshes.
+    /// * `source` - The backend to fetch the flags from.
+    ///
+    /// # Returns
+    ///
+    /// The `FlagRefresh` handle.
+    pub fn start(
+        config: Arc<Config>,
+        interval: Duration,
+        source: impl FlagSource + 'static,
+    ) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || loop {
+            if let Err(err) = config.flags().refresh(&source) {
+                warn!(error = %err, "failed to refresh feature flags, keeping the stored flags");
+            }
+            match stopped.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => continue,
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+            }
+        });
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for FlagRefresh {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
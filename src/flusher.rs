@@ -0,0 +1,318 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use tracing::error;
+
+use crate::{
+    filesystem::Filesystem,
+    hooks::{Event, EventKind, Hook},
+    utils::FileType,
+    Error, LogPolicy, WriteStrategy,
+};
+
+/// How often waits for the background thread check that it is still running.
+const WORKER_CHECK: Duration = Duration::from_millis(100);
+
+/// A write waiting to be performed by the background thread.
+struct PendingWrite {
+    key: String,
+    file_type: FileType,
+    data: Arc<Vec<u8>>,
+    generation: u64,
+}
+
+#[derive(Default)]
+struct State {
+    pending: HashMap<PathBuf, PendingWrite>,
+    errors: Vec<Error>,
+    generation: u64,
+    /// The path the background thread is writing to, outside the lock.
+    writing: Option<PathBuf>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    idle: Condvar,
+}
+
+impl Shared {
+    /// Locks the state, recovering it if a hook panicked on the background thread.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Waits for a change of the state, or until the background thread should be checked.
+    fn wait<'a>(&self, state: MutexGuard<'a, State>) -> MutexGuard<'a, State> {
+        self.idle
+            .wait_timeout(state, WORKER_CHECK)
+            .unwrap_or_else(PoisonError::into_inner)
+            .0
+    }
+}
+
+/// Performs atomic writes on a background thread.
+///
+/// Pending payloads are kept in memory until they reach the disk, so reads issued in the
+/// meantime observe the latest value. Writes to the same path are coalesced.
+pub(crate) struct Flusher {
+    shared: Arc<Shared>,
+    sender: Option<mpsc::Sender<PathBuf>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Flusher {
+    pub(crate) fn new(
+        hooks: Vec<Hook>,
+        fs: Arc<dyn Filesystem>,
+        strategy: WriteStrategy,
+        log_policy: LogPolicy,
+    ) -> Self {
+        let shared = Arc::new(Shared::default());
+        let (sender, receiver) = mpsc::channel::<PathBuf>();
+        let worker = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                for path in receiver {
+                    write_pending(&shared, &hooks, fs.as_ref(), strategy, log_policy, &path);
+                }
+            })
+        };
+        Self {
+            shared,
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    /// Queues a payload to be written to a path.
+    pub(crate) fn enqueue(&self, key: &str, file_type: FileType, path: &Path, data: Vec<u8>) {
+        let mut state = self.shared.lock();
+        state.generation += 1;
+        let write = PendingWrite {
+            key: key.to_string(),
+            file_type,
+            data: Arc::new(data),
+            generation: state.generation,
+        };
+        state.pending.insert(path.to_path_buf(), write);
+        drop(state);
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(path.to_path_buf());
+        }
+    }
+
+    /// Returns the payload waiting to be written to a path, if any.
+    pub(crate) fn pending(&self, path: &Path) -> Option<Arc<Vec<u8>>> {
+        let state = self.shared.lock();
+        state.pending.get(path).map(|write| write.data.clone())
+    }
+
+    /// Discards the payload waiting to be written to a path.
+    ///
+    /// Waits for a write to the path already in progress, so it can't land after a write
+    /// the caller performs next.
+    pub(crate) fn cancel(&self, path: &Path) {
+        let mut state = self.shared.lock();
+        if state.pending.remove(path).is_some() && state.pending.is_empty() {
+            self.shared.idle.notify_all();
+        }
+        while state.writing.as_deref() == Some(path) && self.is_running() {
+            state = self.shared.wait(state);
+        }
+    }
+
+    /// Blocks until every queued write has been performed.
+    ///
+    /// Returns the first error reported by the background thread since the last flush, or
+    /// an error if the thread stopped with writes still queued.
+    pub(crate) fn flush(&self) -> Result<(), Error> {
+        let mut state = self.shared.lock();
+        while !state.pending.is_empty() {
+            if !self.is_running() {
+                return Err(Error::Generic {
+                    path: None,
+                    reason: format!(
+                        "the background writer stopped, {} queued writes were not performed",
+                        state.pending.len()
+                    ),
+                });
+            }
+            state = self.shared.wait(state);
+        }
+        let mut errors = std::mem::take(&mut state.errors).into_iter();
+        match errors.next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Determines if the background thread is still running.
+    fn is_running(&self) -> bool {
+        self.worker
+            .as_ref()
+            .is_some_and(|worker| !worker.is_finished())
+    }
+}
+
+impl Drop for Flusher {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Writes the payload queued for a path, if it has not been written yet.
+fn write_pending(
+    shared: &Shared,
+    hooks: &[Hook],
+    fs: &dyn Filesystem,
+    strategy: WriteStrategy,
+    log_policy: LogPolicy,
+    path: &Path,
+) {
+    let Some((key, file_type, data, generation)) = ({
+        let mut state = shared.lock();
+        let write = state.pending.get(path).map(|write| {
+            (
+                write.key.clone(),
+                write.file_type,
+                write.data.clone(),
+                write.generation,
+            )
+        });
+        if write.is_some() {
+            state.writing = Some(path.to_path_buf());
+        }
+        write
+    }) else {
+        return;
+    };
+
+    let result = strategy.write(fs, path, &data, false);
+
+    let mut state = shared.lock();
+    state.writing = None;
+    if state
+        .pending
+        .get(path)
+        .is_some_and(|write| write.generation == generation)
+    {
+        state.pending.remove(path);
+    }
+    match result {
+        Ok(()) => {
+            log_policy.log("wrote file", Some(&key), path);
+            let event = Event {
+                kind: EventKind::AfterWrite,
+                key: &key,
+                file_type,
+                path,
+            };
+            for hook in hooks {
+                hook(&event);
+            }
+        }
+        Err(error) => {
+            error!("{}", error.to_string());
+            state.errors.push(error);
+        }
+    }
+    shared.idle.notify_all();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::Duration,
+    };
+
+    use super::Flusher;
+    use crate::{
+        filesystem::Filesystem, hooks::Hook, utils::FileType, LogPolicy, MemoryFilesystem,
+        WriteStrategy,
+    };
+
+    /// A filesystem taking a while to write files.
+    #[derive(Default)]
+    struct SlowFilesystem(MemoryFilesystem);
+
+    impl Filesystem for SlowFilesystem {
+        fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+            self.0.read(path)
+        }
+
+        fn write(&self, path: &Path, data: &[u8], create_new: bool) -> io::Result<()> {
+            std::thread::sleep(Duration::from_millis(200));
+            self.0.write(path, data, create_new)
+        }
+
+        fn remove(&self, path: &Path) -> io::Result<()> {
+            self.0.remove(path)
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.0.exists(path)
+        }
+
+        fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+            self.0.list(dir)
+        }
+    }
+
+    #[test]
+    fn cancel_waits_for_writes_in_progress() {
+        let fs = Arc::new(SlowFilesystem::default());
+        let flusher = Flusher::new(
+            Vec::new(),
+            fs.clone(),
+            WriteStrategy::Atomic,
+            LogPolicy::default(),
+        );
+        let path = Path::new("/store/theme.json");
+        flusher.enqueue("theme", FileType::Json, path, b"old".to_vec());
+        while flusher.shared.lock().writing.is_none() {
+            std::thread::yield_now();
+        }
+
+        flusher.cancel(path);
+        fs.0.write(path, b"new", false).unwrap();
+        drop(flusher);
+        assert_eq!(fs.read(path).unwrap(), b"new");
+    }
+
+    #[test]
+    fn flush_fails_once_the_writer_stopped() {
+        let hook: Hook = Arc::new(|_| panic!("hook failed"));
+        let flusher = Flusher::new(
+            vec![hook],
+            Arc::new(MemoryFilesystem::default()),
+            WriteStrategy::Atomic,
+            LogPolicy::default(),
+        );
+        flusher.enqueue(
+            "a",
+            FileType::Json,
+            Path::new("/store/a.json"),
+            b"a".to_vec(),
+        );
+        flusher.enqueue(
+            "b",
+            FileType::Json,
+            Path::new("/store/b.json"),
+            b"b".to_vec(),
+        );
+
+        assert!(flusher.flush().is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+
+use crate::{traits::Get, utils::FileType, Config, Error};
+
+/// A key stored in more than one format, such as after a format migration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatConflict {
+    /// The key stored more than once.
+    pub key: String,
+    /// The formats the key is stored in, in the resolution order of the `Config`, so the first
+    /// one is read by [`Config::get_any`]. Formats left out of the order come after the others,
+    /// plain files last.
+    pub file_types: Vec<FileType>,
+}
+
+/// Returns the structured formats enabled at compile time, in the default resolution order.
+pub(crate) fn default_order() -> Vec<FileType> {
+    vec![
+        #[cfg(feature = "toml")]
+        FileType::Toml,
+        #[cfg(feature = "json")]
+        FileType::Json,
+        #[cfg(feature = "ron")]
+        FileType::Ron,
+        #[cfg(feature = "yaml")]
+        FileType::Yaml,
+        #[cfg(feature = "ini")]
+        FileType::Ini,
+        #[cfg(feature = "json5")]
+        FileType::Json5,
+    ]
+}
+
+/// Returns an enabled format able to store any value.
+#[cfg(any(feature = "toml", feature = "ini"))]
+pub(crate) fn suggested_format() -> Option<FileType> {
+    [
+        #[cfg(feature = "json")]
+        FileType::Json,
+        #[cfg(feature = "ron")]
+        FileType::Ron,
+        #[cfg(feature = "yaml")]
+        FileType::Yaml,
+    ]
+    .first()
+    .copied()
+}
+
+impl Config {
+    /// Sets the order formats are tried in by the format-agnostic accessors.
+    ///
+    /// When a key exists in several formats, [`Config::get_any`] and [`Config::format_of`]
+    /// pick the first one in this order. By default toml is preferred over json, json over
+    /// ron, ron over yaml, yaml over ini and ini over json5. Formats left out of the order
+    /// are never read by these accessors.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// // Prefer the json files written after migrating from ron.
+    /// let config = Config::new("org.example.Demo", 1, None)?.with_format_order([FileType::Json]);
+    /// let colors: serde_json::Value = config.get_any("colors")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The formats in order of preference, plain files are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_format_order(mut self, order: impl IntoIterator<Item = FileType>) -> Self {
+        self.format_order.clear();
+        for file_type in order {
+            if file_type != FileType::Plain && !self.format_order.contains(&file_type) {
+                self.format_order.push(file_type);
+            }
+        }
+        self
+    }
+
+    /// Returns the format a key is read in by [`Config::get_any`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// The first format of the resolution order the key is stored in, or `None` if it isn't
+    /// stored in any of them.
+    pub fn format_of(&self, key: &str) -> Option<FileType> {
+        self.format_order.iter().copied().find(|file_type| {
+            self.resolve(key, *file_type)
+                .is_ok_and(|path| self.exists(&path))
+        })
+    }
+
+    /// Gets the content of a file with the given key in whichever format it is stored in.
+    ///
+    /// When the key is stored in several formats, the first one in the resolution order wins,
+    /// see [`Config::with_format_order`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value or an `Error` if the key isn't stored in
+    /// any format of the resolution order or could not be read.
+    pub fn get_any<T: DeserializeOwned>(&self, key: &str) -> Result<T, Error> {
+        match self.format_of(key) {
+            Some(file_type) => self.get(key, file_type),
+            None => {
+                let file_type = self
+                    .format_order
+                    .first()
+                    .copied()
+                    .unwrap_or(FileType::Plain);
+                Err(Error::KeyNotFound {
+                    key: key.to_string(),
+                    file_type,
+                    path: self.path(key, file_type)?,
+                })
+            }
+        }
+    }
+
+    /// Lists the keys stored in more than one format in the configuration path.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// for conflict in config.conflicts()? {
+    ///     println!("{} is stored as {:?}", conflict.key, conflict.file_types);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the conflicting keys sorted by name, or an `Error` if the
+    /// configuration path could not be listed.
+    pub fn conflicts(&self) -> Result<Vec<FormatConflict>, Error> {
+        let mut keys: BTreeMap<String, Vec<FileType>> = BTreeMap::new();
+        for entry in self.entries()? {
+            let entry = entry?;
+            keys.entry(entry.key).or_default().push(entry.file_type);
+        }
+        let rank = |file_type: &FileType| {
+            self.format_order
+                .iter()
+                .position(|preferred| preferred == file_type)
+                .unwrap_or(self.format_order.len() + usize::from(*file_type == FileType::Plain))
+        };
+        Ok(keys
+            .into_iter()
+            .filter(|(_, file_types)| file_types.len() > 1)
+            .map(|(key, mut file_types)| {
+                file_types.sort_by_key(rank);
+                FormatConflict { key, file_types }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "json")]
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "json")]
+use crate::LibsetResultExt;
+use crate::{
+    entry::{LOCKS_DIR, LOCK_SUFFIX},
+    utils::FileType,
+    Config, Error,
+};
+
+/// A shared lock on a key, taken with [`Config::read_guard`].
+///
+/// Any number of processes and threads can hold a read guard on the same key, while a
+/// [`WriteGuard`] waits until all of them are dropped. The lock is released when the guard is
+/// dropped.
+#[derive(Debug)]
+#[must_use = "the lock is released when the guard is dropped"]
+pub struct ReadGuard {
+    _file: File,
+}
+
+/// An exclusive lock on a key, taken with [`Config::write_guard`].
+///
+/// Only one write guard can be held on a key at a time, and no read guards while it is held.
+/// The lock is released when the guard is dropped.
+#[derive(Debug)]
+#[must_use = "the lock is released when the guard is dropped"]
+pub struct WriteGuard {
+    _file: File,
+}
+
+/// An exclusive lock shared by every process of the application, taken with
+/// [`Config::named_lock`].
+///
+/// The lock is released when the guard is dropped.
+#[derive(Debug)]
+#[must_use = "the lock is released when the guard is dropped"]
+pub struct NamedLock {
+    _file: File,
+}
+
+impl Config {
+    /// Takes a shared lock on a key, waiting while a writer holds it.
+    ///
+    /// Locks are advisory, libset does not take them on its own. A process reading several
+    /// keys takes a read guard on each of them to get a consistent view, as long as the writers
+    /// take a [`Config::write_guard`] around their writes. Waiting happens in the operating
+    /// system, without polling. The lock is held on a hidden `.lock` file next to the key,
+    /// which is left in place once the guard is dropped.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let _accounts = config.read_guard("accounts")?;
+    /// let _balances = config.read_guard("balances")?;
+    /// let accounts: Vec<String> = config.get_json("accounts")?;
+    /// let balances: Vec<u64> = config.get_json("balances")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lock, shared by every file type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the guard or an `Error` if the lock file could not be opened or
+    /// locked.
+    pub fn read_guard(&self, key: &str) -> Result<ReadGuard, Error> {
+        let path = self.lock_path(key)?;
+        let file = open_lock(&path)?;
+        file.lock_shared().map_err(|err| Error::io(&path, err))?;
+        self.log("locked key for reading", key, &path);
+        Ok(ReadGuard { _file: file })
+    }
+
+    /// Takes an exclusive lock on a key, waiting while readers or another writer hold it.
+    ///
+    /// Locks are advisory, see [`Config::read_guard`].
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// {
+    ///     let _accounts = config.write_guard("accounts")?;
+    ///     let _balances = config.write_guard("balances")?;
+    ///     config.set_json("accounts", ["alice"])?;
+    ///     config.set_json("balances", [42])?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lock, shared by every file type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the guard or an `Error` if the lock file could not be opened or
+    /// locked.
+    pub fn write_guard(&self, key: &str) -> Result<WriteGuard, Error> {
+        let path = self.lock_path(key)?;
+        let file = open_lock(&path)?;
+        file.lock().map_err(|err| Error::io(&path, err))?;
+        self.log("locked key for writing", key, &path);
+        Ok(WriteGuard { _file: file })
+    }
+
+    /// Takes a named lock, waiting while another process or thread holds it.
+    ///
+    /// Named locks belong to the version directory, so every scope of the application shares
+    /// them. Use them to keep maintenance operations such as imports, migrations and compactions
+    /// from running at the same time. Like key guards, they are advisory and kept in hidden files
+    /// left in place once dropped.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let _import = config.named_lock("import")?;
+    /// config.set_json("accounts", ["alice"])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the lock, following the same rules as keys.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the lock or an `Error` if the lock file could not be opened or
+    /// locked.
+    pub fn named_lock(&self, name: &str) -> Result<NamedLock, Error> {
+        let path = self.named_lock_path(name)?;
+        let file = open_lock(&path)?;
+        file.lock().map_err(|err| Error::io(&path, err))?;
+        self.log_policy.log("took named lock", Some(name), &path);
+        Ok(NamedLock { _file: file })
+    }
+
+    /// Takes a named lock if no other process or thread holds it.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// if let Some(_compaction) = config.try_named_lock("compaction")? {
+    ///     config.apply_retention()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the lock, following the same rules as keys.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the lock, `None` if it is held elsewhere, or an `Error` if the
+    /// lock file could not be opened or locked.
+    pub fn try_named_lock(&self, name: &str) -> Result<Option<NamedLock>, Error> {
+        let path = self.named_lock_path(name)?;
+        let file = open_lock(&path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(err)) => return Err(Error::io(&path, err)),
+        }
+        self.log_policy.log("took named lock", Some(name), &path);
+        Ok(Some(NamedLock { _file: file }))
+    }
+
+    /// Reads, modifies and writes back a json file while holding its write guard.
+    ///
+    /// A missing file starts from the default value of the type.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `f` - Function modifying the value.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the result of `f` or an `Error` if the file could not be locked,
+    /// read or written.
+    #[cfg(feature = "json")]
+    pub(crate) fn update_json<T, R>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, Error>
+    where
+        T: Serialize + DeserializeOwned + Default,
+    {
+        let _guard = self.write_guard(key)?;
+        let mut value: T = self.get_json(key).or_default_value()?;
+        let result = f(&mut value);
+        self.set_json(key, &value)?;
+        Ok(result)
+    }
+
+    /// Returns the path of the lock file of a named lock, creating its directory.
+    fn named_lock_path(&self, name: &str) -> Result<PathBuf, Error> {
+        let path = self.root.join(LOCKS_DIR).join(format!(
+            "{}{LOCK_SUFFIX}",
+            self.validator.validate(name)?.display()
+        ));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| Error::io(parent, err))?;
+        }
+        Ok(path)
+    }
+
+    /// Returns the path of the lock file of a key.
+    fn lock_path(&self, key: &str) -> Result<PathBuf, Error> {
+        let path = self.path(key, FileType::Plain)?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        Ok(path.with_file_name(format!(".{name}{LOCK_SUFFIX}")))
+    }
+}
+
+/// Opens a lock file, creating it if needed.
+fn open_lock(path: &Path) -> Result<File, Error> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|err| Error::io(path, err))
+}
@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+use crate::utils::FileType;
+
+/// Name of the field holding the header in JSON documents.
+#[cfg(feature = "json")]
+const JSON_FIELD: &str = "\"$libset\": \"";
+/// Marker starting the header text.
+const MARKER: &str = "libset ";
+
+/// Describes the release that wrote a file.
+///
+/// Files only carry this information when they were written by a `Config` with
+/// [`Config::with_file_header`](crate::Config::with_file_header) enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrittenBy {
+    /// The version of the application that wrote the file.
+    pub app_version: String,
+    /// The configuration version the file was written for.
+    pub schema_version: u64,
+}
+
+/// Adds a header to serialized data.
+///
+/// TOML, RON, YAML, INI and JSON5 files get a leading comment, JSON objects get a `$libset`
+/// field as their first member. Other JSON values and plain files are returned unchanged.
+pub(crate) fn stamp(data: String, file_type: FileType, written_by: &WrittenBy) -> String {
+    let header = format!(
+        "{MARKER}app={} schema={}",
+        written_by.app_version, written_by.schema_version
+    );
+    match file_type {
+        #[cfg(feature = "toml")]
+        FileType::Toml => format!("# {header}\n{data}"),
+        #[cfg(feature = "ron")]
+        FileType::Ron => format!("// {header}\n{data}"),
+        #[cfg(feature = "yaml")]
+        FileType::Yaml => format!("# {header}\n{data}"),
+        #[cfg(feature = "ini")]
+        FileType::Ini => format!("; {header}\n{data}"),
+        #[cfg(feature = "json5")]
+        FileType::Json5 => format!("// {header}\n{data}"),
+        #[cfg(feature = "json")]
+        FileType::Json if data == "{}" => format!("{{\n  {JSON_FIELD}{header}\"\n}}"),
+        #[cfg(feature = "json")]
+        FileType::Json => match data.strip_prefix("{\n") {
+            Some(rest) => format!("{{\n  {JSON_FIELD}{header}\",\n{rest}"),
+            None => data,
+        },
+        _ => data,
+    }
+}
+
+/// Removes a header that the format's parser would not ignore.
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+pub(crate) fn strip(data: &str, file_type: FileType) -> Cow<'_, str> {
+    #[cfg(feature = "json")]
+    if file_type == FileType::Json {
+        if let Some(rest) = data.strip_prefix("{\n") {
+            if let Some(field) = rest.trim_start_matches(' ').strip_prefix(JSON_FIELD) {
+                let next = field.find('\n').map(|end| &field[end + 1..]).unwrap_or("");
+                return Cow::Owned(format!("{{\n{next}"));
+            }
+        }
+    }
+    Cow::Borrowed(data)
+}
+
+/// Reads the header of a file, if it has one.
+pub(crate) fn parse(data: &str, file_type: FileType) -> Option<WrittenBy> {
+    let line = data.lines().take(2).find_map(|line| {
+        let line = line.trim();
+        match file_type {
+            #[cfg(feature = "json")]
+            FileType::Json => line.strip_prefix(JSON_FIELD),
+            FileType::Plain => None,
+            #[allow(unreachable_patterns)]
+            _ => line
+                .strip_prefix('#')
+                .or_else(|| line.strip_prefix("//"))
+                .or_else(|| line.strip_prefix(';')),
+        }
+    })?;
+    let line = line.trim().trim_end_matches(',').trim_end_matches('"');
+    let mut app_version = None;
+    let mut schema_version = None;
+    for field in line.strip_prefix(MARKER)?.split_whitespace() {
+        match field.split_once('=') {
+            Some(("app", value)) => app_version = Some(value.to_string()),
+            Some(("schema", value)) => schema_version = value.parse().ok(),
+            _ => {}
+        }
+    }
+    Some(WrittenBy {
+        app_version: app_version?,
+        schema_version: schema_version?,
+    })
+}
+
+/// Makes an application version safe to embed in a header.
+pub(crate) fn sanitize(app_version: &str) -> String {
+    app_version
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c == '"' || c == '\\' {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
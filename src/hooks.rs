@@ -0,0 +1,34 @@
+use std::{path::Path, sync::Arc};
+
+use crate::utils::FileType;
+
+/// The operation an [`Event`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// A file is about to be written.
+    BeforeWrite,
+    /// A file was written.
+    AfterWrite,
+    /// A file is about to be read.
+    BeforeRead,
+    /// A file was read.
+    AfterRead,
+    /// A file was removed.
+    Remove,
+}
+
+/// Represents an operation performed on a stored file.
+#[derive(Debug, Clone, Copy)]
+pub struct Event<'a> {
+    /// The operation being performed.
+    pub kind: EventKind,
+    /// The key of the file.
+    pub key: &'a str,
+    /// The format of the file.
+    pub file_type: FileType,
+    /// The absolute path of the file.
+    pub path: &'a Path,
+}
+
+/// A callback registered with [`Config::on_event`](crate::Config::on_event).
+pub(crate) type Hook = Arc<dyn Fn(&Event) + Send + Sync>;
@@ -0,0 +1,69 @@
+use crate::{Config, Error};
+
+/// Files holding the machine identifier, in order of preference.
+const MACHINE_ID_FILES: &[&str] = &["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+/// Files holding the hostname, read when the machine has no identifier.
+const HOSTNAME_FILES: &[&str] = &["/etc/hostname", "/proc/sys/kernel/hostname"];
+
+/// Returns a stable identifier of the current machine.
+///
+/// The machine identifier is preferred since it survives renames, the hostname is used where
+/// there is none, such as on macOS and Windows.
+fn host_id() -> Option<String> {
+    let files = MACHINE_ID_FILES
+        .iter()
+        .chain(HOSTNAME_FILES)
+        .filter_map(|file| {
+            std::fs::read_to_string(file)
+                .ok()
+                .map(|id| id.trim().to_string())
+        });
+    let variables = ["HOSTNAME", "COMPUTERNAME"]
+        .into_iter()
+        .filter_map(|variable| std::env::var(variable).ok());
+    files.chain(variables).find(|id| !id.is_empty())
+}
+
+impl Config {
+    /// Moves the configuration into a scope belonging to the current machine.
+    ///
+    /// Settings synced between machines through dotfiles or cloud storage often include
+    /// values that only make sense on one of them, such as the monitor layout or GPU options.
+    /// Those are written to a scope named after the machine, while reads fall back to the
+    /// shared settings, see [`Config::scoped_with_fallback`].
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let shared = Config::new("org.example.Compositor", 1, None)?;
+    /// let theme: String = shared.get_json("theme")?;
+    /// let device = Config::new("org.example.Compositor", 1, None)?.per_host()?;
+    /// device.set_json("monitors", ["DP-1", "HDMI-A-1"])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated `Config` object or `Error::NoHostId` if the machine
+    /// has neither an identifier nor a hostname.
+    pub fn per_host(self) -> Result<Self, Error> {
+        let host = host_id().ok_or(Error::NoHostId)?;
+        self.for_host(&host)
+    }
+
+    /// Moves the configuration into the scope of a given machine, see [`Config::per_host`].
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The identifier of the machine.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated `Config` object or an `Error` if the identifier is
+    /// not a valid scope name or the directory could not be created.
+    pub fn for_host(self, host: &str) -> Result<Self, Error> {
+        self.scoped_with_fallback(&format!("host-{host}"))
+    }
+}
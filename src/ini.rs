@@ -0,0 +1,350 @@
+use std::{fmt, path::Path};
+
+use ::ini::Ini;
+use serde::{
+    de::{
+        self, value::StringDeserializer, DeserializeOwned, DeserializeSeed, IntoDeserializer,
+        MapAccess, SeqAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer, Serialize,
+};
+
+use crate::{env::Node, formats, kind::Position, utils::FileType, Error};
+
+/// Serializes a value as ini.
+///
+/// The value must be a map: scalars are written as properties before the first section,
+/// maps as sections holding scalars, and lists of scalars as comma-separated values.
+/// Null values are skipped.
+///
+/// # Returns
+///
+/// A `Result` containing the serialized text, or `Error::UnsupportedByFormat` if the value
+/// has a shape ini cannot store.
+pub(crate) fn serialize<T: Serialize + ?Sized>(
+    key: &str,
+    path: &Path,
+    value: &T,
+) -> Result<String, Error> {
+    let unsupported = |reason: String| Error::UnsupportedByFormat {
+        key: key.to_string(),
+        file_type: FileType::Ini,
+        path: path.to_path_buf(),
+        reason,
+        suggested_format: formats::suggested_format(),
+    };
+    let content = serde_content::Serializer::new()
+        .human_readable()
+        .serialize(value)
+        .map_err(|err| unsupported(err.to_string()))?;
+    let node = Node::deserialize(content.into_deserializer())
+        .map_err(|err| unsupported(err.to_string()))?;
+    let Node::Map(entries) = node else {
+        return Err(unsupported(
+            "only maps can be stored at the top level".to_string(),
+        ));
+    };
+    let mut ini = Ini::new();
+    for (name, node) in entries {
+        match node {
+            Node::Map(properties) => {
+                let mut section = ini.with_section(Some(name.as_str()));
+                for (property, node) in properties {
+                    if let Some(value) =
+                        property_value(&format!("{name}.{property}"), node).map_err(unsupported)?
+                    {
+                        section.set(property, value);
+                    }
+                }
+            }
+            node => {
+                if let Some(value) = property_value(&name, node).map_err(unsupported)? {
+                    ini.with_general_section().set(name, value);
+                }
+            }
+        }
+    }
+    let mut data = Vec::new();
+    ini.write_to(&mut data)
+        .map_err(|err| Error::io(path, err))?;
+    String::from_utf8(data).map_err(|err| unsupported(err.to_string()))
+}
+
+/// Returns the text of a property, `None` for null values.
+fn property_value(name: &str, node: Node) -> Result<Option<String>, String> {
+    match node {
+        Node::Null => Ok(None),
+        Node::Scalar(value) => Ok(Some(value)),
+        Node::Seq(items) => items
+            .into_iter()
+            .map(|item| match item {
+                Node::Scalar(value) if !value.contains(',') => Ok(value),
+                Node::Scalar(_) => Err(format!("list items of {name} contain commas")),
+                _ => Err(format!("{name} is a list of values other than scalars")),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|items| Some(items.join(","))),
+        Node::Map(_) => Err(format!("{name} nests sections, which ini can't represent")),
+    }
+}
+
+/// Deserializes ini text into a value.
+///
+/// Properties before the first section become top-level fields and sections become nested
+/// maps. Values are read as strings and converted to booleans, numbers and lists of
+/// comma-separated values as the target type requires. When a property or section appears
+/// more than once, the last one wins.
+///
+/// # Returns
+///
+/// A `Result` containing the value, or `Error::Ini` if the text is not valid ini or does not
+/// match the type.
+pub(crate) fn deserialize<T: DeserializeOwned>(
+    key: &str,
+    path: &Path,
+    data: &str,
+) -> Result<T, Error> {
+    let failed = |reason: String, position: Option<Position>| Error::Ini {
+        key: key.to_string(),
+        path: path.to_path_buf(),
+        reason,
+        position,
+        #[cfg(feature = "miette")]
+        source_code: position.and_then(|_| crate::diagnostic::source_code(path, data)),
+    };
+    let ini = Ini::load_from_str(data).map_err(|err| {
+        failed(
+            err.msg.to_string(),
+            Some(Position {
+                line: err.line,
+                column: err.col,
+            }),
+        )
+    })?;
+    let mut document = Vec::new();
+    for (section, properties) in &ini {
+        let properties = properties
+            .iter()
+            .map(|(name, value)| (name.to_string(), Node::Scalar(value.to_string())));
+        match section {
+            None => properties.for_each(|(name, node)| insert(&mut document, name, node)),
+            Some(section) => {
+                let mut entries = Vec::new();
+                properties.for_each(|(name, node)| insert(&mut entries, name, node));
+                insert(&mut document, section.to_string(), Node::Map(entries));
+            }
+        }
+    }
+    T::deserialize(NodeDeserializer(Node::Map(document))).map_err(|err| failed(err.0, None))
+}
+
+/// Adds an entry to a map, replacing any entry with the same name.
+fn insert(entries: &mut Vec<(String, Node)>, name: String, node: Node) {
+    entries.retain(|(existing, _)| *existing != name);
+    entries.push((name, node));
+}
+
+/// Failure raised while deserializing an ini document.
+#[derive(Debug)]
+pub(crate) struct IniError(String);
+
+impl fmt::Display for IniError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for IniError {}
+
+impl de::Error for IniError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// Deserializes a parsed ini document, converting its string values as the target requires.
+pub(crate) struct NodeDeserializer(pub(crate) Node);
+
+impl NodeDeserializer {
+    fn scalar(&self) -> Result<&str, IniError> {
+        match &self.0 {
+            Node::Scalar(value) => Ok(value),
+            _ => Err(IniError("expected a value, found a section".to_string())),
+        }
+    }
+
+    fn parse<T: std::str::FromStr>(&self) -> Result<T, IniError>
+    where
+        T::Err: fmt::Display,
+    {
+        let value = self.scalar()?;
+        value
+            .trim()
+            .parse()
+            .map_err(|err| IniError(format!("invalid value {value:?}: {err}")))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for NodeDeserializer {
+    type Error = IniError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Node::Null => visitor.visit_none(),
+            Node::Scalar(value) => visitor.visit_string(value),
+            Node::Seq(items) => visitor.visit_seq(NodeSeq(items.into_iter())),
+            Node::Map(entries) => visitor.visit_map(NodeMap {
+                entries: entries.into_iter(),
+                current: None,
+            }),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.scalar()?.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => visitor.visit_bool(true),
+            "false" | "0" | "no" | "off" | "" => visitor.visit_bool(false),
+            value => Err(IniError(format!(
+                "invalid value {value:?}: expected a boolean"
+            ))),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Node::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Reads a list of values, splitting a single value on commas.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let items = match self.0 {
+            Node::Scalar(value) if value.trim().is_empty() => Vec::new(),
+            Node::Scalar(value) => value
+                .split(',')
+                .map(|item| Node::Scalar(item.trim().to_string()))
+                .collect(),
+            Node::Seq(items) => items,
+            Node::Null => Vec::new(),
+            Node::Map(_) => {
+                return Err(IniError(
+                    "expected a list of values, found a section".to_string(),
+                ))
+            }
+        };
+        visitor.visit_seq(NodeSeq(items.into_iter()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let value: StringDeserializer<IniError> = self.scalar()?.to_string().into_deserializer();
+        visitor.visit_enum(value)
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct map struct identifier ignored_any
+    }
+}
+
+/// Items of a list.
+struct NodeSeq(std::vec::IntoIter<Node>);
+
+impl<'de> SeqAccess<'de> for NodeSeq {
+    type Error = IniError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.0
+            .next()
+            .map(|item| seed.deserialize(NodeDeserializer(item)))
+            .transpose()
+    }
+}
+
+/// Properties of a section, or the sections and top-level properties of a document.
+struct NodeMap {
+    entries: std::vec::IntoIter<(String, Node)>,
+    current: Option<Node>,
+}
+
+impl<'de> MapAccess<'de> for NodeMap {
+    type Error = IniError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((name, node)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.current = Some(node);
+        seed.deserialize(name.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let node = self
+            .current
+            .take()
+            .ok_or_else(|| IniError("value requested before its key".to_string()))?;
+        seed.deserialize(NodeDeserializer(node))
+    }
+}
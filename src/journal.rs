@@ -0,0 +1,240 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::SystemTime,
+};
+
+use tracing::warn;
+
+use crate::{filesystem::Filesystem, recovery::is_stale, utils::sanitize_name, Config, Error};
+
+/// First line of every journal.
+const HEADER: &str = "libset-journal 1";
+/// Counter used to give journals unique names within a process.
+static JOURNAL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A journal recording the renames a batch is about to perform.
+pub(crate) struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    /// Removes the journal once every rename it records has been performed.
+    pub(crate) fn finish(self, fs: &dyn Filesystem) -> Result<(), Error> {
+        fs.remove(&self.path)
+            .map_err(|err| Error::io(&self.path, err))
+    }
+}
+
+impl Config {
+    /// Enables or disables journaling of batched writes.
+    ///
+    /// Before a [`Batch`](crate::Batch) renames its files into place, the list of renames is
+    /// recorded in a journal next to the version directory. If the process dies halfway
+    /// through, the next [`Config::new`] or [`Config::recover`] completes the remaining
+    /// renames, so a group of related keys is never left half-applied. Journals of batches
+    /// other processes are still committing are left alone, one is replayed once its process
+    /// exited, which is only detected on Linux, or after ten minutes.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether batches should be journaled.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_journal(mut self, enabled: bool) -> Self {
+        self.journal = enabled;
+        self
+    }
+
+    /// Records the renames of a batch when journaling is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `renames` - The synced temporary files and the paths they are renamed to.
+    pub(crate) fn begin_journal(
+        &self,
+        renames: &[(&Path, &Path)],
+    ) -> Result<Option<Journal>, Error> {
+        if !self.journal {
+            return Ok(None);
+        }
+        let mut text = format!("{HEADER}\n");
+        for (temporary, target) in renames {
+            text.push_str(&format!(
+                "{}\t{}\n",
+                self.relative(temporary),
+                self.relative(target)
+            ));
+        }
+        let dir = self.journal_dir();
+        self.fs
+            .create_dir_all(&dir)
+            .map_err(|err| Error::io(&dir, err))?;
+        let path = dir.join(format!(
+            "{}-{}",
+            std::process::id(),
+            JOURNAL_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        self.fs
+            .write(&path, text.as_bytes(), false)
+            .map_err(|err| Error::write(&path, err))?;
+        Ok(Some(Journal { path }))
+    }
+
+    /// Completes the batches interrupted by processes that died while committing them.
+    ///
+    /// Journals of batches other processes are still committing are left alone, see
+    /// [`abandoned`].
+    pub(crate) fn replay_journals(&self, now: SystemTime) -> Result<(), Error> {
+        let dir = self.journal_dir();
+        let Ok(paths) = self.fs.list(&dir) else {
+            return Ok(());
+        };
+        for path in paths {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if name.starts_with('.') || !abandoned(self.fs.as_ref(), &path, &name, now) {
+                continue;
+            }
+            let text = self.fs.read(&path).map_err(|err| Error::io(&path, err))?;
+            match self.replay(&path, &String::from_utf8_lossy(&text)) {
+                Ok(()) => {
+                    self.fs.remove(&path).map_err(|err| Error::io(&path, err))?;
+                    self.log_policy.log("replayed journal", None, &path);
+                }
+                Err(err) => {
+                    warn!(journal = %path.display(), error = %err, "failed to replay journal")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Performs the renames recorded in a journal that have not happened yet.
+    fn replay(&self, path: &Path, text: &str) -> Result<(), Error> {
+        let invalid = |reason: String| Error::Generic {
+            path: Some(path.to_path_buf()),
+            reason,
+        };
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("unsupported journal header".to_string()));
+        }
+        for line in lines.filter(|line| !line.is_empty()) {
+            let Some((temporary, target)) = line.split_once('\t') else {
+                return Err(invalid(format!("invalid journal line {line:?}")));
+            };
+            let temporary = self.root.join(sanitize_name(temporary)?);
+            let target = self.root.join(sanitize_name(target)?);
+            // Renames that already happened left no temporary file behind.
+            if self.fs.exists(&temporary) {
+                self.cache.invalidate(&target);
+                self.fs
+                    .rename(&temporary, &target)
+                    .map_err(|err| Error::io(&target, err))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a path relative to the version root, using `/` as separator.
+    pub(crate) fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Returns the directory holding the journals of this version.
+    fn journal_dir(&self) -> PathBuf {
+        self.root
+            .with_file_name(".journal")
+            .join(format!("v{}", self.version))
+    }
+}
+
+/// Determines if the process that wrote a journal stopped before finishing its batch.
+///
+/// Journals only exist while a batch renames its files, which takes milliseconds, so a
+/// journal is abandoned once its process is no longer running or it was left untouched for
+/// longer than any commit takes. Journals of the current process are never abandoned.
+fn abandoned(fs: &dyn Filesystem, path: &Path, name: &str, now: SystemTime) -> bool {
+    let pid = name
+        .split_once('-')
+        .and_then(|(pid, _)| pid.parse::<u32>().ok());
+    if pid == Some(std::process::id()) {
+        return false;
+    }
+    is_stale(fs, path, now) || pid.is_some_and(|pid| !is_running(pid))
+}
+
+/// Determines if a process is running, assuming it is when that can't be checked.
+///
+/// Only Linux exposes running processes in `/proc`, elsewhere journals are replayed once
+/// they are stale.
+fn is_running(pid: u32) -> bool {
+    let proc = Path::new("/proc");
+    !cfg!(target_os = "linux") || !proc.join("self").exists() || proc.join(pid.to_string()).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    use super::HEADER;
+    use crate::Config;
+
+    /// Writes the journal of an interrupted batch renaming `staged` to `theme.json`.
+    fn interrupted(config: &Config, pid: u32) -> PathBuf {
+        std::fs::write(config.root.join("staged"), "dark").unwrap();
+        let dir = config.journal_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let journal = dir.join(format!("{pid}-0"));
+        std::fs::write(&journal, format!("{HEADER}\nstaged\ttheme.json\n")).unwrap();
+        journal
+    }
+
+    fn replayed(config: &Config) -> bool {
+        config.recover().unwrap();
+        config.root.join("theme.json").exists()
+    }
+
+    #[test]
+    fn journals_of_running_processes_are_kept_until_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        // The first process of the system outlives the test.
+        let journal = interrupted(&config, 1);
+        assert!(!replayed(&config));
+
+        let old = SystemTime::now() - Duration::from_secs(60 * 60);
+        File::options()
+            .write(true)
+            .open(&journal)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert!(replayed(&config));
+        assert!(!journal.exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn journals_of_exited_processes_are_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        interrupted(&config, pid);
+        assert!(replayed(&config));
+    }
+}
@@ -0,0 +1,77 @@
+/// Blanks out comments and trailing commas so commented json parses as strict json.
+///
+/// Everything removed is replaced with spaces of the same length, line breaks are kept, so
+/// parse errors still point at the right line and column of the original file.
+///
+/// # Arguments
+///
+/// * `data` - The commented json document.
+///
+/// # Returns
+///
+/// The document without comments and trailing commas.
+pub(crate) fn strip(data: &str) -> String {
+    let mut output = String::with_capacity(data.len());
+    // Position of the last comma in the output, until something other than whitespace or a
+    // comment follows it.
+    let mut comma = None;
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                comma = None;
+                output.push(c);
+                while let Some(c) = chars.next() {
+                    output.push(c);
+                    match c {
+                        '\\' => output.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                blank(&mut output, c);
+                while let Some(c) = chars.next_if(|c| *c != '\n') {
+                    blank(&mut output, c);
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                blank(&mut output, c);
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    blank(&mut output, c);
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            ',' => {
+                comma = Some(output.len());
+                output.push(c);
+            }
+            '}' | ']' => {
+                if let Some(index) = comma.take() {
+                    output.replace_range(index..index + 1, " ");
+                }
+                output.push(c);
+            }
+            c if c.is_whitespace() => output.push(c),
+            c => {
+                comma = None;
+                output.push(c);
+            }
+        }
+    }
+    output
+}
+
+/// Replaces a removed character with spaces of the same length, keeping line breaks.
+fn blank(output: &mut String, c: char) {
+    if c == '\n' {
+        output.push(c);
+    } else {
+        output.extend(std::iter::repeat_n(' ', c.len_utf8()));
+    }
+}
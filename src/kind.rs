@@ -0,0 +1,352 @@
+use std::{path::Path, sync::RwLock};
+
+use crate::{utils::FileType, Error};
+
+/// A function presenting errors to users, see [`Error::set_formatter`].
+type Formatter = Box<dyn Fn(&Error) -> Option<String> + Send + Sync>;
+
+/// The formatter installed by the application, if any.
+static FORMATTER: RwLock<Option<Formatter>> = RwLock::new(None);
+
+/// The category of an [`Error`], independent of its English message.
+///
+/// Match on the kind together with [`Error::key`], [`Error::path`], [`Error::file_type`] and
+/// [`Error::position`] to build messages in the user's language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The application name is not valid or reserved.
+    InvalidName,
+    /// The key was rejected by the key validator.
+    InvalidKey,
+    /// The user's configuration directory could not be determined.
+    NoConfigDirectory,
+    /// The machine has no identifier or hostname to scope settings by.
+    NoHostId,
+    /// The user is not listed in the user database.
+    UnknownUser,
+    /// No file is stored under the key.
+    NotFound,
+    /// A file is already stored under the key.
+    AlreadyExists,
+    /// The file changed since its version token was issued.
+    Conflict,
+    /// The config or store is sealed against writes.
+    Sealed,
+    /// The file ends before its contents are complete.
+    Truncated,
+    /// Writing the file failed.
+    Write,
+    /// Any other filesystem operation failed.
+    Io,
+    /// The file is not valid text.
+    Encoding,
+    /// The file is not valid in its format, or does not match the requested type.
+    Parse,
+    /// The value could not be serialized.
+    Serialize,
+    /// The document could not be upgraded to its current schema version.
+    Migration,
+    /// Watching for changes failed.
+    Watch,
+    /// Settings could not be read from environment variables.
+    Env,
+    /// Encrypting or decrypting the file failed.
+    Encryption,
+    /// Rendering a template failed.
+    Template,
+    /// Communicating over D-Bus failed.
+    Dbus,
+    /// A manifest could not be parsed.
+    InvalidManifest,
+    /// An archive could not be read or imported.
+    InvalidArchive,
+    /// A snapshot could not be restored.
+    Snapshot,
+    /// Code built on top of libset failed, see [`Error::context`].
+    Context,
+    /// Any other failure.
+    Other,
+}
+
+/// A location within a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// The column within the line in bytes, starting at 1. Parsers report 0 when the failure
+    /// is at the line break before the line.
+    pub column: usize,
+}
+
+impl Error {
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::InvalidName(_) | Self::ReservedName(_) => ErrorKind::InvalidName,
+            Self::InvalidKey { .. } => ErrorKind::InvalidKey,
+            Self::NoConfigDirectory => ErrorKind::NoConfigDirectory,
+            Self::NoHostId => ErrorKind::NoHostId,
+            Self::UnknownUser(_) => ErrorKind::UnknownUser,
+            Self::KeyNotFound { .. } => ErrorKind::NotFound,
+            Self::AlreadyExists { .. } => ErrorKind::AlreadyExists,
+            Self::Conflict { .. } => ErrorKind::Conflict,
+            Self::Sealed { .. } => ErrorKind::Sealed,
+            Self::Truncated { .. } => ErrorKind::Truncated,
+            Self::Write { .. } | Self::WriteInPlace { .. } => ErrorKind::Write,
+            Self::GetKey { .. } | Self::Io { .. } if self.is_not_found() => ErrorKind::NotFound,
+            Self::GetKey { .. } | Self::Io { .. } | Self::ArchiveIo { .. } => ErrorKind::Io,
+            Self::Encoding { .. } => ErrorKind::Encoding,
+            #[cfg(feature = "ron")]
+            Self::Ron { .. } => ErrorKind::Serialize,
+            #[cfg(feature = "ron")]
+            Self::RonSpanned { .. } => ErrorKind::Parse,
+            // Serialization errors have no position in the input.
+            #[cfg(feature = "json")]
+            Self::Json { source, .. } if source.line() == 0 => ErrorKind::Serialize,
+            #[cfg(feature = "json")]
+            Self::Json { .. } => ErrorKind::Parse,
+            #[cfg(feature = "yaml")]
+            Self::Yaml { source, .. } if source.location().is_none() => ErrorKind::Serialize,
+            #[cfg(feature = "yaml")]
+            Self::Yaml { .. } => ErrorKind::Parse,
+            #[cfg(feature = "json5")]
+            Self::Json5 {
+                source: json5::Error::Message { location: None, .. },
+                ..
+            } => ErrorKind::Serialize,
+            #[cfg(feature = "json5")]
+            Self::Json5 { .. } => ErrorKind::Parse,
+            #[cfg(feature = "ini")]
+            Self::Ini { .. } => ErrorKind::Parse,
+            #[cfg(feature = "toml")]
+            Self::TomlSerialize { .. } => ErrorKind::Serialize,
+            #[cfg(feature = "toml")]
+            Self::TomlDeserialize { .. } => ErrorKind::Parse,
+            Self::UnsupportedByFormat { .. } => ErrorKind::Serialize,
+            Self::DuplicateKey { .. } | Self::Layered { .. } | Self::PlainValue { .. } => {
+                ErrorKind::Parse
+            }
+            Self::Migration { .. } => ErrorKind::Migration,
+            #[cfg(feature = "watch")]
+            Self::Watch { .. } => ErrorKind::Watch,
+            Self::Env { .. } => ErrorKind::Env,
+            #[cfg(feature = "encryption")]
+            Self::Encryption { .. } => ErrorKind::Encryption,
+            #[cfg(feature = "age")]
+            Self::Age { .. } => ErrorKind::Encryption,
+            #[cfg(feature = "templates")]
+            Self::Template { .. } => ErrorKind::Template,
+            #[cfg(feature = "dbus")]
+            Self::Dbus { .. } => ErrorKind::Dbus,
+            Self::InvalidManifest(_) => ErrorKind::InvalidManifest,
+            Self::InvalidArchive { .. } => ErrorKind::InvalidArchive,
+            Self::Snapshot { .. } => ErrorKind::Snapshot,
+            Self::Context { .. } => ErrorKind::Context,
+            Self::Generic { .. } => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the key the error relates to, if any.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Self::InvalidKey { key, .. }
+            | Self::KeyNotFound { key, .. }
+            | Self::AlreadyExists { key, .. }
+            | Self::Conflict { key, .. }
+            | Self::Truncated { key, .. }
+            | Self::GetKey { key, .. }
+            | Self::Encoding { key, .. }
+            | Self::PlainValue { key, .. }
+            | Self::UnsupportedByFormat { key, .. }
+            | Self::DuplicateKey { key, .. }
+            | Self::Layered { key, .. }
+            | Self::Migration { key, .. }
+            | Self::Context { key, .. } => Some(key),
+            #[cfg(feature = "ron")]
+            Self::Ron { key, .. } | Self::RonSpanned { key, .. } => Some(key),
+            #[cfg(feature = "json")]
+            Self::Json { key, .. } => Some(key),
+            #[cfg(feature = "yaml")]
+            Self::Yaml { key, .. } => Some(key),
+            #[cfg(feature = "ini")]
+            Self::Ini { key, .. } => Some(key),
+            #[cfg(feature = "json5")]
+            Self::Json5 { key, .. } => Some(key),
+            #[cfg(feature = "toml")]
+            Self::TomlSerialize { key, .. } | Self::TomlDeserialize { key, .. } => Some(key),
+            #[cfg(feature = "templates")]
+            Self::Template { key, .. } => Some(key),
+            #[cfg(feature = "encryption")]
+            Self::Encryption { key: Some(key), .. } => Some(key),
+            #[cfg(feature = "age")]
+            Self::Age { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the path of the file or directory the error relates to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Write { path, .. }
+            | Self::WriteInPlace { path, .. }
+            | Self::Io { path, .. }
+            | Self::KeyNotFound { path, .. }
+            | Self::AlreadyExists { path, .. }
+            | Self::Conflict { path, .. }
+            | Self::Truncated { path, .. }
+            | Self::GetKey { path, .. }
+            | Self::Encoding { path, .. }
+            | Self::PlainValue { path, .. }
+            | Self::UnsupportedByFormat { path, .. }
+            | Self::DuplicateKey { path, .. }
+            | Self::Layered { path, .. }
+            | Self::Migration { path, .. }
+            | Self::Sealed { path, .. }
+            | Self::Context { path, .. } => Some(path),
+            #[cfg(feature = "ron")]
+            Self::Ron { path, .. } | Self::RonSpanned { path, .. } => Some(path),
+            #[cfg(feature = "json")]
+            Self::Json { path, .. } => Some(path),
+            #[cfg(feature = "yaml")]
+            Self::Yaml { path, .. } => Some(path),
+            #[cfg(feature = "ini")]
+            Self::Ini { path, .. } => Some(path),
+            #[cfg(feature = "json5")]
+            Self::Json5 { path, .. } => Some(path),
+            #[cfg(feature = "toml")]
+            Self::TomlSerialize { path, .. } | Self::TomlDeserialize { path, .. } => Some(path),
+            #[cfg(feature = "watch")]
+            Self::Watch { path, .. } => Some(path),
+            #[cfg(feature = "templates")]
+            Self::Template { path, .. } => Some(path),
+            #[cfg(feature = "age")]
+            Self::Age { path, .. } => Some(path),
+            Self::Generic {
+                path: Some(path), ..
+            } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the format of the file the error relates to, if known.
+    pub fn file_type(&self) -> Option<FileType> {
+        match self {
+            Self::KeyNotFound { file_type, .. }
+            | Self::AlreadyExists { file_type, .. }
+            | Self::Conflict { file_type, .. }
+            | Self::Truncated { file_type, .. }
+            | Self::GetKey { file_type, .. }
+            | Self::UnsupportedByFormat { file_type, .. }
+            | Self::DuplicateKey { file_type, .. }
+            | Self::Layered { file_type, .. }
+            | Self::Migration { file_type, .. } => Some(*file_type),
+            #[cfg(feature = "ron")]
+            Self::Ron { .. } | Self::RonSpanned { .. } => Some(FileType::Ron),
+            Self::PlainValue { .. } => Some(FileType::Plain),
+            #[cfg(feature = "json")]
+            Self::Json { .. } => Some(FileType::Json),
+            #[cfg(feature = "yaml")]
+            Self::Yaml { .. } => Some(FileType::Yaml),
+            #[cfg(feature = "ini")]
+            Self::Ini { .. } => Some(FileType::Ini),
+            #[cfg(feature = "json5")]
+            Self::Json5 { .. } => Some(FileType::Json5),
+            #[cfg(feature = "toml")]
+            Self::TomlSerialize { .. } | Self::TomlDeserialize { .. } => Some(FileType::Toml),
+            _ => None,
+        }
+    }
+
+    /// Returns where parsing failed within the file, if the parser reported it.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            #[cfg(feature = "ron")]
+            Self::RonSpanned { source, .. } => Some(Position {
+                line: source.position.line,
+                column: source.position.col,
+            }),
+            #[cfg(feature = "json")]
+            Self::Json { source, .. } if source.line() > 0 => Some(Position {
+                line: source.line(),
+                column: source.column(),
+            }),
+            #[cfg(feature = "yaml")]
+            Self::Yaml { source, .. } => source.location().map(|location| Position {
+                line: location.line(),
+                column: location.column(),
+            }),
+            #[cfg(feature = "ini")]
+            Self::Ini { position, .. } => *position,
+            #[cfg(feature = "json5")]
+            Self::Json5 {
+                source: json5::Error::Message { location, .. },
+                ..
+            } => location.as_ref().map(|location| Position {
+                line: location.line,
+                column: location.column,
+            }),
+            // toml only exposes byte offsets, the line and column are part of its message.
+            #[cfg(feature = "toml")]
+            Self::TomlDeserialize { source, .. } => {
+                let message = source.to_string();
+                let (line, column) = message
+                    .split_once("at line ")?
+                    .1
+                    .lines()
+                    .next()?
+                    .split_once(", column ")?;
+                Some(Position {
+                    line: line.parse().ok()?,
+                    column: column.parse().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Installs the function used by [`Error::localized`] to present errors to users.
+    ///
+    /// The formatter returns `None` for errors it does not handle, which fall back to the
+    /// English message. It applies to the whole process, installing another one replaces it.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, Error, ErrorKind};
+    /// # fn main() -> Result<(), Error> {
+    /// Error::set_formatter(|err| match (err.kind(), err.key()) {
+    ///     (ErrorKind::NotFound, Some(key)) => Some(format!("Clé introuvable : {key}")),
+    ///     (ErrorKind::Parse, Some(key)) => {
+    ///         let line = err.position().map_or(0, |position| position.line);
+    ///         Some(format!("Le fichier {key} est invalide à la ligne {line}"))
+    ///     }
+    ///     _ => None,
+    /// });
+    ///
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// if let Err(err) = config.get_json::<String>("accent") {
+    ///     eprintln!("{}", err.localized());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `formatter` - Function returning the message for an error.
+    pub fn set_formatter<F>(formatter: F)
+    where
+        F: Fn(&Error) -> Option<String> + Send + Sync + 'static,
+    {
+        *FORMATTER.write().unwrap() = Some(Box::new(formatter));
+    }
+
+    /// Returns the message for users, from the installed formatter or the English message.
+    pub fn localized(&self) -> String {
+        FORMATTER
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|formatter| formatter(self))
+            .unwrap_or_else(|| self.to_string())
+    }
+}
@@ -0,0 +1,254 @@
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+use crate::{deserialize, parse::ParseOptions, utils::FileType, Error};
+#[cfg(feature = "ini")]
+use crate::{env::Node, ini};
+
+/// A document held in the value type of its format, so layers can be merged into it.
+pub(crate) enum Document {
+    #[cfg(feature = "toml")]
+    Toml(toml::Value),
+    #[cfg(feature = "json")]
+    Json(serde_json::Value),
+    #[cfg(feature = "ron")]
+    Ron(ron::Value),
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Value),
+    #[cfg(feature = "ini")]
+    Ini(Node),
+    #[cfg(feature = "json5")]
+    Json5(serde_json::Value),
+}
+
+impl Document {
+    /// Parses a document of the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key the document is read for.
+    /// * `file_type` - The format of the document.
+    /// * `path` - The path of the file, used for error reporting.
+    /// * `data` - The text of the document.
+    /// * `options` - The options the format is parsed with.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the document or an `Error` if it could not be parsed.
+    pub(crate) fn parse(
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+        data: &str,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        Ok(match file_type {
+            #[cfg(feature = "toml")]
+            FileType::Toml => Self::Toml(deserialize(key, file_type, path, data, options)?),
+            #[cfg(feature = "json")]
+            FileType::Json => Self::Json(deserialize(key, file_type, path, data, options)?),
+            #[cfg(feature = "ron")]
+            FileType::Ron => Self::Ron(deserialize(key, file_type, path, data, options)?),
+            #[cfg(feature = "yaml")]
+            FileType::Yaml => Self::Yaml(deserialize(key, file_type, path, data, options)?),
+            #[cfg(feature = "ini")]
+            FileType::Ini => Self::Ini(deserialize(key, file_type, path, data, options)?),
+            #[cfg(feature = "json5")]
+            FileType::Json5 => Self::Json5(deserialize(key, file_type, path, data, options)?),
+            FileType::Plain => unreachable!("Plain files have no layers."),
+        })
+    }
+
+    /// Takes the top-level entry of the document named after a key.
+    pub(crate) fn section(self, key: &str) -> Option<Self> {
+        match self {
+            #[cfg(feature = "toml")]
+            Self::Toml(toml::Value::Table(mut table)) => table.remove(key).map(Self::Toml),
+            #[cfg(feature = "json")]
+            Self::Json(serde_json::Value::Object(mut object)) => object.remove(key).map(Self::Json),
+            #[cfg(feature = "ron")]
+            Self::Ron(ron::Value::Map(mut map)) => map
+                .remove(&ron::Value::String(key.to_string()))
+                .map(Self::Ron),
+            #[cfg(feature = "yaml")]
+            Self::Yaml(serde_yaml::Value::Mapping(mut mapping)) => {
+                mapping.remove(key).map(Self::Yaml)
+            }
+            #[cfg(feature = "ini")]
+            Self::Ini(Node::Map(mut entries)) => entries
+                .iter()
+                .rposition(|(name, _)| name == key)
+                .map(|index| Self::Ini(entries.swap_remove(index).1)),
+            #[cfg(feature = "json5")]
+            Self::Json5(serde_json::Value::Object(mut object)) => {
+                object.remove(key).map(Self::Json5)
+            }
+            _ => None,
+        }
+    }
+
+    /// Merges a layer on top of the document.
+    ///
+    /// Tables are merged entry by entry, any other value of the layer replaces the one in the
+    /// document. Layers of another format are ignored.
+    pub(crate) fn merge(&mut self, layer: Self) {
+        match (self, layer) {
+            #[cfg(feature = "toml")]
+            (Self::Toml(document), Self::Toml(layer)) => merge_toml(document, layer),
+            #[cfg(feature = "json")]
+            (Self::Json(document), Self::Json(layer)) => merge_json(document, layer),
+            #[cfg(feature = "ron")]
+            (Self::Ron(document), Self::Ron(layer)) => merge_ron(document, layer),
+            #[cfg(feature = "yaml")]
+            (Self::Yaml(document), Self::Yaml(layer)) => merge_yaml(document, layer),
+            #[cfg(feature = "ini")]
+            (Self::Ini(document), Self::Ini(layer)) => merge_node(document, layer),
+            #[cfg(feature = "json5")]
+            (Self::Json5(document), Self::Json5(layer)) => merge_json(document, layer),
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
+
+    /// Deserializes the merged document into a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key the document was read for.
+    /// * `path` - The path of the file the layers were merged over, used for error reporting.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value or `Error::Layered` if the merged document
+    /// does not match the type.
+    pub(crate) fn into_value<T: DeserializeOwned>(
+        self,
+        key: &str,
+        path: &Path,
+    ) -> Result<T, Error> {
+        let (file_type, result) = match self {
+            #[cfg(feature = "toml")]
+            Self::Toml(value) => (
+                FileType::Toml,
+                T::deserialize(value).map_err(|err| err.to_string().trim_end().to_string()),
+            ),
+            #[cfg(feature = "json")]
+            Self::Json(value) => (
+                FileType::Json,
+                serde_json::from_value(value).map_err(|err| err.to_string()),
+            ),
+            #[cfg(feature = "ron")]
+            Self::Ron(value) => (
+                FileType::Ron,
+                value.into_rust().map_err(|err| err.to_string()),
+            ),
+            #[cfg(feature = "yaml")]
+            Self::Yaml(value) => (
+                FileType::Yaml,
+                serde_yaml::from_value(value).map_err(|err| err.to_string()),
+            ),
+            #[cfg(feature = "ini")]
+            Self::Ini(value) => (
+                FileType::Ini,
+                T::deserialize(ini::NodeDeserializer(value)).map_err(|err| err.to_string()),
+            ),
+            #[cfg(feature = "json5")]
+            Self::Json5(value) => (
+                FileType::Json5,
+                serde_json::from_value(value).map_err(|err| err.to_string()),
+            ),
+        };
+        result.map_err(|reason| Error::Layered {
+            key: key.to_string(),
+            file_type,
+            path: path.to_path_buf(),
+            reason,
+        })
+    }
+}
+
+#[cfg(feature = "toml")]
+fn merge_toml(document: &mut toml::Value, layer: toml::Value) {
+    match (document, layer) {
+        (toml::Value::Table(document), toml::Value::Table(layer)) => {
+            for (name, value) in layer {
+                match document.get_mut(&name) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        document.insert(name, value);
+                    }
+                }
+            }
+        }
+        (document, layer) => *document = layer,
+    }
+}
+
+#[cfg(any(feature = "json", feature = "json5"))]
+fn merge_json(document: &mut serde_json::Value, layer: serde_json::Value) {
+    match (document, layer) {
+        (serde_json::Value::Object(document), serde_json::Value::Object(layer)) => {
+            for (name, value) in layer {
+                match document.get_mut(&name) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        document.insert(name, value);
+                    }
+                }
+            }
+        }
+        (document, layer) => *document = layer,
+    }
+}
+
+#[cfg(feature = "ron")]
+fn merge_ron(document: &mut ron::Value, layer: ron::Value) {
+    match (document, layer) {
+        (ron::Value::Map(document), ron::Value::Map(layer)) => {
+            for (name, value) in layer {
+                let merged = match document.remove(&name) {
+                    Some(mut existing) => {
+                        merge_ron(&mut existing, value);
+                        existing
+                    }
+                    None => value,
+                };
+                document.insert(name, merged);
+            }
+        }
+        (document, layer) => *document = layer,
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn merge_yaml(document: &mut serde_yaml::Value, layer: serde_yaml::Value) {
+    match (document, layer) {
+        (serde_yaml::Value::Mapping(document), serde_yaml::Value::Mapping(layer)) => {
+            for (name, value) in layer {
+                match document.get_mut(&name) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        document.insert(name, value);
+                    }
+                }
+            }
+        }
+        (document, layer) => *document = layer,
+    }
+}
+
+#[cfg(feature = "ini")]
+fn merge_node(document: &mut Node, layer: Node) {
+    match (document, layer) {
+        (Node::Map(document), Node::Map(layer)) => {
+            for (name, value) in layer {
+                match document.iter_mut().find(|(existing, _)| *existing == name) {
+                    Some((_, existing)) => merge_node(existing, value),
+                    None => document.push((name, value)),
+                }
+            }
+        }
+        (document, layer) => *document = layer,
+    }
+}
@@ -0,0 +1,68 @@
+use std::{ops::Deref, sync::OnceLock};
+
+use serde::de::DeserializeOwned;
+
+use crate::{traits::Get, utils::FileType, Config, Error};
+
+/// A value loaded from a file the first time it is accessed.
+///
+/// Created with [`Config::lazy_json`] and its siblings. Dereferencing a `Lazy` whose file
+/// can't be loaded panics, use [`Lazy::get`] to handle the error instead.
+pub struct Lazy<'a, T> {
+    config: &'a Config,
+    key: String,
+    file_type: FileType,
+    value: OnceLock<T>,
+}
+
+impl<'a, T: DeserializeOwned> Lazy<'a, T> {
+    pub(crate) fn new(config: &'a Config, key: &str, file_type: FileType) -> Self {
+        Self {
+            config,
+            key: key.to_string(),
+            file_type,
+            value: OnceLock::new(),
+        }
+    }
+
+    /// Returns the value, loading it if it hasn't been loaded yet.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a reference to the value or an `Error` if it could not be loaded.
+    pub fn get(&self) -> Result<&T, Error> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = self.config.get::<T>(&self.key, self.file_type)?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    /// Determines if the value has been loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Loads the value again from the filesystem.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a reference to the new value or an `Error` if it could not be
+    /// loaded, in which case the previous value is kept.
+    pub fn reload(&mut self) -> Result<&T, Error> {
+        let value = self.config.get::<T>(&self.key, self.file_type)?;
+        self.value = OnceLock::from(value);
+        self.get()
+    }
+}
+
+impl<T: DeserializeOwned> Deref for Lazy<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match self.get() {
+            Ok(value) => value,
+            Err(err) => panic!("Failed to load {}: {err}", self.key),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::{
+    deserialize, entry::LEGACY_MARKER, traits::Set, utils::decode, utils::FileType, Config, Error,
+};
+
+/// A file written before the application used libset, see [`Config::import_legacy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyLocation {
+    /// The path of the file, such as an old dotfile.
+    pub path: PathBuf,
+    /// The format of the file.
+    pub format: FileType,
+    /// The key the settings are stored under.
+    pub key: String,
+}
+
+impl Config {
+    /// Imports settings from the locations the application used before libset.
+    ///
+    /// Runs once per version: a hidden marker is written to the version root after a
+    /// successful import, and later calls return without reading anything. Each file is
+    /// parsed and stored under its key in the same format, missing files are skipped and
+    /// keys already stored are left untouched. When a file can't be read or parsed nothing
+    /// is recorded, so the import is tried again on the next run.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType, LegacyLocation};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let home = dirs::home_dir().unwrap();
+    /// let config = Config::new("org.example.Editor", 1, None)?;
+    /// config.import_legacy(&[LegacyLocation {
+    ///     path: home.join(".editorrc.json"),
+    ///     format: FileType::Json,
+    ///     key: "settings".to_string(),
+    /// }])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `locations` - The legacy files and the keys to store them under.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the keys imported, empty if the import already happened, or an
+    /// `Error` if a file could not be read, parsed or stored.
+    pub fn import_legacy(&self, locations: &[LegacyLocation]) -> Result<Vec<String>, Error> {
+        let marker = self.root.join(LEGACY_MARKER);
+        if self.fs.exists(&marker) {
+            return Ok(Vec::new());
+        }
+        let mut imported = Vec::new();
+        for location in locations {
+            let LegacyLocation { path, format, key } = location;
+            if self.exists(&self.path(key, *format)?) {
+                continue;
+            }
+            let bytes = match std::fs::read(path) {
+                Ok(bytes) => bytes,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(Error::for_key(key, *format, path, err)),
+            };
+            let data = decode(key, path, bytes)?;
+            match format {
+                FileType::Plain => self.set_plain(key, data)?,
+                format => {
+                    let value: Value = deserialize(key, *format, path, &data, &self.parse)?;
+                    self.set(key, *format, &value)?;
+                }
+            }
+            self.log_policy
+                .log("imported legacy file", Some(key.as_str()), path);
+            imported.push(key.clone());
+        }
+        self.fs
+            .write(&marker, imported.join("\n").as_bytes(), false)
+            .map_err(|err| Error::io(&marker, err))?;
+        Ok(imported)
+    }
+}
@@ -15,6 +15,9 @@
 //! * [`JSON`](https://www.json.org/json-en.html) - JavaScript Object Notation
 //! * [`TOML`](https://toml.io/en/) - Tom's Obvious Minimal Language
 //! * [`RON`](https://github.com/ron-rs/ron) - Rusty Object Notation
+//! * [`YAML`](https://yaml.org/) - YAML Ain't Markup Language
+//! * [`INI`](https://en.wikipedia.org/wiki/INI_file) - Initialization files
+//! * [`JSON5`](https://json5.org/) - JSON for humans, with comments and trailing commas
 //!
 //! ## Features
 //! By default, Libset enables JSON format support. Additional formats can be activated using feature flags:
@@ -22,6 +25,23 @@
 //! * `json` - Seamlessly interact with JSON files.
 //! * `toml` - Effortlessly modify TOML files.
 //! * `ron` - Easily retreive RON files.
+//! * `yaml` - Read and write YAML files.
+//! * `ini` - Read and write INI files with sections.
+//! * `json5` - Read hand-edited JSON5 files with comments and trailing commas.
+//! * `watch` - Get notified when stored files change, with the values that changed in them, stream raw filesystem events, or keep a value reloaded with the `daemon` module.
+//! * `sighup` - Reload settings when a Unix daemon receives `SIGHUP`.
+//! * `encryption` - Encrypt selected keys at rest.
+//! * `age` - Store json files encrypted with the `age` tool.
+//! * `keyring` - Store encryption keys in the operating system keyring.
+//! * `tpm` - Seal encryption keys with the TPM2 chip on Linux.
+//! * `rayon` - Load batches of files in parallel.
+//! * `templates` - Render stored templates into new files.
+//! * `docs` - Generate documentation for settings types.
+//! * `cli` - Build the `libset` command line tool.
+//! * `tui` - Add an interactive store browser to the command line tool.
+//! * `dbus` - Expose settings to other processes over D-Bus.
+//! * `repair` - Fix common damage in json and toml files.
+//! * `miette` - Render parse errors with source snippets through miette.
 //!
 //! ## Additional Benefits
 
@@ -30,49 +50,318 @@
 //! - **Cross-Platform Compatibility**: Works seamlessly across different operating systems, enhancing flexibility in deployment.
 //! - **Documentation**: Comprehensive documentation and examples make integration and usage straightforward for developers of all levels.
 
-use std::{io::Write, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
 
 use serde::{de::DeserializeOwned, Serialize};
-use tracing::info;
 
+#[cfg(feature = "age")]
+mod age;
+mod archive;
+mod batch;
+#[cfg(unix)]
+mod broadcast;
+mod cache;
+mod check;
+#[cfg(any(
+    feature = "toml",
+    feature = "json",
+    feature = "ron",
+    feature = "yaml",
+    feature = "ini",
+    feature = "json5"
+))]
+mod collection;
+mod color;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+mod credentials;
+#[cfg(feature = "watch")]
+pub mod daemon;
+#[cfg(feature = "dbus")]
+pub mod dbus;
+#[cfg(feature = "miette")]
+mod diagnostic;
+#[cfg(all(feature = "watch", feature = "json"))]
+mod diff;
+#[cfg(feature = "docs")]
+pub mod docs;
+#[cfg(feature = "json")]
+mod duplicates;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod entry;
+mod env;
 mod error;
+mod ext;
+mod filesystem;
+#[cfg(feature = "json")]
+mod flags;
+mod flusher;
+mod formats;
+mod guard;
+mod header;
+mod hooks;
+mod host;
+#[cfg(feature = "ini")]
+mod ini;
+mod journal;
+#[cfg(feature = "json")]
+mod jsonc;
+mod kind;
+#[cfg(any(
+    feature = "toml",
+    feature = "json",
+    feature = "ron",
+    feature = "yaml",
+    feature = "ini",
+    feature = "json5"
+))]
+mod layer;
+mod lazy;
+#[cfg(feature = "json")]
+mod legacy;
+#[cfg(feature = "toml")]
+mod limits;
+mod local;
+mod log;
+mod manifest;
+#[cfg(feature = "json")]
+mod metrics;
+mod middleware;
+#[cfg(feature = "json")]
+mod migrate;
+#[cfg(feature = "json")]
+mod mru;
+mod overwrite;
+mod parse;
+mod plain;
+mod preload;
+#[cfg(feature = "std-fs")]
+mod profile;
+mod prune;
+mod recovery;
+#[cfg(feature = "repair")]
+mod repair;
+mod retention;
+mod seal;
+mod secret;
+mod shortcut;
+mod snapshot;
+#[cfg(all(feature = "json", feature = "std-fs"))]
+pub mod state;
+mod strategy;
+mod summary;
+#[cfg(feature = "templates")]
+mod template;
+mod token;
+#[cfg(all(target_os = "linux", feature = "tpm"))]
+mod tpm;
 mod traits;
+mod truncation;
+mod units;
+#[cfg(unix)]
+mod user;
 mod utils;
+mod validator;
+#[cfg(feature = "watch")]
+mod watch;
 
+pub use archive::Archive;
+pub use batch::Batch;
+#[cfg(unix)]
+pub use broadcast::{Broadcast, Subscription};
+use cache::{Cache, PathCache, Stamp};
+use check::Schema;
+pub use check::{Issue, IssueKind, Report};
+#[cfg(feature = "json")]
+pub use collection::MergePolicy;
+#[cfg(any(
+    feature = "toml",
+    feature = "json",
+    feature = "ron",
+    feature = "yaml",
+    feature = "ini",
+    feature = "json5"
+))]
+pub use collection::{Collection, COLLECTION_CACHE_CAPACITY};
+pub use color::Color;
+#[cfg(all(feature = "watch", feature = "json"))]
+pub use diff::{Diff, FieldChange};
+#[cfg(feature = "json")]
+pub use duplicates::{DuplicateKey, DuplicateKeys};
+#[cfg(feature = "keyring")]
+pub use encryption::KeyringKey;
+#[cfg(feature = "encryption")]
+pub use encryption::{
+    EncryptedKeys, Encryption, EncryptionKey, KeyChain, KeyProvider, PassphraseKey, StaticKey,
+};
+use entry::{is_temporary, split_name};
+pub use entry::{Entries, Entry, Walk};
 pub use error::Error;
+pub use ext::LibsetResultExt;
+#[cfg(feature = "std-fs")]
+pub use filesystem::StdFilesystem;
+pub use filesystem::{Filesystem, MemoryFilesystem, Metadata};
+#[cfg(feature = "json")]
+pub use flags::{FlagRefresh, FlagSource, Flags};
+use flusher::Flusher;
+pub use formats::FormatConflict;
+pub use guard::{NamedLock, ReadGuard, WriteGuard};
+pub use header::WrittenBy;
+use hooks::Hook;
+pub use hooks::{Event, EventKind};
+pub use kind::{ErrorKind, Position};
+pub use lazy::Lazy;
+#[cfg(feature = "json")]
+pub use legacy::LegacyLocation;
+use local::LocalFile;
+pub use log::LogPolicy;
+pub use manifest::{Drift, DriftKind, Manifest};
+#[cfg(feature = "json")]
+pub use metrics::{Counter, Timestamp};
+pub use middleware::{Direction, EntryInfo, Middleware};
+#[cfg(feature = "json")]
+pub use migrate::{Migration, SCHEMA_VERSION_FIELD};
+#[cfg(feature = "json")]
+pub use mru::Mru;
+pub use overwrite::OverwriteBehavior;
+use parse::ParseOptions;
+pub use plain::PlainPolicy;
+pub use preload::Preload;
+#[cfg(feature = "std-fs")]
+pub use profile::PROFILE_VARIABLE;
+#[cfg(feature = "repair")]
+pub use repair::Repair;
+pub use retention::Retention;
+pub use secret::Secret;
+pub use shortcut::{Modifiers, Shortcut, ShortcutKey};
+pub use strategy::WriteStrategy;
+pub use summary::{Summary, Usage};
+pub use token::Token;
+#[cfg(all(target_os = "linux", feature = "tpm"))]
+pub use tpm::TpmKey;
 use traits::{Get, Set};
-use utils::sanitize_name;
+pub use units::{ByteSize, HumanDuration, ParseValueError};
 pub use utils::FileType;
+use utils::{decode, find_case_insensitive, normalize_key, sanitize_name};
+pub use validator::KeyValidator;
+#[cfg(feature = "watch")]
+pub use watch::{Change, ChangeKind, RawEvent, RawEventKind, RawEvents, Watcher};
+pub use zeroize::Zeroizing;
 
 /// Represents a configuration object.
 ///
 /// Start by creating a new `Config` object:
-/// ```
+/// ```no_run
+/// # use libset::Config;
+/// # fn main() -> Result<(), libset::Error> {
 /// let config = Config::new("org.example.Demo", 1, None)?;
+/// # Ok(())
+/// # }
 /// ```
 /// Provide an application name, a version and optionally a prefix, then, a new directory will be added
 /// to your filesystem, this is where all the created files will be stored in.
 ///
 /// ### Write a file.
-/// ```rust
+/// ```no_run
+/// # use libset::Config;
+/// # use serde_json::json;
+/// # fn main() -> Result<(), libset::Error> {
 /// let config = Config::new("org.example.Demo", 1, None)?;
 /// config.set_json("colors", json!({ "accent": "#7a7af9" }))?;
+/// # Ok(())
+/// # }
 /// ```
 /// > This wil store the file here: `$HOME/.config/org.example.Demo/v1/colors.json`
 ///
 /// ### Get a file.
-/// ```rust
+/// ```no_run
+/// # use libset::Config;
+/// # use serde::{Deserialize, Serialize};
+/// # fn main() -> Result<(), libset::Error> {
+/// # let config = Config::new("org.example.Demo", 1, None)?;
 /// #[derive(Debug, Serialize, Deserialize)]
 /// struct Colors { accent: String }
 /// let settings: Colors = config.get_json("colors")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ### Share between threads.
+/// `Config` is `Send + Sync`, so a single instance can be stored in a `static` and used from
+/// any thread. Its caches use read-write locks, concurrent reads never wait for each other.
+/// ```no_run
+/// # use std::sync::OnceLock;
+/// # use libset::Config;
+/// # use serde_json::Value;
+/// static CONFIG: OnceLock<Config> = OnceLock::new();
+///
+/// fn config() -> &'static Config {
+///     CONFIG.get_or_init(|| Config::new("org.example.Demo", 1, None).unwrap())
+/// }
+///
+/// # fn main() -> Result<(), libset::Error> {
+/// std::thread::spawn(|| config().set_json("colors", serde_json::json!({ "accent": "#7a7af9" })));
+/// let colors = config().cached_json::<Value>("colors")?;
+/// # Ok(())
+/// # }
 /// ```
 pub struct Config {
+    name: String,
     path: PathBuf,
+    root: PathBuf,
+    scope: Option<PathBuf>,
+    fallbacks: Vec<PathBuf>,
+    credentials: Option<PathBuf>,
+    version: u64,
+    written_by: Option<WrittenBy>,
+    journal: bool,
+    snapshot_fallback: bool,
+    retention: Retention,
+    #[cfg(feature = "encryption")]
+    required_encryption: Option<EncryptedKeys>,
+    schemas: Vec<Schema>,
+    normalize_keys: bool,
+    case_insensitive_keys: bool,
+    validator: KeyValidator,
+    hooks: Vec<Hook>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    log_policy: LogPolicy,
+    write_strategy: WriteStrategy,
+    overwrite: OverwriteBehavior,
+    #[cfg(feature = "toml")]
+    toml_stringified_keys: bool,
+    parse: ParseOptions,
+    plain_policy: PlainPolicy,
+    sealed: AtomicBool,
+    local: Option<LocalFile>,
+    format_order: Vec<FileType>,
+    #[cfg(feature = "json")]
+    migrations: HashMap<String, Arc<[Migration]>>,
+    flusher: Option<Flusher>,
+    fs: Arc<dyn Filesystem>,
+    cache: Arc<Cache>,
+    paths: PathCache,
 }
 
+// Keeps `Config` usable from `static` holders, every field added must be thread safe.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Config>();
+};
+
 impl Config {
+    #[cfg(feature = "std-fs")]
     /// Creates a new `Config` object.
     ///
+    /// Temporary files left behind by processes that crashed while writing are removed and
+    /// logged, see [`Config::recover`].
+    ///
     /// # Arguments
     ///
     /// * `name` - The name of the application.
@@ -83,20 +372,408 @@ impl Config {
     ///
     /// A `Result` containing the new `Config` object or an `Error` if an error occurred.
     pub fn new(name: &str, version: u64, scope: Option<&str>) -> Result<Self, Error> {
-        let main_path = sanitize_name(name)?.join(format!("v{}", version));
+        Self::new_without_recovery(name, version, scope).map(Config::recover_quietly)
+    }
 
+    /// Creates a new `Config` object without removing files left behind by crashed processes.
+    ///
+    /// [`Config::new`] cleans up abandoned temporary files, see [`Config::recover`]. Use this
+    /// constructor when the store must not be modified on startup.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the application.
+    /// * `version` - The version of the configuration.
+    /// * `scope` - An optional scope for the application.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `Config` object or an `Error` if an error occurred.
+    #[cfg(feature = "std-fs")]
+    pub fn new_without_recovery(
+        name: &str,
+        version: u64,
+        scope: Option<&str>,
+    ) -> Result<Self, Error> {
         let user_path = dirs::config_dir().ok_or(Error::NoConfigDirectory)?;
+        Self::new_in(&user_path, name, version, scope)
+    }
+
+    /// Creates a new `Config` object stored under a base directory of the local filesystem.
+    ///
+    /// Unlike [`Config::new`], files left behind by crashed processes are not removed, call
+    /// [`Config::recover`] to clean them up.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The directory holding the directories of every application.
+    /// * `name` - The name of the application.
+    /// * `version` - The version of the configuration.
+    /// * `scope` - An optional scope for the application.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `Config` object or an `Error` if an error occurred.
+    #[cfg(feature = "std-fs")]
+    pub fn new_in(
+        base: &Path,
+        name: &str,
+        version: u64,
+        scope: Option<&str>,
+    ) -> Result<Self, Error> {
+        Self::new_with_filesystem(StdFilesystem, base, name, version, scope)
+    }
+
+    /// Creates a new `Config` object stored under a base directory of another storage.
+    ///
+    /// The directories of the configuration are created through the storage, the local
+    /// filesystem is not touched. Files left behind by crashed processes are not removed, call
+    /// [`Config::recover`] to clean them up.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, MemoryFilesystem};
+    /// # use std::path::Path;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new_with_filesystem(
+    ///     MemoryFilesystem::default(),
+    ///     Path::new("/settings"),
+    ///     "org.example.Demo",
+    ///     1,
+    ///     None,
+    /// )?;
+    /// config.set_plain("session", "42")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `fs` - The storage files are read from and written to.
+    /// * `base` - The directory holding the directories of every application.
+    /// * `name` - The name of the application.
+    /// * `version` - The version of the configuration.
+    /// * `scope` - An optional scope for the application.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `Config` object or an `Error` if an error occurred.
+    pub fn new_with_filesystem(
+        fs: impl Filesystem + 'static,
+        base: &Path,
+        name: &str,
+        version: u64,
+        scope: Option<&str>,
+    ) -> Result<Self, Error> {
+        let main_path = sanitize_name(name)?.join(format!("v{}", version));
 
-        let config_path = if let Some(scope) = scope {
-            let scope = sanitize_name(scope)?;
-            user_path.join(main_path).join(scope)
+        let root = base.join(main_path);
+        let scope = scope.map(sanitize_name).transpose()?.map(PathBuf::from);
+        let config_path = if let Some(scope) = &scope {
+            root.join(scope)
         } else {
-            user_path.join(main_path)
+            root.clone()
         };
 
-        std::fs::create_dir_all(&config_path)?;
+        fs.create_dir_all(&config_path)
+            .map_err(|err| Error::io(&config_path, err))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            path: config_path,
+            root,
+            scope,
+            fallbacks: Vec::new(),
+            credentials: credentials::credentials_directory(),
+            version,
+            written_by: None,
+            journal: false,
+            snapshot_fallback: false,
+            retention: Retention::default(),
+            #[cfg(feature = "encryption")]
+            required_encryption: None,
+            schemas: Vec::new(),
+            normalize_keys: false,
+            case_insensitive_keys: false,
+            validator: KeyValidator::default(),
+            hooks: Vec::new(),
+            middleware: Vec::new(),
+            log_policy: LogPolicy::default(),
+            write_strategy: WriteStrategy::default(),
+            overwrite: OverwriteBehavior::default(),
+            #[cfg(feature = "toml")]
+            toml_stringified_keys: false,
+            parse: ParseOptions::default(),
+            plain_policy: PlainPolicy::default(),
+            sealed: AtomicBool::new(false),
+            local: None,
+            format_order: formats::default_order(),
+            #[cfg(feature = "json")]
+            migrations: HashMap::new(),
+            flusher: None,
+            fs: Arc::new(fs),
+            cache: Arc::default(),
+            paths: PathCache::default(),
+        })
+    }
+
+    /// Moves the configuration into a scope that falls back to the current one for reads.
+    ///
+    /// Reads look for the key in the new scope first and then in each scope it falls back
+    /// to, while writes always go to the new scope. Calling this repeatedly builds a chain,
+    /// so per-profile overrides can sit on top of shared defaults.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?.scoped_with_fallback("profile-work")?;
+    /// // Reads profile-work/theme.json, or theme.json if the profile doesn't override it.
+    /// let theme: String = config.get_json("theme")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `scope` - The scope to read from first and write to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated `Config` object or an `Error` if the scope is invalid
+    /// or its directory could not be created.
+    pub fn scoped_with_fallback(mut self, scope: &str) -> Result<Self, Error> {
+        let scope = sanitize_name(scope)?.to_path_buf();
+        let path = self.root.join(&scope);
+        self.fs
+            .create_dir_all(&path)
+            .map_err(|err| Error::io(&path, err))?;
+        let previous = std::mem::replace(&mut self.path, path);
+        self.fallbacks.insert(0, previous);
+        self.scope = Some(scope);
+        self.paths.clear();
+        Ok(self)
+    }
+
+    /// Records the application version in every file written from now on.
+    ///
+    /// TOML and RON files start with a comment and JSON objects get a leading `$libset`
+    /// field naming the application version and the configuration version, which can be read
+    /// back with [`Config::written_by`]. The field is removed again when JSON files are read.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_version` - The version of the application, such as `env!("CARGO_PKG_VERSION")`.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_file_header(mut self, app_version: &str) -> Self {
+        self.written_by = Some(WrittenBy {
+            app_version: header::sanitize(app_version),
+            schema_version: self.version,
+        });
+        self
+    }
+
+    /// Enables or disables Unicode NFC normalization of key names.
+    ///
+    /// When enabled, keys such as `Thème` written with a combining accent and with a
+    /// precomposed character resolve to the same file.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether keys should be normalized.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_normalized_keys(mut self, enabled: bool) -> Self {
+        self.normalize_keys = enabled;
+        self.paths.clear();
+        self
+    }
+
+    /// Enables or disables case-insensitive key lookup.
+    ///
+    /// When enabled, new files are stored with lowercase names and lookups match existing files
+    /// regardless of case, so `THEME` and `theme` behave the same on every platform.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether keys should be matched case-insensitively.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_case_insensitive_keys(mut self, enabled: bool) -> Self {
+        self.case_insensitive_keys = enabled;
+        self.paths.clear();
+        self
+    }
+
+    /// Sets the validator applied to every key before it is resolved to a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `validator` - The validation rules for keys.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_key_validator(mut self, validator: KeyValidator) -> Self {
+        self.validator = validator;
+        self.paths.clear();
+        self
+    }
+
+    /// Enables or disables write-behind mode.
+    ///
+    /// In write-behind mode `set_*` calls serialize the value and return immediately, the atomic
+    /// write happens on a background thread. Reads observe pending values, [`Config::flush`]
+    /// waits for queued writes and dropping the `Config` completes them.
+    ///
+    /// Hooks registered after enabling this mode are not notified of background writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether writes should be performed in the background.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_write_behind(mut self, enabled: bool) -> Self {
+        self.flusher = enabled.then(|| self.new_flusher());
+        self
+    }
+
+    /// Sets how many values read with the `cached_*` methods and preloaded files are kept.
+    ///
+    /// Defaults to 256. When the cache is full, the least recently used value is evicted to
+    /// make room and further preloaded files are read again when requested. A capacity of `0`
+    /// disables caching. Values cached so far are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of values kept.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache = Arc::new(Cache::new(capacity));
+        self
+    }
+
+    /// Drops every cached value, preloaded file and resolved path.
+    ///
+    /// Values returned by the `cached_*` methods stay valid, the next call reads the file
+    /// again. Use this to release memory or after changing files behind the `Config`'s back
+    /// on a storage that doesn't report modification times.
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+        self.paths.clear();
+    }
+
+    /// Sets the storage files are read from and written to, the local filesystem by default.
+    ///
+    /// Writes still queued in write-behind mode are completed on the previous filesystem.
+    /// See [`Filesystem`] for the operations that always use the local filesystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `fs` - The filesystem backing the configuration path.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_filesystem(mut self, fs: impl Filesystem + 'static) -> Self {
+        self.fs = Arc::new(fs);
+        self.restart_flusher();
+        self.paths.clear();
+        self
+    }
+
+    /// Creates the background writer for write-behind mode.
+    fn new_flusher(&self) -> Flusher {
+        Flusher::new(
+            self.hooks.clone(),
+            self.fs.clone(),
+            self.write_strategy,
+            self.log_policy,
+        )
+    }
+
+    /// Replaces the background writer so it picks up the current settings, completing the
+    /// writes queued on the previous one.
+    fn restart_flusher(&mut self) {
+        if self.flusher.is_some() {
+            self.flusher = Some(self.new_flusher());
+        }
+    }
+
+    /// Waits until every write queued in write-behind mode reaches the filesystem.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or the first `Error` reported by a background write.
+    pub fn flush(&self) -> Result<(), Error> {
+        match &self.flusher {
+            Some(flusher) => flusher.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Registers a hook invoked before and after every read, write and removal.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, EventKind};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let mut config = Config::new("org.example.Demo", 1, None)?;
+    /// config.on_event(|event| {
+    ///     if event.kind == EventKind::AfterWrite {
+    ///         println!("{} changed", event.key);
+    ///     }
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - Function invoked with each event.
+    pub fn on_event<F>(&mut self, hook: F)
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        self.hooks.push(Arc::new(hook));
+    }
 
-        Ok(Self { path: config_path })
+    /// Appends a middleware to the chain applied to file contents.
+    ///
+    /// Middleware transforms serialized bytes before they are written and raw bytes before
+    /// they are decoded, enabling layers such as encryption or compression.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, Direction, EntryInfo, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut config = Config::new("org.example.Demo", 1, None)?;
+    /// config.add_middleware(|data: &mut Vec<u8>, direction: Direction, _: &EntryInfo| {
+    ///     if direction == Direction::Write {
+    ///         data.extend_from_slice(b"\n");
+    ///     }
+    ///     Ok::<_, Error>(())
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `middleware` - The transformation to add.
+    pub fn add_middleware<M>(&mut self, middleware: M)
+    where
+        M: Middleware + 'static,
+    {
+        self.middleware.push(Arc::new(middleware));
     }
 
     /// Determines if a plain file with the given key is present in the filesystem.
@@ -109,7 +786,12 @@ impl Config {
     ///
     /// `true` if the plain file exists, `false` otherwise.
     pub fn has_plain(&self, key: &str) -> bool {
-        self.path.join(key).exists()
+        if self.credential_path(key).is_some_and(|path| path.is_file()) {
+            return true;
+        }
+        self.resolve(key, FileType::Plain)
+            .map(|path| self.exists(&path))
+            .unwrap_or(false)
     }
 
     /// Determines if a toml file with the given key is present in the filesystem.
@@ -123,7 +805,9 @@ impl Config {
     /// `true` if the toml file exists, `false` otherwise.
     #[cfg(feature = "toml")]
     pub fn has_toml(&self, key: &str) -> bool {
-        self.path.join(format!("{key}.{}", FileType::Toml)).exists()
+        self.resolve(key, FileType::Toml)
+            .map(|path| self.exists(&path))
+            .unwrap_or(false)
     }
 
     /// Determines if a json file with the given key is present in the filesystem.
@@ -137,7 +821,9 @@ impl Config {
     /// `true` if the json file exists, `false` otherwise.
     #[cfg(feature = "json")]
     pub fn has_json(&self, key: &str) -> bool {
-        self.path.join(format!("{key}.{}", FileType::Json)).exists()
+        self.resolve(key, FileType::Json)
+            .map(|path| self.exists(&path))
+            .unwrap_or(false)
     }
 
     /// Determines if a ron file with the given key is present in the filesystem.
@@ -151,11 +837,65 @@ impl Config {
     /// `true` if the ron file exists, `false` otherwise.
     #[cfg(feature = "ron")]
     pub fn has_ron(&self, key: &str) -> bool {
-        self.path.join(format!("{key}.{}", FileType::Ron)).exists()
+        self.resolve(key, FileType::Ron)
+            .map(|path| self.exists(&path))
+            .unwrap_or(false)
+    }
+
+    /// Determines if a yaml file with the given key is present in the filesystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the yaml file exists, `false` otherwise.
+    #[cfg(feature = "yaml")]
+    pub fn has_yaml(&self, key: &str) -> bool {
+        self.resolve(key, FileType::Yaml)
+            .map(|path| self.exists(&path))
+            .unwrap_or(false)
+    }
+
+    /// Determines if an ini file with the given key is present in the filesystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the ini file exists, `false` otherwise.
+    #[cfg(feature = "ini")]
+    pub fn has_ini(&self, key: &str) -> bool {
+        self.resolve(key, FileType::Ini)
+            .map(|path| self.exists(&path))
+            .unwrap_or(false)
+    }
+
+    /// Determines if a json5 file with the given key is present in the filesystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the json5 file exists, `false` otherwise.
+    #[cfg(feature = "json5")]
+    pub fn has_json5(&self, key: &str) -> bool {
+        self.resolve(key, FileType::Json5)
+            .map(|path| self.exists(&path))
+            .unwrap_or(false)
     }
 
     /// Gets the content of a toml file with the given key and deserializes it into a type.
     ///
+    /// When a `.local.toml` file sits next to the file, such as `settings.local.toml` next to
+    /// `settings.toml`, its tables are merged on top. Local files hold machine-specific
+    /// settings that are not synced, writes never touch them.
+    ///
     /// # Arguments
     ///
     /// * `key` - The key used to store the file.
@@ -196,7 +936,7 @@ impl Config {
         self.get(key, FileType::Ron)
     }
 
-    /// Gets the content of a plain file with the given key.
+    /// Gets the content of a yaml file with the given key and deserializes it into a type.
     ///
     /// # Arguments
     ///
@@ -204,160 +944,1801 @@ impl Config {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the value or an `Error` if an error occurred.
-    pub fn get_plain(&self, key: &str) -> Result<String, Error> {
-        std::fs::read_to_string(self.path.join(key))
-            .map_err(|err| Error::GetKey(key.to_string(), err))
+    /// A `Result` containing the deserialized value or an `Error` if an error occurred.
+    #[cfg(feature = "yaml")]
+    pub fn get_yaml<T: DeserializeOwned>(&self, key: &str) -> Result<T, Error> {
+        self.get(key, FileType::Yaml)
     }
 
-    /// Sets the content of a toml file with the given key and serializes the value.
+    /// Gets the content of an ini file with the given key and deserializes it into a type.
     ///
     /// # Arguments
     ///
     /// * `key` - The key used to store the file.
-    /// * `value` - The value to be serialized and stored.
     ///
     /// # Returns
     ///
-    /// A `Result` indicating success or an `Error` if an error occurred.
+    /// A `Result` containing the deserialized value or an `Error` if an error occurred.
+    #[cfg(feature = "ini")]
+    pub fn get_ini<T: DeserializeOwned>(&self, key: &str) -> Result<T, Error> {
+        self.get(key, FileType::Ini)
+    }
+
+    /// Gets the content of a json5 file with the given key and deserializes it into a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value or an `Error` if an error occurred.
+    #[cfg(feature = "json5")]
+    pub fn get_json5<T: DeserializeOwned>(&self, key: &str) -> Result<T, Error> {
+        self.get(key, FileType::Json5)
+    }
+
+    /// Gets the content of a toml file with the given key, if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value, `None` if the file doesn't exist, or an
+    /// `Error` if the file could not be read or parsed.
     #[cfg(feature = "toml")]
-    pub fn set_toml<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
-        self.set(key, FileType::Toml, value)
+    pub fn get_toml_opt<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get(key, FileType::Toml).ignore_missing()
     }
 
-    /// Sets the content of a json file with the given key and serializes the value.
+    /// Gets the content of a json file with the given key, if it exists.
     ///
     /// # Arguments
     ///
     /// * `key` - The key used to store the file.
-    /// * `value` - The value to be serialized and stored.
     ///
     /// # Returns
     ///
-    /// A `Result` indicating success or an `Error` if an error occurred.
+    /// A `Result` containing the deserialized value, `None` if the file doesn't exist, or an
+    /// `Error` if the file could not be read or parsed.
     #[cfg(feature = "json")]
-    pub fn set_json<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
-        self.set(key, FileType::Json, value)
+    pub fn get_json_opt<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get(key, FileType::Json).ignore_missing()
     }
 
-    /// Sets the content of a ron file with the given key and serializes the value.
+    /// Gets the content of a ron file with the given key, if it exists.
     ///
     /// # Arguments
     ///
     /// * `key` - The key used to store the file.
-    /// * `value` - The value to be serialized and stored.
     ///
     /// # Returns
     ///
-    /// A `Result` indicating success or an `Error` if an error occurred.
+    /// A `Result` containing the deserialized value, `None` if the file doesn't exist, or an
+    /// `Error` if the file could not be read or parsed.
     #[cfg(feature = "ron")]
-    pub fn set_ron<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
-        self.set(key, FileType::Ron, value)
+    pub fn get_ron_opt<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get(key, FileType::Ron).ignore_missing()
     }
 
-    /// Sets the content of a plain file with the given key.
+    /// Gets the content of a yaml file with the given key, if it exists.
     ///
     /// # Arguments
     ///
     /// * `key` - The key used to store the file.
-    /// * `value` - String to write.
     ///
     /// # Returns
     ///
-    /// A `Result` indicating success or an `Error` if an error occurred.
-    pub fn set_plain(&self, key: &str, value: impl ToString) -> Result<(), Error> {
-        let key_path = self.path.join(key);
-        atomicwrites::AtomicFile::new(&key_path, atomicwrites::OverwriteBehavior::AllowOverwrite)
-            .write(|file| file.write_all(value.to_string().as_bytes()))?;
-        Ok(())
+    /// A `Result` containing the deserialized value, `None` if the file doesn't exist, or an
+    /// `Error` if the file could not be read or parsed.
+    #[cfg(feature = "yaml")]
+    pub fn get_yaml_opt<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get(key, FileType::Yaml).ignore_missing()
     }
 
-    /// Given a key, returns the file path in the filesystem.
+    /// Gets the content of an ini file with the given key, if it exists.
     ///
     /// # Arguments
     ///
     /// * `key` - The key used to store the file.
-    /// * `file_type` - The file extension.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the file path or an `Error` if an error occurred.
-    pub fn path(&self, key: &str, file_type: FileType) -> Result<PathBuf, Error> {
-        let name = if FileType::Plain == file_type {
-            key.to_string()
-        } else {
-            format!("{key}.{file_type}")
-        };
-        let path = self.path.join(sanitize_name(&name)?);
-        info!("Found key {}.", key);
-        Ok(path)
+    /// A `Result` containing the deserialized value, `None` if the file doesn't exist, or an
+    /// `Error` if the file could not be read or parsed.
+    #[cfg(feature = "ini")]
+    pub fn get_ini_opt<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get(key, FileType::Ini).ignore_missing()
     }
 
-    /// Removes all files in the configuration path.
+    /// Gets the content of a json5 file with the given key, if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the file path or an `Error` if an error occurred.
-    pub fn clean(&self) -> Result<(), Error> {
-        std::fs::remove_dir_all(&self.path.parent().unwrap()).map_err(|err| Error::Io(err))
+    /// A `Result` containing the deserialized value, `None` if the file doesn't exist, or an
+    /// `Error` if the file could not be read or parsed.
+    #[cfg(feature = "json5")]
+    pub fn get_json5_opt<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get(key, FileType::Json5).ignore_missing()
     }
-}
 
-impl Get for Config {
-    /// Given a key, obtains the file and parses it into a type.
+    /// Gets the content of a plain file with the given key, if it exists.
     ///
     /// # Arguments
     ///
     /// * `key` - The key used to store the file.
-    /// * `file_type` - The file extension.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the deserialized value or an `Error` if an error occurred.
-    fn get<T: DeserializeOwned>(&self, key: &str, file_type: FileType) -> Result<T, Error> {
-        let key_path = self.path(key, file_type)?;
-        let data = std::fs::read_to_string(&key_path)
-            .map_err(|err| Error::GetKey(key.to_string(), err))?;
-
-        let t = match file_type {
-            #[cfg(feature = "toml")]
-            FileType::Toml => toml::from_str(&data)?,
-            #[cfg(feature = "json")]
-            FileType::Json => serde_json::from_str(&data)?,
-            #[cfg(feature = "ron")]
-            FileType::Ron => ron::from_str(&data)?,
-            FileType::Plain => unreachable!("Never get plain text with get method."),
-        };
-        info!("Retrieved file from {}.", key_path.display());
-        Ok(t)
+    /// A `Result` containing the value, `None` if the file doesn't exist, or an `Error` if the
+    /// file could not be read.
+    pub fn get_plain_opt(&self, key: &str) -> Result<Option<String>, Error> {
+        self.get_plain(key).ignore_missing()
     }
-}
 
-impl Set for Config {
-    /// Given a key, saves the serialized value to the file.
+    /// Gets the content of a toml file with the given key, along with its version token.
     ///
     /// # Arguments
     ///
     /// * `key` - The key used to store the file.
-    /// * `file_type` - The file extension.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the deserialized value or an `Error` if an error occurred.
-    fn set<T: Serialize>(&self, key: &str, file_type: FileType, value: T) -> Result<(), Error> {
-        let key_path = self.path(key, file_type)?;
-        let data = match file_type {
-            #[cfg(feature = "toml")]
-            FileType::Toml => toml::to_string_pretty(&value)?,
-            #[cfg(feature = "json")]
-            FileType::Json => serde_json::to_string_pretty(&value)?,
-            #[cfg(feature = "ron")]
-            FileType::Ron => ron::ser::to_string_pretty(&value, ron::ser::PrettyConfig::new())?,
-            FileType::Plain => unreachable!("Never get plain text with get method."),
-        };
-        atomicwrites::AtomicFile::new(&key_path, atomicwrites::OverwriteBehavior::AllowOverwrite)
-            .write(|file| file.write_all(data.as_bytes()))?;
-        info!("File written to {}.", key_path.display());
-        Ok(())
+    /// A `Result` containing the deserialized value and its `Token` or an `Error` if an error
+    /// occurred.
+    #[cfg(feature = "toml")]
+    pub fn get_toml_versioned<T: DeserializeOwned>(&self, key: &str) -> Result<(T, Token), Error> {
+        self.get_versioned(key, FileType::Toml)
     }
+
+    /// Gets the content of a json file with the given key, along with its version token.
+    ///
+    /// Pass the token to [`Config::set_json_cas`] to write the file back only if it hasn't
+    /// changed since it was read.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, Error};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let (mut count, token) = config.get_json_versioned::<u64>("count")?;
+    /// count += 1;
+    /// match config.set_json_cas("count", count, &token) {
+    ///     Err(Error::Conflict { .. }) => println!("someone else updated the count"),
+    ///     result => result?,
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value and its `Token` or an `Error` if an error
+    /// occurred.
+    #[cfg(feature = "json")]
+    pub fn get_json_versioned<T: DeserializeOwned>(&self, key: &str) -> Result<(T, Token), Error> {
+        self.get_versioned(key, FileType::Json)
+    }
+
+    /// Gets the content of a ron file with the given key, along with its version token.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value and its `Token` or an `Error` if an error
+    /// occurred.
+    #[cfg(feature = "ron")]
+    pub fn get_ron_versioned<T: DeserializeOwned>(&self, key: &str) -> Result<(T, Token), Error> {
+        self.get_versioned(key, FileType::Ron)
+    }
+
+    /// Gets the content of a yaml file with the given key, along with its version token.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value and its `Token` or an `Error` if an error
+    /// occurred.
+    #[cfg(feature = "yaml")]
+    pub fn get_yaml_versioned<T: DeserializeOwned>(&self, key: &str) -> Result<(T, Token), Error> {
+        self.get_versioned(key, FileType::Yaml)
+    }
+
+    /// Gets the content of an ini file with the given key, along with its version token.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value and its `Token` or an `Error` if an error
+    /// occurred.
+    #[cfg(feature = "ini")]
+    pub fn get_ini_versioned<T: DeserializeOwned>(&self, key: &str) -> Result<(T, Token), Error> {
+        self.get_versioned(key, FileType::Ini)
+    }
+
+    /// Gets the content of a json5 file with the given key, along with its version token.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value and its `Token` or an `Error` if an error
+    /// occurred.
+    #[cfg(feature = "json5")]
+    pub fn get_json5_versioned<T: DeserializeOwned>(&self, key: &str) -> Result<(T, Token), Error> {
+        self.get_versioned(key, FileType::Json5)
+    }
+
+    /// Gets a shared, cached instance of a toml file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the shared value or an `Error` if an error occurred.
+    #[cfg(feature = "toml")]
+    pub fn cached_toml<T>(&self, key: &str) -> Result<Arc<T>, Error>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        self.cached(key, FileType::Toml)
+    }
+
+    /// Gets a shared, cached instance of a json file with the given key.
+    ///
+    /// Every caller receives the same deserialized value until the file is written through
+    /// this `Config` or changes on disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the shared value or an `Error` if an error occurred.
+    #[cfg(feature = "json")]
+    pub fn cached_json<T>(&self, key: &str) -> Result<Arc<T>, Error>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        self.cached(key, FileType::Json)
+    }
+
+    /// Gets a shared, cached instance of a ron file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the shared value or an `Error` if an error occurred.
+    #[cfg(feature = "ron")]
+    pub fn cached_ron<T>(&self, key: &str) -> Result<Arc<T>, Error>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        self.cached(key, FileType::Ron)
+    }
+
+    /// Gets a shared, cached instance of a yaml file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the shared value or an `Error` if an error occurred.
+    #[cfg(feature = "yaml")]
+    pub fn cached_yaml<T>(&self, key: &str) -> Result<Arc<T>, Error>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        self.cached(key, FileType::Yaml)
+    }
+
+    /// Gets a shared, cached instance of an ini file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the shared value or an `Error` if an error occurred.
+    #[cfg(feature = "ini")]
+    pub fn cached_ini<T>(&self, key: &str) -> Result<Arc<T>, Error>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        self.cached(key, FileType::Ini)
+    }
+
+    /// Gets a shared, cached instance of a json5 file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the shared value or an `Error` if an error occurred.
+    #[cfg(feature = "json5")]
+    pub fn cached_json5<T>(&self, key: &str) -> Result<Arc<T>, Error>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        self.cached(key, FileType::Json5)
+    }
+
+    /// Creates a lazily loaded value for a toml file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Lazy` that reads the file on first access.
+    #[cfg(feature = "toml")]
+    pub fn lazy_toml<T: DeserializeOwned>(&self, key: &str) -> Lazy<'_, T> {
+        Lazy::new(self, key, FileType::Toml)
+    }
+
+    /// Creates a lazily loaded value for a json file with the given key.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, Lazy};
+    /// # use serde::Deserialize;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// #[derive(Deserialize)]
+    /// struct Theme { accent: String }
+    ///
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let theme: Lazy<Theme> = config.lazy_json("theme");
+    /// println!("{}", theme.get()?.accent);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Lazy` that reads the file on first access.
+    #[cfg(feature = "json")]
+    pub fn lazy_json<T: DeserializeOwned>(&self, key: &str) -> Lazy<'_, T> {
+        Lazy::new(self, key, FileType::Json)
+    }
+
+    /// Creates a lazily loaded value for a ron file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Lazy` that reads the file on first access.
+    #[cfg(feature = "ron")]
+    pub fn lazy_ron<T: DeserializeOwned>(&self, key: &str) -> Lazy<'_, T> {
+        Lazy::new(self, key, FileType::Ron)
+    }
+
+    /// Creates a lazily loaded value for a yaml file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Lazy` that reads the file on first access.
+    #[cfg(feature = "yaml")]
+    pub fn lazy_yaml<T: DeserializeOwned>(&self, key: &str) -> Lazy<'_, T> {
+        Lazy::new(self, key, FileType::Yaml)
+    }
+
+    /// Creates a lazily loaded value for an ini file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Lazy` that reads the file on first access.
+    #[cfg(feature = "ini")]
+    pub fn lazy_ini<T: DeserializeOwned>(&self, key: &str) -> Lazy<'_, T> {
+        Lazy::new(self, key, FileType::Ini)
+    }
+
+    /// Creates a lazily loaded value for a json5 file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Lazy` that reads the file on first access.
+    #[cfg(feature = "json5")]
+    pub fn lazy_json5<T: DeserializeOwned>(&self, key: &str) -> Lazy<'_, T> {
+        Lazy::new(self, key, FileType::Json5)
+    }
+
+    /// Gets the content of several toml files and deserializes them into a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys used to store the files.
+    ///
+    /// # Returns
+    ///
+    /// A map from each key to its deserialized value or the `Error` that occurred.
+    #[cfg(feature = "toml")]
+    pub fn get_many_toml<T>(&self, keys: &[&str]) -> HashMap<String, Result<T, Error>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        self.get_many(keys, FileType::Toml)
+    }
+
+    /// Gets the content of several json files and deserializes them into a type.
+    ///
+    /// With the `rayon` feature enabled the files are loaded in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys used to store the files.
+    ///
+    /// # Returns
+    ///
+    /// A map from each key to its deserialized value or the `Error` that occurred.
+    #[cfg(feature = "json")]
+    pub fn get_many_json<T>(&self, keys: &[&str]) -> HashMap<String, Result<T, Error>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        self.get_many(keys, FileType::Json)
+    }
+
+    /// Gets the content of several ron files and deserializes them into a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys used to store the files.
+    ///
+    /// # Returns
+    ///
+    /// A map from each key to its deserialized value or the `Error` that occurred.
+    #[cfg(feature = "ron")]
+    pub fn get_many_ron<T>(&self, keys: &[&str]) -> HashMap<String, Result<T, Error>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        self.get_many(keys, FileType::Ron)
+    }
+
+    /// Gets the content of several yaml files and deserializes them into a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys used to store the files.
+    ///
+    /// # Returns
+    ///
+    /// A map from each key to its deserialized value or the `Error` that occurred.
+    #[cfg(feature = "yaml")]
+    pub fn get_many_yaml<T>(&self, keys: &[&str]) -> HashMap<String, Result<T, Error>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        self.get_many(keys, FileType::Yaml)
+    }
+
+    /// Gets the content of several ini files and deserializes them into a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys used to store the files.
+    ///
+    /// # Returns
+    ///
+    /// A map from each key to its deserialized value or the `Error` that occurred.
+    #[cfg(feature = "ini")]
+    pub fn get_many_ini<T>(&self, keys: &[&str]) -> HashMap<String, Result<T, Error>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        self.get_many(keys, FileType::Ini)
+    }
+
+    /// Gets the content of several json5 files and deserializes them into a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys used to store the files.
+    ///
+    /// # Returns
+    ///
+    /// A map from each key to its deserialized value or the `Error` that occurred.
+    #[cfg(feature = "json5")]
+    pub fn get_many_json5<T>(&self, keys: &[&str]) -> HashMap<String, Result<T, Error>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        self.get_many(keys, FileType::Json5)
+    }
+
+    /// Gets the content of a plain file with the given key.
+    ///
+    /// A credential with the same name provided by the service manager takes precedence over
+    /// the stored file, see [`Config::with_credentials_directory`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value or an `Error` if an error occurred.
+    pub fn get_plain(&self, key: &str) -> Result<String, Error> {
+        if let Some(mut value) = self.credential(key)? {
+            return Ok(std::mem::take(&mut *value));
+        }
+        let key_path = self.resolve(key, FileType::Plain)?;
+        self.read_file(key, FileType::Plain, &key_path)
+    }
+
+    /// Sets the content of a toml file with the given key and serializes the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "toml")]
+    pub fn set_toml<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set(key, FileType::Toml, value)
+    }
+
+    /// Sets the content of a json file with the given key and serializes the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "json")]
+    pub fn set_json<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set(key, FileType::Json, value)
+    }
+
+    /// Sets the content of a ron file with the given key and serializes the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "ron")]
+    pub fn set_ron<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set(key, FileType::Ron, value)
+    }
+
+    /// Sets the content of a yaml file with the given key and serializes the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "yaml")]
+    pub fn set_yaml<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set(key, FileType::Yaml, value)
+    }
+
+    /// Sets the content of an ini file with the given key and serializes the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "ini")]
+    pub fn set_ini<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set(key, FileType::Ini, value)
+    }
+
+    /// Sets the content of a json5 file with the given key and serializes the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "json5")]
+    pub fn set_json5<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set(key, FileType::Json5, value)
+    }
+
+    /// Sets the content of a plain file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - String to write.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn set_plain(&self, key: &str, value: impl ToString) -> Result<(), Error> {
+        let key_path = self.path(key, FileType::Plain)?;
+        self.write_file(
+            key,
+            FileType::Plain,
+            &key_path,
+            value.to_string().as_bytes(),
+            self.overwrite,
+        )
+    }
+
+    /// Creates a plain file with the given key, failing if it already exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - String to write.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::AlreadyExists` if the file is present, or another
+    /// `Error` if an error occurred.
+    pub fn set_plain_new(&self, key: &str, value: impl ToString) -> Result<(), Error> {
+        let key_path = self.path(key, FileType::Plain)?;
+        self.write_file(
+            key,
+            FileType::Plain,
+            &key_path,
+            value.to_string().as_bytes(),
+            OverwriteBehavior::DisallowOverwrite,
+        )
+    }
+
+    /// Creates a toml file with the given key, failing if it already exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::AlreadyExists` if the file is present, or another
+    /// `Error` if an error occurred.
+    #[cfg(feature = "toml")]
+    pub fn set_toml_new<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set_new(key, FileType::Toml, value)
+    }
+
+    /// Creates a json file with the given key, failing if it already exists.
+    ///
+    /// The check and the write happen atomically, so when several processes race to
+    /// initialize the same key exactly one of them succeeds.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::AlreadyExists` if the file is present, or another
+    /// `Error` if an error occurred.
+    #[cfg(feature = "json")]
+    pub fn set_json_new<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set_new(key, FileType::Json, value)
+    }
+
+    /// Creates a ron file with the given key, failing if it already exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::AlreadyExists` if the file is present, or another
+    /// `Error` if an error occurred.
+    #[cfg(feature = "ron")]
+    pub fn set_ron_new<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set_new(key, FileType::Ron, value)
+    }
+
+    /// Creates a yaml file with the given key, failing if it already exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::AlreadyExists` if the file is present, or another
+    /// `Error` if an error occurred.
+    #[cfg(feature = "yaml")]
+    pub fn set_yaml_new<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set_new(key, FileType::Yaml, value)
+    }
+
+    /// Creates an ini file with the given key, failing if it already exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::AlreadyExists` if the file is present, or another
+    /// `Error` if an error occurred.
+    #[cfg(feature = "ini")]
+    pub fn set_ini_new<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set_new(key, FileType::Ini, value)
+    }
+
+    /// Creates a json5 file with the given key, failing if it already exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::AlreadyExists` if the file is present, or another
+    /// `Error` if an error occurred.
+    #[cfg(feature = "json5")]
+    pub fn set_json5_new<T: Serialize>(&self, key: &str, value: T) -> Result<(), Error> {
+        self.set_new(key, FileType::Json5, value)
+    }
+
+    /// Serializes a value into a new file, failing if it already exists.
+    fn set_new<T: Serialize>(&self, key: &str, file_type: FileType, value: T) -> Result<(), Error> {
+        let key_path = self.path(key, file_type)?;
+        let data = self.serialize_value(key, file_type, &key_path, &value)?;
+        self.write_file(
+            key,
+            file_type,
+            &key_path,
+            data.as_bytes(),
+            OverwriteBehavior::DisallowOverwrite,
+        )
+    }
+
+    /// Saves a toml file with the given key if it hasn't changed since `token` was issued.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `token` - The token returned when the file was read.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::Conflict` if the file changed or was removed, or
+    /// another `Error` if an error occurred.
+    #[cfg(feature = "toml")]
+    pub fn set_toml_cas<T: Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        token: &Token,
+    ) -> Result<(), Error> {
+        self.set_cas(key, FileType::Toml, value, token)
+    }
+
+    /// Saves a json file with the given key if it hasn't changed since `token` was issued.
+    ///
+    /// The [write guard](Config::write_guard) of the key is held while the file is compared
+    /// and written, so of two writers holding the same token only the first one succeeds,
+    /// in this or any other process. Writes that don't take the guard, such as
+    /// [`Config::set_json`], are still detected as long as they happen before the comparison.
+    /// Don't call this while holding the write guard of the key yourself, it would wait for
+    /// it forever.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `token` - The token returned when the file was read.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::Conflict` if the file changed or was removed, or
+    /// another `Error` if an error occurred.
+    #[cfg(feature = "json")]
+    pub fn set_json_cas<T: Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        token: &Token,
+    ) -> Result<(), Error> {
+        self.set_cas(key, FileType::Json, value, token)
+    }
+
+    /// Saves a ron file with the given key if it hasn't changed since `token` was issued.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `token` - The token returned when the file was read.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::Conflict` if the file changed or was removed, or
+    /// another `Error` if an error occurred.
+    #[cfg(feature = "ron")]
+    pub fn set_ron_cas<T: Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        token: &Token,
+    ) -> Result<(), Error> {
+        self.set_cas(key, FileType::Ron, value, token)
+    }
+
+    /// Saves a yaml file with the given key if it hasn't changed since `token` was issued.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `token` - The token returned when the file was read.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::Conflict` if the file changed or was removed, or
+    /// another `Error` if an error occurred.
+    #[cfg(feature = "yaml")]
+    pub fn set_yaml_cas<T: Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        token: &Token,
+    ) -> Result<(), Error> {
+        self.set_cas(key, FileType::Yaml, value, token)
+    }
+
+    /// Saves an ini file with the given key if it hasn't changed since `token` was issued.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `token` - The token returned when the file was read.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::Conflict` if the file changed or was removed, or
+    /// another `Error` if an error occurred.
+    #[cfg(feature = "ini")]
+    pub fn set_ini_cas<T: Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        token: &Token,
+    ) -> Result<(), Error> {
+        self.set_cas(key, FileType::Ini, value, token)
+    }
+
+    /// Saves a json5 file with the given key if it hasn't changed since `token` was issued.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `token` - The token returned when the file was read.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::Conflict` if the file changed or was removed, or
+    /// another `Error` if an error occurred.
+    #[cfg(feature = "json5")]
+    pub fn set_json5_cas<T: Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        token: &Token,
+    ) -> Result<(), Error> {
+        self.set_cas(key, FileType::Json5, value, token)
+    }
+
+    /// Reads which release wrote a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the header, `None` if the file was written without one, or an
+    /// `Error` if the file could not be read.
+    pub fn written_by(&self, key: &str, file_type: FileType) -> Result<Option<WrittenBy>, Error> {
+        let key_path = self.resolve(key, file_type)?;
+        let data = self.read_file(key, file_type, &key_path)?;
+        Ok(header::parse(&data, file_type))
+    }
+
+    /// Reads files into the internal cache on a background thread.
+    ///
+    /// The next read of each preloaded key is served from memory as long as the file did not
+    /// change, later reads go to the storage again. Files are kept as stored, before any
+    /// middleware runs, and only up to the capacity set with [`Config::with_cache_capacity`].
+    /// The returned handle can be waited on, polled as a future or ignored.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let preload = config.preload(&[("colors", FileType::Json), ("fonts", FileType::Json)])?;
+    /// // ... build the user interface ...
+    /// preload.wait()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The key and format of each file to preload.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Preload` handle or an `Error` if a key is invalid.
+    pub fn preload(&self, keys: &[(&str, FileType)]) -> Result<Preload, Error> {
+        let paths = keys
+            .iter()
+            .map(|(key, file_type)| self.resolve(key, *file_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Preload::spawn(
+            self.cache.clone(),
+            self.fs.clone(),
+            paths,
+            self.log_policy,
+        ))
+    }
+
+    /// Starts a batch of writes committed together.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType};
+    /// # use serde_json::json;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let mut batch = config.batch();
+    /// batch
+    ///     .set("colors", FileType::Json, &json!({ "accent": "#7a7af9" }))?
+    ///     .set("fonts", FileType::Json, &json!({ "size": 12 }))?;
+    /// batch.commit()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// An empty `Batch` bound to this `Config`.
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new(self)
+    }
+
+    /// Serializes and writes several values at once.
+    ///
+    /// Every value is serialized and written to a temporary file before any file is replaced,
+    /// so a failure leaves the previous state intact. Non-atomic write strategies replace the
+    /// files in turn instead, see [`Batch::commit`].
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - The key, format and value of each file, plain files are not supported.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn set_many<T: Serialize>(&self, entries: &[(&str, FileType, T)]) -> Result<(), Error> {
+        let mut batch = self.batch();
+        for (key, file_type, value) in entries {
+            batch.set(key, *file_type, value)?;
+        }
+        batch.commit()
+    }
+
+    /// Removes the file with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn remove(&self, key: &str, file_type: FileType) -> Result<(), Error> {
+        self.check_sealed()?;
+        let key_path = self.path(key, file_type)?;
+        if let Some(flusher) = &self.flusher {
+            flusher.cancel(&key_path);
+        }
+        self.cache.invalidate(&key_path);
+        self.fs
+            .remove(&key_path)
+            .map_err(|err| Error::for_key(key, file_type, &key_path, err))?;
+        self.emit(EventKind::Remove, key, file_type, &key_path);
+        self.log("removed file", key, &key_path);
+        Ok(())
+    }
+
+    /// Given a key, returns the file path in the filesystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the file path or an `Error` if an error occurred.
+    pub fn path(&self, key: &str, file_type: FileType) -> Result<PathBuf, Error> {
+        let path = match self.paths.get(key, file_type) {
+            Some(path) => path,
+            None => {
+                let normalized = self.normalized_key(key);
+                let name = if FileType::Plain == file_type {
+                    normalized.to_string()
+                } else {
+                    format!("{normalized}.{file_type}")
+                };
+                let path = self.path.join(self.validator.validate(&name)?);
+                self.paths.insert(key, file_type, path.clone());
+                path
+            }
+        };
+        let path = if self.case_insensitive_keys {
+            find_case_insensitive(self.fs.as_ref(), path)
+        } else {
+            path
+        };
+        self.log("found key", key, &path);
+        Ok(path)
+    }
+
+    /// Returns a key with the normalization enabled on this `Config` applied, as its file is named.
+    pub(crate) fn normalized_key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        normalize_key(key, self.normalize_keys, self.case_insensitive_keys)
+    }
+
+    /// Returns the path a key is read from, checking fallback scopes when it isn't present.
+    ///
+    /// Falls back to the path in the current scope if no scope contains the key.
+    fn resolve(&self, key: &str, file_type: FileType) -> Result<PathBuf, Error> {
+        let path = self.path(key, file_type)?;
+        if self.fallbacks.is_empty() || self.exists(&path) {
+            return Ok(path);
+        }
+        let Ok(relative) = path.strip_prefix(&self.path) else {
+            return Ok(path);
+        };
+        let fallback = self
+            .fallbacks
+            .iter()
+            .map(|dir| dir.join(relative))
+            .map(|path| {
+                if self.case_insensitive_keys {
+                    find_case_insensitive(self.fs.as_ref(), path)
+                } else {
+                    path
+                }
+            })
+            .find(|path| self.exists(path));
+        Ok(fallback.unwrap_or(path))
+    }
+
+    /// Lists the files stored in the configuration path.
+    ///
+    /// The whole directory is listed before the first entry is returned, the metadata of each
+    /// entry is then read as the iterator advances.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an iterator over the entries or an `Error` if an error occurred.
+    pub fn entries(&self) -> Result<Entries, Error> {
+        Entries::new(self.fs.clone(), &self.path, self.scope.clone())
+    }
+
+    /// Lists the files stored in the version root and all of its scopes.
+    ///
+    /// Every entry is tagged with the scope it was found in, `None` for the version root.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an iterator over the entries or an `Error` if an error occurred.
+    pub fn walk(&self) -> Result<Walk, Error> {
+        Walk::new(self.fs.clone(), &self.root)
+    }
+
+    /// Lists the keys stored in the configuration path.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the keys or an `Error` if an error occurred.
+    pub fn keys(&self) -> Result<Vec<String>, Error> {
+        let paths = self
+            .fs
+            .list(&self.path)
+            .map_err(|err| Error::io(&self.path, err))?;
+        Ok(paths
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str())
+            .filter(|name| !is_temporary(Path::new(name)))
+            .map(|name| split_name(name).0)
+            .collect())
+    }
+
+    /// Lists the entries matching a predicate.
+    ///
+    /// Like [`Config::entries`], the whole directory is listed up front, only the metadata of
+    /// the entries is read lazily.
+    ///
+    /// Combine with [`Iterator::skip`] and [`Iterator::take`] to paginate the results:
+    /// ```no_run
+    /// # use libset::{Config, FileType};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let large_json = config
+    ///     .keys_where(|entry| entry.file_type == FileType::Json && entry.len > 1024)?
+    ///     .skip(100)
+    ///     .take(50)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - The filter applied to each entry.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an iterator over the matching entries or an `Error` if an error occurred.
+    pub fn keys_where<F>(
+        &self,
+        mut predicate: F,
+    ) -> Result<impl Iterator<Item = Result<Entry, Error>>, Error>
+    where
+        F: FnMut(&Entry) -> bool,
+    {
+        Ok(self
+            .entries()?
+            .filter(move |entry| entry.as_ref().map_or(true, &mut predicate)))
+    }
+
+    /// Lists a page of the entries matching a predicate.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - The filter applied to each entry.
+    /// * `offset` - The number of matching entries to skip.
+    /// * `limit` - The maximum number of entries to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the entries of the page or an `Error` if an error occurred.
+    pub fn keys_page<F>(
+        &self,
+        predicate: F,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Entry>, Error>
+    where
+        F: FnMut(&Entry) -> bool,
+    {
+        self.keys_where(predicate)?
+            .skip(offset)
+            .take(limit)
+            .collect()
+    }
+
+    /// Loads several files of the same format, in parallel when the `rayon` feature is enabled.
+    fn get_many<T>(&self, keys: &[&str], file_type: FileType) -> HashMap<String, Result<T, Error>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            keys.par_iter()
+                .map(|key| (key.to_string(), self.get(key, file_type)))
+                .collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            keys.iter()
+                .map(|key| (key.to_string(), self.get(key, file_type)))
+                .collect()
+        }
+    }
+
+    /// Reads a file through the shared cache.
+    fn cached<T>(&self, key: &str, file_type: FileType) -> Result<Arc<T>, Error>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let key_path = self.resolve(key, file_type)?;
+        if let Some(value) = self.cache.get::<T>(self.fs.as_ref(), &key_path) {
+            return Ok(value);
+        }
+        let stamp = Stamp::of(self.fs.as_ref(), &key_path);
+        let value = Arc::new(self.get::<T>(key, file_type)?);
+        self.cache.insert(&key_path, stamp, value.clone());
+        Ok(value)
+    }
+
+    /// Reads a file along with a token identifying its current version.
+    fn get_versioned<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        file_type: FileType,
+    ) -> Result<(T, Token), Error> {
+        let key_path = self.resolve(key, file_type)?;
+        self.emit(EventKind::BeforeRead, key, file_type, &key_path);
+        let (bytes, pending) = self.stored(key, file_type, &key_path)?;
+        let token = Token::new(self.fs.as_ref(), &key_path, &bytes, pending);
+        let data = self.decode_file(key, file_type, &key_path, bytes)?;
+        let value = deserialize(key, file_type, &key_path, &data, &self.parse)?;
+        Ok((value, token))
+    }
+
+    /// Writes a file only if its current version matches `token`, holding the write guard of
+    /// the key from the comparison until the file is written.
+    fn set_cas<T: Serialize>(
+        &self,
+        key: &str,
+        file_type: FileType,
+        value: T,
+        token: &Token,
+    ) -> Result<(), Error> {
+        let key_path = self.path(key, file_type)?;
+        let _guard = self.write_guard(key)?;
+        let current_path = self.resolve(key, file_type)?;
+        let current = match self.stored(key, file_type, &current_path) {
+            Ok((bytes, pending)) => {
+                Some(Token::new(self.fs.as_ref(), &current_path, &bytes, pending))
+            }
+            Err(err) if err.is_not_found() => None,
+            Err(err) => return Err(err),
+        };
+        if !current.is_some_and(|current| current.matches(token)) {
+            return Err(Error::Conflict {
+                key: key.to_string(),
+                file_type,
+                path: key_path,
+            });
+        }
+        let data = self.serialize_value(key, file_type, &key_path, &value)?;
+        self.write_file(
+            key,
+            file_type,
+            &key_path,
+            data.as_bytes(),
+            OverwriteBehavior::AllowOverwrite,
+        )
+    }
+
+    /// Determines if a file is present, including writes still queued in write-behind mode.
+    fn exists(&self, path: &Path) -> bool {
+        self.fs.exists(path)
+            || self
+                .flusher
+                .as_ref()
+                .is_some_and(|flusher| flusher.pending(path).is_some())
+    }
+
+    /// Invokes the registered hooks with an event.
+    fn emit(&self, kind: EventKind, key: &str, file_type: FileType, path: &Path) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let event = Event {
+            kind,
+            key,
+            file_type,
+            path,
+        };
+        for hook in &self.hooks {
+            hook(&event);
+        }
+    }
+
+    /// Reads and decodes the contents of a file.
+    fn read_file(&self, key: &str, file_type: FileType, path: &Path) -> Result<String, Error> {
+        self.emit(EventKind::BeforeRead, key, file_type, path);
+        let (bytes, _) = self.stored(key, file_type, path)?;
+        self.decode_file(key, file_type, path, bytes)
+    }
+
+    /// Reads the stored contents of a file, preferring pending and preloaded data.
+    ///
+    /// The returned flag is `true` if the contents haven't been written to disk yet.
+    fn stored(
+        &self,
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+    ) -> Result<(Vec<u8>, bool), Error> {
+        if let Some(data) = self
+            .flusher
+            .as_ref()
+            .and_then(|flusher| flusher.pending(path))
+        {
+            return Ok((data.to_vec(), true));
+        }
+        let data = match self.cache.take_raw(self.fs.as_ref(), path) {
+            Some(data) => data,
+            None => self
+                .fs
+                .read(path)
+                .map_err(|err| Error::for_key(key, file_type, path, err))?,
+        };
+        Ok((data, false))
+    }
+
+    /// Runs the middleware chain over stored data and decodes it into text.
+    fn decode_file(
+        &self,
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+        mut bytes: Vec<u8>,
+    ) -> Result<String, Error> {
+        let normalized = self.normalized_key(key);
+        let entry = EntryInfo {
+            key: &normalized,
+            file_type,
+            path,
+        };
+        #[cfg(feature = "encryption")]
+        self.check_encrypted(&normalized, &bytes, Direction::Read)?;
+        middleware::run(&self.middleware, &mut bytes, Direction::Read, &entry)?;
+        let data = decode(key, path, bytes)?;
+        self.emit(EventKind::AfterRead, key, file_type, path);
+        Ok(data)
+    }
+
+    /// Serializes a value, adding the file header when enabled.
+    pub(crate) fn serialize_value<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+        value: &T,
+    ) -> Result<String, Error> {
+        #[cfg(feature = "json")]
+        if let Some(version) = self.schema_version(key) {
+            let document = migrate::stamp(key, file_type, path, value, version)?;
+            return self.serialize_stamped(key, file_type, path, &document);
+        }
+        self.serialize_stamped(key, file_type, path, value)
+    }
+
+    /// Serializes a value as is, adding the file header when enabled.
+    fn serialize_stamped<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+        value: &T,
+    ) -> Result<String, Error> {
+        let data = match file_type {
+            #[cfg(feature = "toml")]
+            FileType::Toml => limits::serialize(key, path, value, self.toml_stringified_keys)?,
+            _ => serialize(key, file_type, path, value)?,
+        };
+        Ok(match &self.written_by {
+            Some(written_by) => header::stamp(data, file_type, written_by),
+            None => data,
+        })
+    }
+
+    /// Runs the middleware chain over data about to be written.
+    fn encode(
+        &self,
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut data = data.to_vec();
+        let normalized = self.normalized_key(key);
+        let entry = EntryInfo {
+            key: &normalized,
+            file_type,
+            path,
+        };
+        middleware::run(&self.middleware, &mut data, Direction::Write, &entry)?;
+        #[cfg(feature = "encryption")]
+        self.check_encrypted(&normalized, &data, Direction::Write)?;
+        Ok(data)
+    }
+
+    /// Atomically writes the contents of a file.
+    ///
+    /// With `OverwriteBehavior::DisallowOverwrite` the write bypasses write-behind mode and
+    /// fails with `Error::AlreadyExists` if the file is present. With
+    /// `OverwriteBehavior::BackupThenOverwrite` the file is copied first if it is present.
+    fn write_file(
+        &self,
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+        data: &[u8],
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        self.check_sealed()?;
+        let already_exists = || Error::AlreadyExists {
+            key: key.to_string(),
+            file_type,
+            path: path.to_path_buf(),
+        };
+        let create_new = matches!(overwrite, OverwriteBehavior::DisallowOverwrite);
+        if create_new && self.exists(path) {
+            return Err(already_exists());
+        }
+        if overwrite == OverwriteBehavior::BackupThenOverwrite && self.exists(path) {
+            self.back_up(key, file_type, path)?;
+        }
+        self.emit(EventKind::BeforeWrite, key, file_type, path);
+        self.cache.invalidate(path);
+        let data = self.encode(key, file_type, path, data)?;
+        match &self.flusher {
+            Some(flusher) if !create_new => {
+                flusher.enqueue(key, file_type, path, data);
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.write_strategy
+            .write(self.fs.as_ref(), path, &data, create_new)
+            .map_err(|err| match err {
+                Error::Write { source: err, .. }
+                    if create_new && err.kind() == std::io::ErrorKind::AlreadyExists =>
+                {
+                    already_exists()
+                }
+                err => err,
+            })?;
+        self.emit(EventKind::AfterWrite, key, file_type, path);
+        Ok(())
+    }
+
+    /// Serializes a value and writes it to the file, applying the given overwrite behavior.
+    fn set_as<T: Serialize>(
+        &self,
+        key: &str,
+        file_type: FileType,
+        value: T,
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        let key_path = self.path(key, file_type)?;
+        let data = self.serialize_value(key, file_type, &key_path, &value)?;
+        self.write_file(key, file_type, &key_path, data.as_bytes(), overwrite)?;
+        self.log("wrote file", key, &key_path);
+        Ok(())
+    }
+
+    /// Removes all files in the configuration path.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the file path or an `Error` if an error occurred.
+    pub fn clean(&self) -> Result<(), Error> {
+        self.check_sealed()?;
+        let path = self.path.parent().unwrap();
+        self.fs
+            .remove_dir_all(path)
+            .map_err(|err| Error::io(path, err))
+    }
+}
+
+impl Get for Config {
+    /// Given a key, obtains the file and parses it into a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value or an `Error` if an error occurred.
+    fn get<T: DeserializeOwned>(&self, key: &str, file_type: FileType) -> Result<T, Error> {
+        #[cfg(any(
+            feature = "toml",
+            feature = "json",
+            feature = "ron",
+            feature = "yaml",
+            feature = "ini",
+            feature = "json5"
+        ))]
+        if let Some(t) = self.get_layered(key, file_type)? {
+            return Ok(t);
+        }
+        let key_path = self.resolve(key, file_type)?;
+        let data = self.read_file(key, file_type, &key_path)?;
+        #[cfg(feature = "json")]
+        if let Some(document) = self.migrate(key, file_type, &key_path, &data)? {
+            let t = serde_json::from_value(document).map_err(|source| Error::Json {
+                key: key.to_string(),
+                path: key_path.clone(),
+                source,
+                #[cfg(feature = "miette")]
+                source_code: None,
+            })?;
+            self.log("read file", key, &key_path);
+            return Ok(t);
+        }
+
+        let t = match deserialize(key, file_type, &key_path, &data, &self.parse) {
+            Err(err @ Error::Truncated { .. }) if self.snapshot_fallback => {
+                match self.get_from_snapshot(key, file_type, &key_path)? {
+                    Some(t) => t,
+                    None => return Err(err),
+                }
+            }
+            result => result?,
+        };
+        self.log("read file", key, &key_path);
+        Ok(t)
+    }
+}
+
+impl Set for Config {
+    /// Given a key, saves the serialized value to the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized value or an `Error` if an error occurred.
+    fn set<T: Serialize>(&self, key: &str, file_type: FileType, value: T) -> Result<(), Error> {
+        self.set_as(key, file_type, value, self.overwrite)
+    }
+}
+
+/// Serializes a value in the given format.
+///
+/// # Arguments
+///
+/// * `key` - The key the value is stored under.
+/// * `file_type` - The format to serialize to.
+/// * `path` - The path of the file, used for error reporting.
+/// * `value` - The value to be serialized.
+///
+/// # Returns
+///
+/// A `Result` containing the serialized text or an `Error` if an error occurred.
+pub(crate) fn serialize<T: Serialize + ?Sized>(
+    key: &str,
+    file_type: FileType,
+    path: &Path,
+    value: &T,
+) -> Result<String, Error> {
+    let data = match file_type {
+        #[cfg(feature = "toml")]
+        FileType::Toml => limits::serialize(key, path, value, false)?,
+        #[cfg(feature = "json")]
+        FileType::Json => serde_json::to_string_pretty(value).map_err(|source| Error::Json {
+            key: key.to_string(),
+            path: path.to_path_buf(),
+            source,
+            #[cfg(feature = "miette")]
+            source_code: None,
+        })?,
+        #[cfg(feature = "ron")]
+        FileType::Ron => {
+            ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::new()).map_err(|source| {
+                Error::Ron {
+                    key: key.to_string(),
+                    path: path.to_path_buf(),
+                    source,
+                }
+            })?
+        }
+        #[cfg(feature = "yaml")]
+        FileType::Yaml => serde_yaml::to_string(value).map_err(|source| Error::Yaml {
+            key: key.to_string(),
+            path: path.to_path_buf(),
+            source,
+            #[cfg(feature = "miette")]
+            source_code: None,
+        })?,
+        #[cfg(feature = "ini")]
+        FileType::Ini => ini::serialize(key, path, value)?,
+        // Pretty-printed json is valid json5, and easier to edit than json5's compact output.
+        #[cfg(feature = "json5")]
+        FileType::Json5 => serde_json::to_string_pretty(value).map_err(|err| Error::Json5 {
+            key: key.to_string(),
+            path: path.to_path_buf(),
+            source: serde::ser::Error::custom(err),
+            #[cfg(feature = "miette")]
+            source_code: None,
+        })?,
+        FileType::Plain => {
+            return Err(Error::UnsupportedByFormat {
+                key: key.to_string(),
+                file_type,
+                path: path.to_path_buf(),
+                reason: "plain files hold text, write it with set_plain".to_string(),
+                suggested_format: None,
+            })
+        }
+    };
+    Ok(data)
+}
+
+/// Deserializes a value from text in the given format.
+///
+/// # Arguments
+///
+/// * `key` - The key the value is stored under.
+/// * `file_type` - The format to deserialize from.
+/// * `path` - The path of the file, used for error reporting.
+/// * `data` - The text to be deserialized.
+/// * `options` - The options the format is parsed with.
+///
+/// # Returns
+///
+/// A `Result` containing the deserialized value or an `Error` if an error occurred.
+pub(crate) fn deserialize<T: DeserializeOwned>(
+    key: &str,
+    file_type: FileType,
+    path: &Path,
+    data: &str,
+    #[cfg_attr(not(any(feature = "json", feature = "ron")), allow(unused_variables))]
+    options: &ParseOptions,
+) -> Result<T, Error> {
+    let data = &*header::strip(data, file_type);
+    #[cfg(feature = "json")]
+    let stripped;
+    #[cfg(feature = "json")]
+    let text = if file_type == FileType::Json && options.jsonc {
+        stripped = jsonc::strip(data);
+        &*stripped
+    } else {
+        data
+    };
+    #[cfg(not(feature = "json"))]
+    let text = data;
+    // Only failures caused by the document ending early are reported as truncated.
+    let truncated = |eof: bool| {
+        (eof || truncation::is_truncated(text, file_type)).then(|| Error::Truncated {
+            key: key.to_string(),
+            file_type,
+            path: path.to_path_buf(),
+        })
+    };
+    let value = match file_type {
+        #[cfg(feature = "toml")]
+        FileType::Toml => toml::from_str(data).map_err(|source| {
+            let eof = source
+                .span()
+                .is_some_and(|span| span.start >= data.trim_end().len());
+            truncated(eof).unwrap_or_else(|| Error::TomlDeserialize {
+                key: key.to_string(),
+                path: path.to_path_buf(),
+                source: Box::new(source),
+                #[cfg(feature = "miette")]
+                source_code: diagnostic::source_code(path, data),
+            })
+        })?,
+        #[cfg(feature = "json")]
+        FileType::Json => {
+            options.check_duplicates(key, path, text)?;
+            serde_json::from_str(text).map_err(|source| {
+                truncated(source.is_eof()).unwrap_or_else(|| Error::Json {
+                    key: key.to_string(),
+                    path: path.to_path_buf(),
+                    source,
+                    #[cfg(feature = "miette")]
+                    source_code: diagnostic::source_code(path, data),
+                })
+            })?
+        }
+        #[cfg(feature = "ron")]
+        FileType::Ron => options.ron.from_str(data).map_err(|source| {
+            truncated(source.code == ron::Error::Eof).unwrap_or_else(|| Error::RonSpanned {
+                key: key.to_string(),
+                path: path.to_path_buf(),
+                source: Box::new(source),
+                #[cfg(feature = "miette")]
+                source_code: diagnostic::source_code(path, data),
+            })
+        })?,
+        #[cfg(feature = "yaml")]
+        FileType::Yaml => serde_yaml::from_str(data).map_err(|source| Error::Yaml {
+            key: key.to_string(),
+            path: path.to_path_buf(),
+            source,
+            #[cfg(feature = "miette")]
+            source_code: diagnostic::source_code(path, data),
+        })?,
+        #[cfg(feature = "ini")]
+        FileType::Ini => ini::deserialize(key, path, data)?,
+        #[cfg(feature = "json5")]
+        FileType::Json5 => json5::from_str(data).map_err(|source| {
+            truncated(false).unwrap_or_else(|| Error::Json5 {
+                key: key.to_string(),
+                path: path.to_path_buf(),
+                source,
+                #[cfg(feature = "miette")]
+                source_code: diagnostic::source_code(path, data),
+            })
+        })?,
+        FileType::Plain => {
+            return Err(Error::PlainValue {
+                key: key.to_string(),
+                path: path.to_path_buf(),
+                reason: "plain files hold text, read it with get_plain".to_string(),
+            })
+        }
+    };
+    Ok(value)
 }
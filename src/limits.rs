@@ -0,0 +1,154 @@
+use std::{borrow::Cow, path::Path};
+
+use serde::Serialize;
+use serde_content::{Data, Number, Serializer, Value};
+
+use crate::{formats, utils::FileType, Config, Error};
+
+impl Config {
+    /// Sets whether integer map keys are written to toml files as strings.
+    ///
+    /// Toml tables only accept string keys, so maps such as `HashMap<u32, String>` fail with
+    /// [`Error::UnsupportedByFormat`] by default. When enabled, their keys are written as
+    /// strings instead. The toml parser does not convert them back, read such files into
+    /// maps with string keys.
+    ///
+    /// ```no_run
+    /// # use std::collections::BTreeMap;
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?.with_toml_stringified_keys(true);
+    /// let ports = BTreeMap::from([(8080, "http"), (8443, "https")]);
+    /// config.set_toml("ports", &ports)?;
+    /// let ports: BTreeMap<String, String> = config.get_toml("ports")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether integer keys are converted.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_toml_stringified_keys(mut self, enabled: bool) -> Self {
+        self.toml_stringified_keys = enabled;
+        self
+    }
+}
+
+/// Serializes a value to toml, explaining the failure when toml cannot represent it.
+///
+/// # Arguments
+///
+/// * `key` - The key the value is stored under.
+/// * `path` - The path of the file, used for error reporting.
+/// * `value` - The value to be serialized.
+/// * `stringify_keys` - Whether integer map keys are written as strings.
+///
+/// # Returns
+///
+/// A `Result` containing the serialized text or an `Error` if an error occurred.
+pub(crate) fn serialize<T: Serialize + ?Sized>(
+    key: &str,
+    path: &Path,
+    value: &T,
+    stringify_keys: bool,
+) -> Result<String, Error> {
+    let source = match toml::to_string_pretty(value) {
+        Ok(data) => return Ok(data),
+        Err(source) => source,
+    };
+    let unsupported = |reason: String| Error::UnsupportedByFormat {
+        key: key.to_string(),
+        file_type: FileType::Toml,
+        path: path.to_path_buf(),
+        reason,
+        suggested_format: formats::suggested_format(),
+    };
+    match source.to_string().as_str() {
+        "map key was not a string" if stringify_keys => {
+            if let Ok(mut content) = Serializer::new().human_readable().serialize(value) {
+                stringify(&mut content);
+                if let Ok(data) = toml::to_string_pretty(&content) {
+                    return Ok(data);
+                }
+            }
+            return Err(unsupported(
+                "tables only accept string and integer keys".to_string(),
+            ));
+        }
+        "map key was not a string" => {
+            return Err(unsupported(
+                "tables only accept string keys, see `Config::with_toml_stringified_keys`"
+                    .to_string(),
+            ));
+        }
+        "unsupported rust type" => {
+            if let Ok(value) = toml::Value::try_from(value) {
+                if !value.is_table() {
+                    return Err(unsupported(format!(
+                        "documents must be a table at the top level, found {}",
+                        value.type_str()
+                    )));
+                }
+            }
+        }
+        _ => {}
+    }
+    Err(Error::TomlSerialize {
+        key: key.to_string(),
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Replaces the integer map keys of a value with strings, recursively.
+fn stringify(value: &mut Value) {
+    match value {
+        Value::Map(entries) => {
+            for (key, value) in entries {
+                if let Value::Number(number) = key {
+                    if let Some(text) = integer(number) {
+                        *key = Value::String(Cow::Owned(text));
+                    }
+                }
+                stringify(value);
+            }
+        }
+        Value::Seq(values) | Value::Tuple(values) => values.iter_mut().for_each(stringify),
+        Value::Option(Some(value)) => stringify(value),
+        Value::Struct(inner) => stringify_data(&mut inner.data),
+        Value::Enum(inner) => stringify_data(&mut inner.data),
+        _ => {}
+    }
+}
+
+/// Replaces the integer map keys within the data of a struct or enum variant.
+fn stringify_data(data: &mut Data) {
+    match data {
+        Data::Unit => {}
+        Data::NewType { value } => stringify(value),
+        Data::Tuple { values } => values.iter_mut().for_each(stringify),
+        Data::Struct { fields } => fields.iter_mut().for_each(|(_, value)| stringify(value)),
+    }
+}
+
+/// Formats a number if it is an integer.
+fn integer(number: &Number) -> Option<String> {
+    let text = match number {
+        Number::I8(n) => n.to_string(),
+        Number::U8(n) => n.to_string(),
+        Number::I16(n) => n.to_string(),
+        Number::U16(n) => n.to_string(),
+        Number::I32(n) => n.to_string(),
+        Number::U32(n) => n.to_string(),
+        Number::I64(n) => n.to_string(),
+        Number::U64(n) => n.to_string(),
+        Number::I128(n) => n.to_string(),
+        Number::U128(n) => n.to_string(),
+        _ => return None,
+    };
+    Some(text)
+}
@@ -0,0 +1,190 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(any(
+    feature = "toml",
+    feature = "json",
+    feature = "ron",
+    feature = "yaml",
+    feature = "ini",
+    feature = "json5"
+))]
+use serde::de::DeserializeOwned;
+
+#[cfg(any(
+    feature = "toml",
+    feature = "json",
+    feature = "ron",
+    feature = "yaml",
+    feature = "ini",
+    feature = "json5"
+))]
+use crate::layer::Document;
+#[cfg(feature = "toml")]
+use crate::utils::decode;
+use crate::{utils::FileType, Config, Error};
+
+/// Extension of the file next to a toml file whose settings are merged over it on reads.
+#[cfg(feature = "toml")]
+const LOCAL_EXTENSION: &str = "local.toml";
+
+/// A project-local file found by [`Config::discover_local`].
+#[derive(Debug, Clone)]
+pub(crate) struct LocalFile {
+    path: PathBuf,
+    file_type: FileType,
+}
+
+impl Config {
+    /// Layers a project-local file over the store, found in the current directory or its
+    /// closest ancestor containing it.
+    ///
+    /// Like Cargo and ESLint, command line tools can keep per-repository overrides in a file
+    /// such as `.myapprc`. Each top-level table of the file is merged over the key of the same
+    /// name when it is read in the file's format, table by table, so the file only needs the
+    /// settings it changes, on top of any `.local.toml` override file. Writes always go to the
+    /// store and never touch the file.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// // With a .myapprc containing `{ "build": { "jobs": 2 } }`, jobs is 2 inside the
+    /// // repository and the other build settings come from the store.
+    /// let config = Config::new("org.example.Tool", 1, None)?
+    ///     .discover_local(".myapprc", FileType::Json)?;
+    /// let build: serde_json::Value = config.get_json("build")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the project-local file.
+    /// * `file_type` - The format of the file, plain files are not supported.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated `Config` object or an `Error` if the current directory
+    /// could not be determined. Finding no file is not an error.
+    pub fn discover_local(self, file_name: &str, file_type: FileType) -> Result<Self, Error> {
+        let current = std::env::current_dir().map_err(|err| Error::io(Path::new("."), err))?;
+        Ok(self.discover_local_from(&current, file_name, file_type))
+    }
+
+    /// Layers a project-local file over the store, found in a directory or its closest
+    /// ancestor containing it.
+    ///
+    /// See [`Config::discover_local`], which starts from the current directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to start searching from.
+    /// * `file_name` - The name of the project-local file.
+    /// * `file_type` - The format of the file, plain files are not supported.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn discover_local_from(mut self, dir: &Path, file_name: &str, file_type: FileType) -> Self {
+        if file_type == FileType::Plain {
+            return self;
+        }
+        self.local = dir
+            .ancestors()
+            .map(|dir| dir.join(file_name))
+            .find(|path| path.is_file())
+            .map(|path| LocalFile { path, file_type });
+        if let Some(local) = &self.local {
+            self.log_policy
+                .log("found project-local file", None, &local.path);
+        }
+        self
+    }
+
+    /// Returns the project-local file found by [`Config::discover_local`], if any.
+    pub fn local_file(&self) -> Option<&Path> {
+        self.local.as_ref().map(|local| local.path.as_path())
+    }
+
+    /// Reads a key with its local override file and the project-local file merged over it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the merged value, `None` if no layer sets the key, or an `Error`
+    /// if a file could not be read or the merged document doesn't match the type.
+    #[cfg(any(
+        feature = "toml",
+        feature = "json",
+        feature = "ron",
+        feature = "yaml",
+        feature = "ini",
+        feature = "json5"
+    ))]
+    pub(crate) fn get_layered<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        file_type: FileType,
+    ) -> Result<Option<T>, Error> {
+        let key_path = self.resolve(key, file_type)?;
+        let mut layers = Vec::new();
+        #[cfg(feature = "toml")]
+        if file_type == FileType::Toml {
+            layers.extend(self.local_override(key, &key_path)?);
+        }
+        layers.extend(self.project_layer(key, file_type)?);
+        if layers.is_empty() {
+            return Ok(None);
+        }
+        let mut layers = layers.into_iter();
+        let mut document = match self.read_file(key, file_type, &key_path) {
+            Ok(data) => Document::parse(key, file_type, &key_path, &data, &self.parse)?,
+            Err(err) if err.is_not_found() => layers.next().unwrap(),
+            Err(err) => return Err(err),
+        };
+        for layer in layers {
+            document.merge(layer);
+        }
+        self.log("read file", key, &key_path);
+        document.into_value(key, &key_path).map(Some)
+    }
+
+    /// Reads the `.local.toml` file next to a toml file, if present.
+    ///
+    /// Local override files hold machine-specific settings that are not synced, they are
+    /// read as they are, without running the middleware chain.
+    #[cfg(feature = "toml")]
+    fn local_override(&self, key: &str, key_path: &Path) -> Result<Option<Document>, Error> {
+        let path = key_path.with_extension(LOCAL_EXTENSION);
+        let bytes = match self.fs.read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::for_key(key, FileType::Toml, &path, err)),
+        };
+        let data = decode(key, &path, bytes)?;
+        Document::parse(key, FileType::Toml, &path, &data, &self.parse).map(Some)
+    }
+
+    /// Reads the section of the project-local file named after a key, if present.
+    #[cfg(any(
+        feature = "toml",
+        feature = "json",
+        feature = "ron",
+        feature = "yaml",
+        feature = "ini",
+        feature = "json5"
+    ))]
+    fn project_layer(&self, key: &str, file_type: FileType) -> Result<Option<Document>, Error> {
+        let Some(local) = self
+            .local
+            .as_ref()
+            .filter(|local| local.file_type == file_type)
+        else {
+            return Ok(None);
+        };
+        let data = match std::fs::read_to_string(&local.path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::io(&local.path, err)),
+        };
+        Ok(Document::parse(key, file_type, &local.path, &data, &self.parse)?.section(key))
+    }
+}
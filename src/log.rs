@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use tracing::Level;
+
+use crate::Config;
+
+/// Controls the messages libset logs for reads, writes and removals.
+///
+/// By default every operation is logged at `INFO` along with the key and full path of the
+/// file. Lower the level to keep logs quiet, or leave out keys and paths when logs are shared
+/// and file names could reveal information about the user. Failures are always logged.
+///
+/// ```no_run
+/// # use libset::{Config, LogPolicy};
+/// # fn main() -> Result<(), libset::Error> {
+/// let config = Config::new("org.example.Demo", 1, None)?.with_log_policy(LogPolicy {
+///     level: Some(tracing::Level::DEBUG),
+///     log_keys: true,
+///     log_paths: false,
+/// });
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogPolicy {
+    /// The level operations are logged at, `None` disables them.
+    pub level: Option<Level>,
+    /// Whether messages include the key of the file.
+    pub log_keys: bool,
+    /// Whether messages include the path of the file.
+    pub log_paths: bool,
+}
+
+impl Default for LogPolicy {
+    fn default() -> Self {
+        Self {
+            level: Some(Level::INFO),
+            log_keys: true,
+            log_paths: true,
+        }
+    }
+}
+
+impl LogPolicy {
+    /// Logs an operation on a file, leaving out what the policy excludes.
+    pub(crate) fn log(&self, message: &str, key: Option<&str>, path: &Path) {
+        let Some(level) = self.level else {
+            return;
+        };
+        let key = key.filter(|_| self.log_keys);
+        let path = self
+            .log_paths
+            .then(|| tracing::field::display(path.display()));
+        macro_rules! log {
+            ($level:ident) => {
+                tracing::$level!(key, path, "{message}")
+            };
+        }
+        match level {
+            Level::ERROR => log!(error),
+            Level::WARN => log!(warn),
+            Level::INFO => log!(info),
+            Level::DEBUG => log!(debug),
+            _ => log!(trace),
+        }
+    }
+}
+
+impl Config {
+    /// Sets what is logged for reads, writes and removals.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The logging rules.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_log_policy(mut self, policy: LogPolicy) -> Self {
+        self.log_policy = policy;
+        self.restart_flusher();
+        self
+    }
+
+    /// Logs an operation on a key following the log policy.
+    pub(crate) fn log(&self, message: &str, key: &str, path: &Path) {
+        self.log_policy.log(message, Some(key), path);
+    }
+}
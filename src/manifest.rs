@@ -0,0 +1,209 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use sha2::{Digest, Sha256};
+
+use crate::{utils::encode_hex, Config, Error};
+
+/// First line of every serialized manifest.
+const HEADER: &str = "libset-manifest 1";
+
+/// Hashes data into a hexadecimal SHA-256 digest.
+pub(crate) fn hash(data: &[u8]) -> String {
+    encode_hex(&Sha256::digest(data))
+}
+
+/// Checks that a name is a hexadecimal SHA-256 digest.
+pub(crate) fn is_hash(name: &str) -> bool {
+    name.len() == 64 && name.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// The content hash of every file in a store.
+///
+/// Paths are relative to the version root and use `/` as separator on every platform, so a
+/// manifest can be exported, stored elsewhere and parsed back with [`str::parse`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub(crate) fn insert(&mut self, path: String, digest: String) {
+        self.files.insert(path, digest);
+    }
+
+    /// Returns the stored paths and the SHA-256 digest of their contents, sorted by path.
+    pub fn files(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.files
+            .iter()
+            .map(|(path, digest)| (path.as_str(), digest.as_str()))
+    }
+
+    /// Computes a single hash identifying the contents of every file.
+    ///
+    /// # Returns
+    ///
+    /// The hexadecimal SHA-256 digest of the serialized manifest.
+    pub fn fingerprint(&self) -> String {
+        hash(self.to_string().as_bytes())
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        for (path, digest) in &self.files {
+            writeln!(f, "{digest} {path}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(Error::InvalidManifest("unsupported header".to_string()));
+        }
+        let mut manifest = Manifest::default();
+        for line in lines.filter(|line| !line.is_empty()) {
+            match line.split_once(' ') {
+                Some((digest, path)) if is_hash(digest) && !path.is_empty() => {
+                    manifest.insert(path.to_string(), digest.to_string())
+                }
+                _ => return Err(Error::InvalidManifest(format!("invalid line {line:?}"))),
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// How a file differs from a manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftKind {
+    /// The file is not part of the manifest.
+    Added,
+    /// The file is part of the manifest but no longer exists.
+    Removed,
+    /// The contents of the file changed.
+    Modified,
+}
+
+/// A file that differs from a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drift {
+    /// The path of the file relative to the version root.
+    pub path: String,
+    /// How the file differs.
+    pub kind: DriftKind,
+}
+
+impl Config {
+    /// Hashes every file in the version root and its scopes.
+    ///
+    /// Files are hashed as they are stored on disk, so encrypted files are compared in their
+    /// encrypted form.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Manifest` or an `Error` if an error occurred.
+    pub fn manifest(&self) -> Result<Manifest, Error> {
+        self.manifest_with(|_, _| Ok(()))
+    }
+
+    /// Computes a stable hash over the contents of every file in the store.
+    ///
+    /// The hash changes whenever a file is added, removed or modified, and is the same on
+    /// every platform for identical contents.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the hexadecimal fingerprint or an `Error` if an error occurred.
+    pub fn fingerprint(&self) -> Result<String, Error> {
+        Ok(self.manifest()?.fingerprint())
+    }
+
+    /// Compares the store with a manifest taken earlier.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, Manifest};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Kiosk", 1, None)?;
+    /// let expected: Manifest = std::fs::read_to_string("/etc/kiosk/manifest")
+    ///     .map_err(|err| libset::Error::from(err.to_string()))?
+    ///     .parse()?;
+    /// for drift in config.verify(&expected)? {
+    ///     eprintln!("{} was {:?}", drift.path, drift.kind);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - The expected state of the store.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing every file that differs, sorted by path, or an `Error` if an
+    /// error occurred. An empty list means the store matches the manifest.
+    pub fn verify(&self, manifest: &Manifest) -> Result<Vec<Drift>, Error> {
+        let current = self.manifest()?;
+        let mut drift: Vec<Drift> = current
+            .files
+            .iter()
+            .filter_map(|(path, digest)| {
+                let kind = match manifest.files.get(path) {
+                    None => DriftKind::Added,
+                    Some(expected) if expected != digest => DriftKind::Modified,
+                    Some(_) => return None,
+                };
+                Some(Drift {
+                    path: path.clone(),
+                    kind,
+                })
+            })
+            .chain(
+                manifest
+                    .files
+                    .keys()
+                    .filter(|path| !current.files.contains_key(*path))
+                    .map(|path| Drift {
+                        path: path.clone(),
+                        kind: DriftKind::Removed,
+                    }),
+            )
+            .collect();
+        drift.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(drift)
+    }
+
+    /// Builds a manifest, passing the digest and contents of every file to `visit`.
+    pub(crate) fn manifest_with<F>(&self, mut visit: F) -> Result<Manifest, Error>
+    where
+        F: FnMut(&str, &[u8]) -> Result<(), Error>,
+    {
+        self.flush()?;
+        let mut manifest = Manifest::default();
+        for entry in self.walk()? {
+            let entry = entry?;
+            let data = self
+                .fs
+                .read(&entry.path)
+                .map_err(|err| Error::io(&entry.path, err))?;
+            let digest = hash(&data);
+            visit(&digest, &data)?;
+            let relative = entry
+                .path
+                .strip_prefix(&self.root)
+                .unwrap_or(&entry.path)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            manifest.insert(relative, digest);
+        }
+        Ok(manifest)
+    }
+}
@@ -0,0 +1,181 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Config, Error, LibsetResultExt};
+
+/// A number stored in a json file, created with [`Config::counter`].
+///
+/// Every change holds the write guard of the key, so processes and threads counting at the
+/// same time never lose each other's increments.
+pub struct Counter<'a> {
+    config: &'a Config,
+    key: String,
+}
+
+/// A point in time stored in a json file as seconds since the Unix epoch, created with
+/// [`Config::timestamp`].
+pub struct Timestamp<'a> {
+    config: &'a Config,
+    key: String,
+}
+
+impl Config {
+    /// Opens a counter, such as the number of times the application was launched.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let launches = config.counter("launch_count").increment()?;
+    /// if launches == 1 {
+    ///     println!("welcome!");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the counter.
+    ///
+    /// # Returns
+    ///
+    /// The `Counter` handle, a counter that was never stored is zero.
+    pub fn counter(&self, key: &str) -> Counter<'_> {
+        Counter {
+            config: self,
+            key: key.to_string(),
+        }
+    }
+
+    /// Opens a timestamp, such as the last time updates were checked for.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let last_check = config.timestamp("last_update_check");
+    /// if last_check.elapsed()?.map_or(true, |elapsed| elapsed > Duration::from_secs(86400)) {
+    ///     println!("checking for updates");
+    ///     last_check.set_now()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the timestamp.
+    ///
+    /// # Returns
+    ///
+    /// The `Timestamp` handle.
+    pub fn timestamp(&self, key: &str) -> Timestamp<'_> {
+        Timestamp {
+            config: self,
+            key: key.to_string(),
+        }
+    }
+}
+
+impl Counter<'_> {
+    /// Returns the current value of the counter.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value, zero if it was never stored, or an `Error` if it
+    /// could not be read.
+    pub fn get(&self) -> Result<u64, Error> {
+        self.config.get_json(&self.key).or_default_value()
+    }
+
+    /// Adds one to the counter.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new value or an `Error` if the counter could not be updated.
+    pub fn increment(&self) -> Result<u64, Error> {
+        self.add(1)
+    }
+
+    /// Adds an amount to the counter, stopping at `u64::MAX`.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - The amount to add.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new value or an `Error` if the counter could not be updated.
+    pub fn add(&self, amount: u64) -> Result<u64, Error> {
+        self.config.update_json(&self.key, |value: &mut u64| {
+            *value = value.saturating_add(amount);
+            *value
+        })
+    }
+
+    /// Sets the counter back to zero.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value before the reset or an `Error` if the counter could
+    /// not be updated.
+    pub fn reset(&self) -> Result<u64, Error> {
+        self.config
+            .update_json(&self.key, |value: &mut u64| std::mem::take(value))
+    }
+}
+
+impl Timestamp<'_> {
+    /// Returns the stored point in time.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the time, `None` if it was never set, or an `Error` if it could
+    /// not be read.
+    pub fn get(&self) -> Result<Option<SystemTime>, Error> {
+        let secs: Option<u64> = self.config.get_json(&self.key).or_default_value()?;
+        Ok(secs.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)))
+    }
+
+    /// Returns the time passed since the stored point in time.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the elapsed time, `None` if the timestamp was never set, or an
+    /// `Error` if it could not be read. A time in the future counts as no time elapsed.
+    pub fn elapsed(&self) -> Result<Option<Duration>, Error> {
+        Ok(self.get()?.map(|time| time.elapsed().unwrap_or_default()))
+    }
+
+    /// Stores a point in time, truncated to whole seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time to store, times before the Unix epoch are stored as the epoch.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the previously stored time or an `Error` if the timestamp could
+    /// not be updated.
+    pub fn set(&self, time: SystemTime) -> Result<Option<SystemTime>, Error> {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let previous = self
+            .config
+            .update_json(&self.key, |value: &mut Option<u64>| value.replace(secs))?;
+        Ok(previous.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)))
+    }
+
+    /// Stores the current time.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the previously stored time or an `Error` if the timestamp could
+    /// not be updated.
+    pub fn set_now(&self) -> Result<Option<SystemTime>, Error> {
+        self.set(SystemTime::now())
+    }
+}
@@ -0,0 +1,82 @@
+use std::{path::Path, sync::Arc};
+
+use crate::{utils::FileType, Error};
+
+/// The direction in which data flows through a [`Middleware`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Serialized data on its way to the filesystem.
+    Write,
+    /// Raw file contents on their way to the deserializer.
+    Read,
+}
+
+/// Describes the file a [`Middleware`] is transforming.
+#[derive(Debug, Clone, Copy)]
+pub struct EntryInfo<'a> {
+    /// The key of the file, normalized like its file name when key normalization or
+    /// case-insensitive keys are enabled.
+    pub key: &'a str,
+    /// The format of the file.
+    pub file_type: FileType,
+    /// The absolute path of the file.
+    pub path: &'a Path,
+}
+
+/// A transformation applied to the bytes of a file when it is written and read.
+///
+/// Middleware runs in registration order on writes and in reverse order on reads, so layers
+/// such as compression followed by encryption undo each other correctly. Closures with the
+/// signature `Fn(&mut Vec<u8>, Direction, &EntryInfo) -> Result<(), Error>` implement this
+/// trait.
+pub trait Middleware: Send + Sync {
+    /// Transforms the data in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The bytes to transform.
+    /// * `direction` - Whether the data is being written or read.
+    /// * `entry` - The file the data belongs to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the data could not be transformed.
+    fn apply(
+        &self,
+        data: &mut Vec<u8>,
+        direction: Direction,
+        entry: &EntryInfo,
+    ) -> Result<(), Error>;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(&mut Vec<u8>, Direction, &EntryInfo) -> Result<(), Error> + Send + Sync,
+{
+    fn apply(
+        &self,
+        data: &mut Vec<u8>,
+        direction: Direction,
+        entry: &EntryInfo,
+    ) -> Result<(), Error> {
+        self(data, direction, entry)
+    }
+}
+
+/// Runs a chain of middleware over the data in the order required by the direction.
+pub(crate) fn run(
+    chain: &[Arc<dyn Middleware>],
+    data: &mut Vec<u8>,
+    direction: Direction,
+    entry: &EntryInfo,
+) -> Result<(), Error> {
+    match direction {
+        Direction::Write => chain
+            .iter()
+            .try_for_each(|middleware| middleware.apply(data, direction, entry)),
+        Direction::Read => chain
+            .iter()
+            .rev()
+            .try_for_each(|middleware| middleware.apply(data, direction, entry)),
+    }
+}
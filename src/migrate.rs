@@ -0,0 +1,168 @@
+use std::{path::Path, sync::Arc};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{deserialize, utils::FileType, Config, Error};
+
+/// The field holding the schema version of documents with migrations.
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// Upgrades a document from one schema version to the next, see [`Config::key_migrations`].
+pub type Migration = fn(&mut Value) -> Result<(), Error>;
+
+impl Config {
+    /// Registers the migrations upgrading the document stored under a key.
+    ///
+    /// Documents carry their schema version in a `schema_version` field, documents without
+    /// it are at version 1. The first migration upgrades version 1 to 2, the second 2 to 3,
+    /// and so on, so the current version is one more than the number of migrations. Reading
+    /// the key upgrades an outdated document and writes it back before returning it, and
+    /// writing the key stamps the current version. The field is ignored by types that don't
+    /// declare it, unless they deny unknown fields.
+    ///
+    /// Files read from fallback scopes and sealed stores are upgraded in memory only.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # use serde_json::{json, Value};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// fn v1_to_v2(document: &mut Value) -> Result<(), libset::Error> {
+    ///     // `font` became `font.family`.
+    ///     let family = document["font"].take();
+    ///     document["font"] = json!({ "family": family });
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut config = Config::new("org.example.Editor", 1, None)?;
+    /// config.key_migrations("settings", &[v1_to_v2]);
+    /// let settings: Value = config.get_json("settings")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the document.
+    /// * `migrations` - The migrations in order, replacing any registered before.
+    pub fn key_migrations(&mut self, key: &str, migrations: &[Migration]) {
+        self.migrations
+            .insert(key.to_string(), Arc::from(migrations));
+    }
+
+    /// Returns the current schema version of a key, `None` if it has no migrations.
+    pub(crate) fn schema_version(&self, key: &str) -> Option<u64> {
+        self.migrations
+            .get(key)
+            .map(|migrations| migrations.len() as u64 + 1)
+    }
+
+    /// Upgrades the document stored under a key to its current schema version.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the upgraded document, `None` if the key has no migrations or
+    /// is stored as plain text, or an `Error` if the document could not be read, upgraded or
+    /// written back.
+    pub(crate) fn migrate(
+        &self,
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+        data: &str,
+    ) -> Result<Option<Value>, Error> {
+        let Some(migrations) = self
+            .migrations
+            .get(key)
+            .filter(|_| file_type != FileType::Plain)
+        else {
+            return Ok(None);
+        };
+        let current = migrations.len() as u64 + 1;
+        let mut document: Value = deserialize(key, file_type, path, data, &self.parse)?;
+        if version(&document) == current {
+            return Ok(Some(document));
+        }
+        // Sealed stores and files of fallback scopes are only upgraded in memory.
+        let own = self.path(key, file_type)? == path && !self.is_sealed();
+        // Upgrade under the write guard, so processes reading concurrently migrate once.
+        let _guard = own.then(|| self.write_guard(key)).transpose()?;
+        if own {
+            let data = self.read_file(key, file_type, path)?;
+            document = deserialize(key, file_type, path, &data, &self.parse)?;
+        }
+        let failed = |reason: String| Error::Migration {
+            key: key.to_string(),
+            file_type,
+            path: path.to_path_buf(),
+            reason,
+        };
+        let from = version(&document);
+        if from > current {
+            return Err(failed(format!(
+                "written with schema version {from}, newer than the supported version {current}"
+            )));
+        }
+        if from == current {
+            return Ok(Some(document));
+        }
+        for (index, migration) in migrations.iter().enumerate().skip(from as usize - 1) {
+            migration(&mut document).map_err(|err| {
+                failed(format!(
+                    "upgrading from version {} failed: {err}",
+                    index + 1
+                ))
+            })?;
+        }
+        match &mut document {
+            Value::Object(object) => {
+                object.insert(SCHEMA_VERSION_FIELD.to_string(), current.into());
+            }
+            _ => return Err(failed("the upgraded document is not an object".to_string())),
+        }
+        if own {
+            self.set_as(key, file_type, &document, self.overwrite)?;
+        }
+        Ok(Some(document))
+    }
+}
+
+/// Returns the schema version of a document, 1 when it has none.
+fn version(document: &Value) -> u64 {
+    document
+        .get(SCHEMA_VERSION_FIELD)
+        .and_then(Value::as_u64)
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Adds the schema version to a value about to be written.
+///
+/// # Returns
+///
+/// A `Result` containing the document with its version, or an `Error` if the value is not
+/// an object.
+pub(crate) fn stamp<T: Serialize + ?Sized>(
+    key: &str,
+    file_type: FileType,
+    path: &Path,
+    value: &T,
+    version: u64,
+) -> Result<Value, Error> {
+    let failed = |reason: String| Error::Migration {
+        key: key.to_string(),
+        file_type,
+        path: path.to_path_buf(),
+        reason,
+    };
+    match serde_json::to_value(value) {
+        Ok(Value::Object(mut object)) => {
+            object.insert(SCHEMA_VERSION_FIELD.to_string(), version.into());
+            Ok(Value::Object(object))
+        }
+        Ok(_) => Err(failed(
+            "documents with migrations must be objects".to_string(),
+        )),
+        Err(err) => Err(failed(err.to_string())),
+    }
+}
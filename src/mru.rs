@@ -0,0 +1,111 @@
+use std::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Config, Error, LibsetResultExt};
+
+/// A most-recently-used list stored in a json file, created with [`Config::mru`].
+///
+/// Items are kept most recent first, without duplicates and never more than the capacity.
+/// Every change holds the write guard of the key, so processes and threads updating the
+/// list at the same time don't lose each other's items.
+pub struct Mru<'a, T = String> {
+    config: &'a Config,
+    key: String,
+    capacity: usize,
+    item: PhantomData<T>,
+}
+
+impl Config {
+    /// Opens a most-recently-used list, such as the recently opened projects.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Editor", 1, None)?;
+    /// let recent = config.mru::<String>("recent_projects", 10);
+    /// recent.touch("~/src/libset".to_string())?;
+    /// for project in recent.list()? {
+    ///     println!("{project}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the list.
+    /// * `capacity` - The maximum number of items kept, older items are dropped.
+    ///
+    /// # Returns
+    ///
+    /// The `Mru` handle, the file is only read and written when the list is used.
+    pub fn mru<T>(&self, key: &str, capacity: usize) -> Mru<'_, T> {
+        Mru {
+            config: self,
+            key: key.to_string(),
+            capacity,
+            item: PhantomData,
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + PartialEq> Mru<'_, T> {
+    /// Moves an item to the front of the list, adding it if it isn't listed.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The item that was just used.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the list could not be updated.
+    pub fn touch(&self, item: T) -> Result<(), Error> {
+        let capacity = self.capacity;
+        self.config.update_json(&self.key, |items: &mut Vec<T>| {
+            items.retain(|existing| *existing != item);
+            items.insert(0, item);
+            items.truncate(capacity);
+        })
+    }
+
+    /// Returns the items of the list, most recent first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the items, empty if the list was never stored, or an `Error` if
+    /// it could not be read.
+    pub fn list(&self) -> Result<Vec<T>, Error> {
+        let mut items: Vec<T> = self.config.get_json(&self.key).or_default_value()?;
+        items.truncate(self.capacity);
+        Ok(items)
+    }
+
+    /// Removes an item from the list.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The item to remove, such as a project that no longer exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `true` if the item was listed, or an `Error` if the list could
+    /// not be updated.
+    pub fn remove(&self, item: &T) -> Result<bool, Error> {
+        self.config.update_json(&self.key, |items: &mut Vec<T>| {
+            let len = items.len();
+            items.retain(|existing| existing != item);
+            items.len() != len
+        })
+    }
+
+    /// Removes every item from the list.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the list could not be updated.
+    pub fn clear(&self) -> Result<(), Error> {
+        self.config
+            .update_json(&self.key, |items: &mut Vec<T>| items.clear())
+    }
+}
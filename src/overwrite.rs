@@ -0,0 +1,308 @@
+use std::path::{Path, PathBuf};
+
+use crate::{hooks::EventKind, utils::FileType, Config, Error};
+
+/// What happens when a value is written to a key that already has a file.
+///
+/// Set the behavior of every `set_*` call with [`Config::with_overwrite_behavior`], or of a
+/// single write with the `set_*_with` methods, such as [`Config::set_json_with`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverwriteBehavior {
+    /// Replace the file.
+    #[default]
+    AllowOverwrite,
+    /// Fail with [`Error::AlreadyExists`] if the file is present. The check and the write
+    /// happen atomically and bypass write-behind mode.
+    DisallowOverwrite,
+    /// Keep a copy of the file before replacing it, so the previous value can be brought
+    /// back with [`Config::restore_backup`]. Only the file being replaced is copied, under
+    /// `.backups` next to the version directory, and each write replaces the previous copy.
+    BackupThenOverwrite,
+}
+
+impl Config {
+    /// Sets what `set_*` calls do when the key already has a file.
+    ///
+    /// The `set_*_new` and `set_*_cas` methods keep their own behavior.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, OverwriteBehavior};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, Some("accounts"))?
+    ///     .with_overwrite_behavior(OverwriteBehavior::BackupThenOverwrite);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `overwrite` - The behavior applied to existing files.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_overwrite_behavior(mut self, overwrite: OverwriteBehavior) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Brings back the value a key had before its last write with
+    /// [`OverwriteBehavior::BackupThenOverwrite`].
+    ///
+    /// The copy is written back as it was stored, so encrypted files stay encrypted. The
+    /// copy is kept, restoring it twice has no further effect.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType, OverwriteBehavior};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?
+    ///     .with_overwrite_behavior(OverwriteBehavior::BackupThenOverwrite);
+    /// config.set_json("wallet", "bc1q...")?;
+    /// config.set_json("wallet", "oops")?;
+    /// config.restore_backup("wallet", FileType::Json)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The format of the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred, such as
+    /// [`Error::KeyNotFound`] if the key has no backup.
+    pub fn restore_backup(&self, key: &str, file_type: FileType) -> Result<(), Error> {
+        self.check_sealed()?;
+        let path = self.path(key, file_type)?;
+        let backup = self.backup_of(&path);
+        let data = self
+            .fs
+            .read(&backup)
+            .map_err(|err| Error::for_key(key, file_type, &backup, err))?;
+        if let Some(flusher) = &self.flusher {
+            flusher.cancel(&path);
+        }
+        self.emit(EventKind::BeforeWrite, key, file_type, &path);
+        self.cache.invalidate(&path);
+        self.write_strategy
+            .write(self.fs.as_ref(), &path, &data, false)?;
+        self.emit(EventKind::AfterWrite, key, file_type, &path);
+        self.log("restored backup", key, &path);
+        Ok(())
+    }
+
+    /// Copies the stored contents of a file before it is replaced, see
+    /// [`OverwriteBehavior::BackupThenOverwrite`].
+    pub(crate) fn back_up(&self, key: &str, file_type: FileType, path: &Path) -> Result<(), Error> {
+        let (data, _) = self.stored(key, file_type, path)?;
+        let backup = self.backup_of(path);
+        if let Some(parent) = backup.parent() {
+            self.fs
+                .create_dir_all(parent)
+                .map_err(|err| Error::io(parent, err))?;
+        }
+        self.fs
+            .write(&backup, &data, false)
+            .map_err(|err| Error::write(&backup, err))
+    }
+
+    /// Returns the path the backup of a file is kept at.
+    fn backup_of(&self, path: &Path) -> PathBuf {
+        self.root
+            .with_file_name(".backups")
+            .join(format!("v{}", self.version))
+            .join(self.relative(path))
+    }
+
+    /// Sets the content of a plain file with the given key, choosing what happens if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - String to write.
+    /// * `overwrite` - The behavior applied if the file exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn set_plain_with(
+        &self,
+        key: &str,
+        value: impl ToString,
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        let key_path = self.path(key, FileType::Plain)?;
+        self.write_file(
+            key,
+            FileType::Plain,
+            &key_path,
+            value.to_string().as_bytes(),
+            overwrite,
+        )
+    }
+
+    /// Sets the content of a toml file with the given key, choosing what happens if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `overwrite` - The behavior applied if the file exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "toml")]
+    pub fn set_toml_with<T: serde::Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        self.set_as(key, FileType::Toml, value, overwrite)
+    }
+
+    /// Sets the content of a json file with the given key, choosing what happens if it exists.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, OverwriteBehavior};
+    /// # use serde_json::json;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let wallet = json!({ "address": "bc1q..." });
+    /// config.set_json_with("wallet", wallet, OverwriteBehavior::BackupThenOverwrite)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `overwrite` - The behavior applied if the file exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "json")]
+    pub fn set_json_with<T: serde::Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        self.set_as(key, FileType::Json, value, overwrite)
+    }
+
+    /// Sets the content of a ron file with the given key, choosing what happens if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `overwrite` - The behavior applied if the file exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "ron")]
+    pub fn set_ron_with<T: serde::Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        self.set_as(key, FileType::Ron, value, overwrite)
+    }
+
+    /// Sets the content of a yaml file with the given key, choosing what happens if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `overwrite` - The behavior applied if the file exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "yaml")]
+    pub fn set_yaml_with<T: serde::Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        self.set_as(key, FileType::Yaml, value, overwrite)
+    }
+
+    /// Sets the content of an ini file with the given key, choosing what happens if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `overwrite` - The behavior applied if the file exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "ini")]
+    pub fn set_ini_with<T: serde::Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        self.set_as(key, FileType::Ini, value, overwrite)
+    }
+
+    /// Sets the content of a json5 file with the given key, choosing what happens if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be serialized and stored.
+    /// * `overwrite` - The behavior applied if the file exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    #[cfg(feature = "json5")]
+    pub fn set_json5_with<T: serde::Serialize>(
+        &self,
+        key: &str,
+        value: T,
+        overwrite: OverwriteBehavior,
+    ) -> Result<(), Error> {
+        self.set_as(key, FileType::Json5, value, overwrite)
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::OverwriteBehavior;
+    use crate::{utils::FileType, Config};
+
+    #[test]
+    fn backs_up_only_the_replaced_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None)
+            .unwrap()
+            .with_overwrite_behavior(OverwriteBehavior::BackupThenOverwrite);
+        config.set_json("colors", "light").unwrap();
+        config.set_json("fonts", "serif").unwrap();
+        config.set_json("colors", "dark").unwrap();
+
+        let backups = config.root.with_file_name(".backups").join("v1");
+        let files: Vec<_> = std::fs::read_dir(&backups).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        assert!(!config.snapshot_dir().exists());
+
+        config.restore_backup("colors", FileType::Json).unwrap();
+        assert_eq!(config.get_json::<String>("colors").unwrap(), "light");
+        assert!(config.restore_backup("fonts", FileType::Json).is_err());
+    }
+}
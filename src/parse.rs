@@ -0,0 +1,180 @@
+#[cfg(feature = "json")]
+use std::{path::Path, sync::Arc};
+
+use crate::Config;
+#[cfg(feature = "json")]
+use crate::{duplicates, utils::FileType, DuplicateKey, DuplicateKeys, Error};
+
+/// A callback registered with [`Config::on_duplicate_key`].
+#[cfg(feature = "json")]
+type DuplicateHook = Arc<dyn Fn(&DuplicateKey) + Send + Sync>;
+
+/// Options applied when files are parsed, set through the `Config` builder methods.
+#[derive(Clone, Default)]
+pub(crate) struct ParseOptions {
+    /// The options ron files are read with.
+    #[cfg(feature = "ron")]
+    pub(crate) ron: ron::Options,
+    /// Whether json files may contain comments and trailing commas.
+    #[cfg(feature = "json")]
+    pub(crate) jsonc: bool,
+    /// What happens when a json object repeats a key.
+    #[cfg(feature = "json")]
+    duplicate_keys: DuplicateKeys,
+    /// The hooks called for repeated keys.
+    #[cfg(feature = "json")]
+    duplicate_hooks: Vec<DuplicateHook>,
+}
+
+#[cfg(feature = "json")]
+impl ParseOptions {
+    /// Looks for keys repeated within the objects of a json document.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key the document is stored under.
+    /// * `path` - The path of the file.
+    /// * `data` - The json document.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, or `Error::DuplicateKey` if a key is repeated and
+    /// duplicates are rejected.
+    pub(crate) fn check_duplicates(&self, key: &str, path: &Path, data: &str) -> Result<(), Error> {
+        if self.duplicate_keys == DuplicateKeys::Ignore {
+            return Ok(());
+        }
+        for field in duplicates::find(data) {
+            if self.duplicate_keys == DuplicateKeys::Reject {
+                return Err(Error::DuplicateKey {
+                    key: key.to_string(),
+                    file_type: FileType::Json,
+                    path: path.to_path_buf(),
+                    field,
+                });
+            }
+            tracing::warn!(key, path = %path.display(), field, "found duplicate key, keeping the last value");
+            let duplicate = DuplicateKey {
+                key,
+                file_type: FileType::Json,
+                path,
+                field: &field,
+            };
+            for hook in &self.duplicate_hooks {
+                hook(&duplicate);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Sets whether json files may contain comments and trailing commas.
+    ///
+    /// When enabled, `//` and `/* */` comments and commas before a closing bracket are ignored
+    /// when json files are read, as in the settings files of VS Code. Files are always
+    /// written as strict json, so comments are lost once a file is written.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?.with_jsonc(true);
+    /// let colors: serde_json::Value = config.get_json("colors")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether comments and trailing commas are accepted.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    #[cfg(feature = "json")]
+    pub fn with_jsonc(mut self, enabled: bool) -> Self {
+        self.parse.jsonc = enabled;
+        self
+    }
+
+    /// Sets what happens when a json file repeats a key within an object.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, DuplicateKeys};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?
+    ///     .with_duplicate_keys(DuplicateKeys::Reject);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `duplicate_keys` - Whether repeated keys are ignored, reported or rejected.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    #[cfg(feature = "json")]
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.parse.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Registers a hook invoked for every repeated key found with [`DuplicateKeys::Warn`].
+    ///
+    /// ```no_run
+    /// # use libset::{Config, DuplicateKeys};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let mut config = Config::new("org.example.Demo", 1, None)?
+    ///     .with_duplicate_keys(DuplicateKeys::Warn);
+    /// config.on_duplicate_key(|duplicate| {
+    ///     eprintln!("{} is set twice in {}", duplicate.field, duplicate.path.display());
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - Function invoked with each repeated key.
+    #[cfg(feature = "json")]
+    pub fn on_duplicate_key<F>(&mut self, hook: F)
+    where
+        F: Fn(&DuplicateKey) + Send + Sync + 'static,
+    {
+        self.parse.duplicate_hooks.push(Arc::new(hook));
+    }
+
+    /// Sets the options ron files are read with.
+    ///
+    /// Enable extensions here to accept hand-written files using them without a
+    /// `#![enable(...)]` attribute. Files are always written without extensions.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # use ron::extensions::Extensions;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?.with_ron_options(
+    ///     ron::Options::default()
+    ///         .with_default_extension(Extensions::IMPLICIT_SOME)
+    ///         .with_default_extension(Extensions::UNWRAP_NEWTYPES)
+    ///         .with_default_extension(Extensions::UNWRAP_VARIANT_NEWTYPES),
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The ron options, including the default extensions.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    #[cfg(feature = "ron")]
+    pub fn with_ron_options(mut self, options: ron::Options) -> Self {
+        self.parse.ron = options;
+        self
+    }
+}
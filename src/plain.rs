@@ -0,0 +1,111 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{utils::FileType, Config, Error};
+
+/// How the typed plain file helpers, such as [`Config::get_plain_parsed`], treat whitespace.
+///
+/// By default values are read without their surrounding whitespace and written with a
+/// trailing newline, as most Unix tools expect of single-value files.
+///
+/// ```no_run
+/// # use libset::{Config, PlainPolicy};
+/// # fn main() -> Result<(), libset::Error> {
+/// let config = Config::new("org.example.Daemon", 1, None)?.with_plain_policy(PlainPolicy {
+///     trim: true,
+///     trailing_newline: false,
+/// });
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlainPolicy {
+    /// Whether surrounding whitespace is removed before the value is parsed.
+    pub trim: bool,
+    /// Whether a newline is appended to written values that don't end with one.
+    pub trailing_newline: bool,
+}
+
+impl Default for PlainPolicy {
+    fn default() -> Self {
+        Self {
+            trim: true,
+            trailing_newline: true,
+        }
+    }
+}
+
+impl Config {
+    /// Sets how the typed plain file helpers treat whitespace.
+    ///
+    /// `get_plain` and `set_plain` keep returning and writing the contents unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The whitespace rules.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_plain_policy(mut self, policy: PlainPolicy) -> Self {
+        self.plain_policy = policy;
+        self
+    }
+
+    /// Gets the content of a plain file with the given key and parses it into a type.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Daemon", 1, None)?;
+    /// config.set_plain_display("pid", std::process::id())?;
+    /// let pid: u32 = config.get_plain_parsed("pid")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed value, `Error::PlainValue` if the contents could not be
+    /// parsed, or another `Error` if the file could not be read.
+    pub fn get_plain_parsed<T>(&self, key: &str) -> Result<T, Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let data = self.get_plain(key)?;
+        let value = if self.plain_policy.trim {
+            data.trim()
+        } else {
+            &data
+        };
+        value.parse().or_else(|err: T::Err| {
+            Err(Error::PlainValue {
+                key: key.to_string(),
+                path: self.resolve(key, FileType::Plain)?,
+                reason: err.to_string(),
+            })
+        })
+    }
+
+    /// Sets the content of a plain file with the given key to a value's display form.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `value` - The value to be formatted and stored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn set_plain_display(&self, key: &str, value: impl Display) -> Result<(), Error> {
+        let mut data = value.to_string();
+        if self.plain_policy.trailing_newline && !data.ends_with('\n') {
+            data.push('\n');
+        }
+        self.set_plain(key, data)
+    }
+}
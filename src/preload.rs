@@ -0,0 +1,106 @@
+use std::{
+    future::Future,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    cache::{Cache, Stamp},
+    filesystem::Filesystem,
+    Error, LogPolicy,
+};
+
+#[derive(Default)]
+struct State {
+    result: Option<Result<(), Error>>,
+    waker: Option<Waker>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    done: Condvar,
+}
+
+/// Handle to files being preloaded in the background.
+///
+/// Use [`Preload::wait`] to block until loading completes, or `.await` the handle.
+/// Dropping the handle lets loading continue unobserved.
+pub struct Preload {
+    shared: Arc<Shared>,
+}
+
+impl Preload {
+    pub(crate) fn spawn(
+        cache: Arc<Cache>,
+        fs: Arc<dyn Filesystem>,
+        paths: Vec<PathBuf>,
+        log_policy: LogPolicy,
+    ) -> Self {
+        let shared = Arc::new(Shared::default());
+        let worker = shared.clone();
+        std::thread::spawn(move || {
+            let mut result = Ok(());
+            for path in paths {
+                let stamp = Stamp::of(fs.as_ref(), &path);
+                match fs.read(&path) {
+                    Ok(data) => {
+                        cache.insert_raw(&path, stamp, data);
+                        log_policy.log("preloaded file", None, &path);
+                    }
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(err) => {
+                        if result.is_ok() {
+                            result = Err(Error::io(&path, err));
+                        }
+                    }
+                }
+            }
+            let mut state = worker.state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+            worker.done.notify_all();
+        });
+        Self { shared }
+    }
+
+    /// Determines if every file has been loaded.
+    pub fn is_finished(&self) -> bool {
+        self.shared.state.lock().unwrap().result.is_some()
+    }
+
+    /// Blocks until every file has been loaded.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or the first `Error` encountered. Missing files are not
+    /// considered errors.
+    pub fn wait(self) -> Result<(), Error> {
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            if let Some(result) = state.result.take() {
+                return result;
+            }
+            state = self.shared.done.wait(state).unwrap();
+        }
+    }
+}
+
+impl Future for Preload {
+    type Output = Result<(), Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::env::VarError;
+
+use crate::{Config, Error};
+
+/// Environment variable read by [`Config::new_with_profile`] unless another one is given.
+pub const PROFILE_VARIABLE: &str = "APP_ENV";
+
+impl Config {
+    /// Creates a new `Config` object using the profile named by an environment variable.
+    ///
+    /// When the variable is set, such as `APP_ENV=staging`, the configuration moves into the
+    /// scope of the same name and falls back to the shared settings for reads, see
+    /// [`Config::scoped_with_fallback`]. When it is unset or empty, the shared settings are used
+    /// directly, so development, staging and production builds of a service pick their
+    /// settings without code branches.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, PROFILE_VARIABLE};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// // Reads staging/database.json when started with APP_ENV=staging.
+    /// let config = Config::new_with_profile("org.example.Service", 1, PROFILE_VARIABLE)?;
+    /// let url: String = config.get_json("database")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the application.
+    /// * `version` - The version of the configuration.
+    /// * `variable` - The environment variable naming the profile, usually [`PROFILE_VARIABLE`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `Config` object or an `Error` if the variable is not valid
+    /// unicode, the profile is not a valid scope name or the directories could not be created.
+    pub fn new_with_profile(name: &str, version: u64, variable: &str) -> Result<Self, Error> {
+        let config = Self::new(name, version, None)?;
+        match std::env::var(variable) {
+            Ok(profile) if !profile.is_empty() => config.scoped_with_fallback(&profile),
+            Ok(_) | Err(VarError::NotPresent) => Ok(config),
+            Err(err) => Err(Error::Env {
+                variable: variable.to_string(),
+                reason: err.to_string(),
+            }),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{hooks::EventKind, Config, Entry, Error};
+
+impl Config {
+    /// Removes the files that were not modified within a duration.
+    ///
+    /// Only files in the configuration path and its nested scopes are considered, so a
+    /// `Config` scoped to a cache directory never touches the rest of the store. Use `filter`
+    /// to narrow this down further, for example to keys starting with a prefix. Files whose
+    /// modification time is not reported by the platform are kept.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let thumbnails = Config::new("org.example.Demo", 1, Some("thumbnails"))?;
+    /// let week = Duration::from_secs(7 * 24 * 60 * 60);
+    /// let removed = thumbnails.prune_older_than(week, |entry| entry.key.starts_with("thumb-"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `age` - How long ago a file must have been modified last to be removed.
+    /// * `filter` - The predicate a file must match to be removed.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the removed entries or an `Error` if an error occurred.
+    pub fn prune_older_than<F>(&self, age: Duration, mut filter: F) -> Result<Vec<Entry>, Error>
+    where
+        F: FnMut(&Entry) -> bool,
+    {
+        self.check_sealed()?;
+        self.flush()?;
+        let now = SystemTime::now();
+        let mut removed = Vec::new();
+        for entry in self.walk()? {
+            let entry = entry?;
+            let expired = entry
+                .modified
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|elapsed| elapsed > age);
+            if !expired || !entry.path.starts_with(&self.path) || !filter(&entry) {
+                continue;
+            }
+            self.cache.invalidate(&entry.path);
+            match self.fs.remove(&entry.path) {
+                Ok(()) => {}
+                // Another process removed it first.
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(Error::for_key(
+                        &entry.key,
+                        entry.file_type,
+                        &entry.path,
+                        err,
+                    ))
+                }
+            }
+            self.emit(EventKind::Remove, &entry.key, entry.file_type, &entry.path);
+            self.log("removed file", &entry.key, &entry.path);
+            removed.push(entry);
+        }
+        Ok(removed)
+    }
+}
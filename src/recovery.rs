@@ -0,0 +1,226 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use tracing::warn;
+
+use crate::{
+    entry::{is_temporary_file, ATOMIC_WRITE_PREFIX},
+    filesystem::Filesystem,
+    strategy::{backup_target, interrupted, Interrupted},
+    Config, Error,
+};
+
+/// How long a temporary file must be left untouched before it is considered abandoned.
+///
+/// Writes finish within milliseconds, so anything older was left behind by a crashed process
+/// rather than being written by another running instance.
+const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+impl Config {
+    /// Removes files left behind by processes that crashed while writing.
+    ///
+    /// Batches whose commit was interrupted are completed first when they were journaled, see
+    /// [`Config::with_journal`]. Then the temporary files and directories created by atomic
+    /// and batched writes are removed once they have been abandoned for a while, as well as,
+    /// on Unix, the sockets of [subscriptions](Config::subscribe) whose process is gone.
+    /// Abandoned backups of [in-place writes](crate::WriteStrategy::DirectWithBackup) are
+    /// restored over their file when it is missing or was cut short, and removed otherwise.
+    /// Every replayed journal and removed file is logged. [`Config::new`] does this
+    /// automatically, use [`Config::new_without_recovery`] to opt out.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the paths that were removed or an `Error` if the store could not
+    /// be scanned.
+    pub fn recover(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut removed = Vec::new();
+        let now = SystemTime::now();
+        self.replay_journals(now)?;
+        remove_stale(self.fs.as_ref(), &self.root, now, &mut removed)?;
+        #[cfg(unix)]
+        self.remove_stale_sockets(&mut removed);
+        for path in &removed {
+            self.log_policy.log("removed stale file", None, path);
+        }
+        Ok(removed)
+    }
+
+    /// Runs [`Config::recover`], logging failures instead of returning them.
+    #[cfg(feature = "std-fs")]
+    pub(crate) fn recover_quietly(self) -> Self {
+        if let Err(err) = self.recover() {
+            warn!(error = %err, "failed to remove stale files");
+        }
+        self
+    }
+}
+
+/// Removes abandoned temporary files and directories under a directory and its scopes, and
+/// restores abandoned backups.
+fn remove_stale(
+    fs: &dyn Filesystem,
+    dir: &Path,
+    now: SystemTime,
+    removed: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    let stale = |path: &Path| is_stale(fs, path, now);
+    for path in fs.list_dirs(dir).map_err(|err| Error::io(dir, err))? {
+        let name = file_name(&path);
+        if name.starts_with(ATOMIC_WRITE_PREFIX) {
+            if stale(&path) && fs.remove_dir_all(&path).is_ok() {
+                removed.push(path);
+            }
+        } else if !name.starts_with('.') {
+            remove_stale(fs, &path, now, removed)?;
+        }
+    }
+    for path in fs.list(dir).map_err(|err| Error::io(dir, err))? {
+        let name = file_name(&path);
+        if let Some(target) = backup_target(&name) {
+            if stale(&path) && restore_backup(fs, &path, &path.with_file_name(target)) {
+                removed.push(path);
+            }
+        } else if is_temporary_file(&name) && stale(&path) && fs.remove(&path).is_ok() {
+            removed.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Determines if a file was left untouched for longer than [`STALE_AFTER`].
+pub(crate) fn is_stale(fs: &dyn Filesystem, path: &Path, now: SystemTime) -> bool {
+    fs.metadata(path)
+        .ok()
+        .and_then(|metadata| metadata.modified)
+        .and_then(|modified| now.duration_since(modified).ok())
+        .is_some_and(|age| age >= STALE_AFTER)
+}
+
+/// Returns the file name of a path, lossily converted.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Restores an abandoned backup over its file if the write that made it was interrupted,
+/// returning whether the backup was removed.
+fn restore_backup(fs: &dyn Filesystem, backup: &Path, target: &Path) -> bool {
+    let Ok(contents) = fs.read(backup) else {
+        return false;
+    };
+    let current = match fs.read(target) {
+        Ok(current) => Some(current),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(_) => return false,
+    };
+    match interrupted(&contents, current.as_deref()) {
+        Some(Interrupted::Incomplete(previous)) => {
+            fs.write(target, previous, false).is_ok() && fs.remove(backup).is_ok()
+        }
+        Some(Interrupted::Completed | Interrupted::Replaced) => fs.remove(backup).is_ok(),
+        None => {
+            warn!(backup = %backup.display(), "ignoring damaged backup");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        path::Path,
+        time::{Duration, SystemTime},
+    };
+
+    use crate::{entry::temporary_name, strategy::backup_path, Config, WriteStrategy};
+
+    /// Makes a file look abandoned by moving its modification time into the past.
+    fn age(path: &Path) {
+        let old = SystemTime::now() - Duration::from_secs(60 * 60);
+        File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(old))
+            .unwrap();
+    }
+
+    #[test]
+    fn removes_only_abandoned_temporary_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        config.set_plain(".notes.tmp", "keep me").unwrap();
+        let temporary = config.path.join(temporary_name("notes", 0));
+        std::fs::write(&temporary, "partial").unwrap();
+        age(&config.path.join(".notes.tmp"));
+        age(&temporary);
+
+        let removed = config.recover().unwrap();
+
+        assert_eq!(removed, vec![temporary.clone()]);
+        assert!(!temporary.exists());
+        assert_eq!(config.get_plain(".notes.tmp").unwrap(), "keep me");
+        assert!(config.keys().unwrap().contains(&".notes.tmp".to_string()));
+    }
+
+    #[test]
+    fn rejects_reserved_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        assert!(config
+            .set_plain(&temporary_name("notes", 0), "data")
+            .is_err());
+    }
+
+    #[test]
+    fn ignores_keys_shaped_like_old_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        config.set_plain("profile", "current").unwrap();
+        config.set_plain(".profile.bak", "old").unwrap();
+        config.set_plain(".bak", "data").unwrap();
+        age(&config.path.join(".profile.bak"));
+        age(&config.path.join(".bak"));
+
+        assert!(config.recover().unwrap().is_empty());
+        assert_eq!(config.get_plain("profile").unwrap(), "current");
+        assert_eq!(config.get_plain(".profile.bak").unwrap(), "old");
+    }
+
+    #[test]
+    fn restores_backups_of_interrupted_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None)
+            .unwrap()
+            .with_write_strategy(WriteStrategy::DirectWithBackup);
+        config.set_plain("profile", "old").unwrap();
+        config.set_plain("profile", "new contents").unwrap();
+        let path = config.path.join("profile");
+        let backup = backup_path(&path);
+
+        // Interrupted before the new contents were fully written.
+        std::fs::write(
+            &backup,
+            format!("12 {}\nold", crate::manifest::hash(b"new contents")),
+        )
+        .unwrap();
+        std::fs::write(&path, "new").unwrap();
+        age(&backup);
+        assert_eq!(config.recover().unwrap(), vec![backup.clone()]);
+        assert_eq!(config.get_plain("profile").unwrap(), "old");
+
+        // Interrupted after the new contents were written, before the backup was removed.
+        std::fs::write(
+            &backup,
+            format!("12 {}\nold", crate::manifest::hash(b"new contents")),
+        )
+        .unwrap();
+        std::fs::write(&path, "new contents").unwrap();
+        age(&backup);
+        assert_eq!(config.recover().unwrap(), vec![backup.clone()]);
+        assert_eq!(config.get_plain("profile").unwrap(), "new contents");
+    }
+}
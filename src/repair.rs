@@ -0,0 +1,179 @@
+use crate::{deserialize, utils::FileType, Config, Error, OverwriteBehavior};
+use serde::de::IgnoredAny;
+
+/// A kind of damage fixed by [`Config::try_repair`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Repair {
+    /// A comma before a closing bracket was removed.
+    TrailingComma,
+    /// A control character inside a string was escaped.
+    ControlCharacter,
+    /// Data after the end of the document was removed.
+    TrailingGarbage,
+}
+
+impl Config {
+    /// Attempts to fix common damage in a json or toml file.
+    ///
+    /// Trailing commas before a closing bracket, unescaped control characters inside strings
+    /// and leftover data after the end of the document, such as the NUL padding some
+    /// filesystems leave behind after a crash, are fixed. The file is only rewritten when the
+    /// repaired document parses, otherwise it is left untouched.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, FileType};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// if config.get_json::<serde_json::Value>("colors").is_err() {
+    ///     let repairs = config.try_repair("colors", FileType::Json)?;
+    ///     println!("fixed {repairs:?}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key used to store the file.
+    /// * `file_type` - The file extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the repairs that were made, empty if the file was already
+    /// valid, or the original parse `Error` if it could not be repaired.
+    pub fn try_repair(&self, key: &str, file_type: FileType) -> Result<Vec<Repair>, Error> {
+        let path = self.resolve(key, file_type)?;
+        let data = self.read_file(key, file_type, &path)?;
+        let Err(err) = deserialize::<IgnoredAny>(key, file_type, &path, &data, &self.parse) else {
+            return Ok(Vec::new());
+        };
+        let format = file_type.to_string();
+        if format != "json" && format != "toml" {
+            return Err(err);
+        }
+
+        let mut repairs = Vec::new();
+        let repaired = repair(&data, format == "toml", &mut repairs);
+        if repairs.is_empty()
+            || deserialize::<IgnoredAny>(key, file_type, &path, &repaired, &self.parse).is_err()
+        {
+            return Err(err);
+        }
+        let target = self.path(key, file_type)?;
+        self.write_file(
+            key,
+            file_type,
+            &target,
+            repaired.as_bytes(),
+            OverwriteBehavior::AllowOverwrite,
+        )?;
+        self.log(&format!("repaired file ({repairs:?})"), key, &target);
+        Ok(repairs)
+    }
+}
+
+/// Records a repair once.
+fn record(repairs: &mut Vec<Repair>, repair: Repair) {
+    if !repairs.contains(&repair) {
+        repairs.push(repair);
+    }
+}
+
+/// Fixes trailing commas, control characters in strings and data after the document.
+fn repair(data: &str, toml: bool, repairs: &mut Vec<Repair>) -> String {
+    let trimmed = data.trim_end_matches('\0');
+    if trimmed.len() != data.len() {
+        record(repairs, Repair::TrailingGarbage);
+    }
+
+    let mut output = String::with_capacity(trimmed.len());
+    let mut depth = 0usize;
+    let mut chars = trimmed.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' if toml && trimmed[index..].starts_with("\"\"\"") => {
+                let end = trimmed[index + 3..]
+                    .find("\"\"\"")
+                    .map_or(trimmed.len(), |end| index + 6 + end);
+                output.push_str(&trimmed[index..end]);
+                while chars.next_if(|(next, _)| *next < end).is_some() {}
+            }
+            '\'' if toml => {
+                let delimiter = if trimmed[index..].starts_with("'''") {
+                    "'''"
+                } else {
+                    "'"
+                };
+                let start = index + delimiter.len();
+                let end = trimmed[start..]
+                    .find(delimiter)
+                    .map_or(trimmed.len(), |end| start + end + delimiter.len());
+                output.push_str(&trimmed[index..end]);
+                while chars.next_if(|(next, _)| *next < end).is_some() {}
+            }
+            '"' => {
+                output.push(c);
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            output.push(c);
+                            if let Some((_, escaped)) = chars.next() {
+                                output.push(escaped);
+                            }
+                        }
+                        '"' => {
+                            output.push(c);
+                            break;
+                        }
+                        // A line break ends a TOML string, the document is not just damaged.
+                        '\n' if toml => {
+                            output.push(c);
+                            break;
+                        }
+                        '\t' if toml => output.push(c),
+                        c if (c as u32) < 0x20 => {
+                            record(repairs, Repair::ControlCharacter);
+                            match c {
+                                '\n' => output.push_str("\\n"),
+                                '\r' => output.push_str("\\r"),
+                                '\t' => output.push_str("\\t"),
+                                c => output.push_str(&format!("\\u{:04x}", c as u32)),
+                            }
+                        }
+                        c => output.push(c),
+                    }
+                }
+            }
+            '#' if toml => {
+                output.push(c);
+                while let Some((_, c)) = chars.next_if(|(_, c)| *c != '\n') {
+                    output.push(c);
+                }
+            }
+            ',' => {
+                let next = trimmed[index + 1..].trim_start().chars().next();
+                let closes = matches!(next, Some('}')) || (!toml && matches!(next, Some(']')));
+                if closes {
+                    record(repairs, Repair::TrailingComma);
+                } else {
+                    output.push(c);
+                }
+            }
+            '[' | '{' => {
+                depth += 1;
+                output.push(c);
+            }
+            ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                output.push(c);
+                // Anything after the closing bracket of a json document is left over.
+                if !toml && depth == 0 && !trimmed[index + 1..].trim().is_empty() {
+                    record(repairs, Repair::TrailingGarbage);
+                    break;
+                }
+            }
+            c => output.push(c),
+        }
+    }
+    output
+}
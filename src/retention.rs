@@ -0,0 +1,162 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime},
+};
+
+use crate::{manifest::Manifest, Config, Error};
+
+/// Rules deciding which snapshots are kept.
+///
+/// A snapshot is kept when any rule keeps it, and the newest snapshot is always kept. The
+/// default policy keeps every snapshot.
+///
+/// ```
+/// use std::time::Duration;
+/// use libset::Retention;
+///
+/// // Keep the 5 most recent snapshots, and any taken in the last 30 days.
+/// let retention = Retention::default()
+///     .keep_last(Some(5))
+///     .keep_within(Some(Duration::from_secs(30 * 24 * 60 * 60)));
+/// assert!(!retention.keeps_everything());
+/// assert!(Retention::default().keeps_everything());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Retention {
+    keep_last: Option<usize>,
+    keep_within: Option<Duration>,
+}
+
+impl Retention {
+    /// Keeps the given number of most recent snapshots, `None` disables the rule.
+    pub fn keep_last(mut self, count: Option<usize>) -> Self {
+        self.keep_last = count;
+        self
+    }
+
+    /// Keeps the snapshots taken within the given duration, `None` disables the rule.
+    pub fn keep_within(mut self, age: Option<Duration>) -> Self {
+        self.keep_within = age;
+        self
+    }
+
+    /// Determines if no rule is set, so no snapshot is ever removed.
+    pub fn keeps_everything(&self) -> bool {
+        self.keep_last.is_none() && self.keep_within.is_none()
+    }
+
+    /// Determines if the snapshot at the given position, newest first, is kept.
+    fn keeps(&self, position: usize, age: Duration) -> bool {
+        position == 0
+            || self.keep_last.is_some_and(|count| position < count)
+            || self.keep_within.is_some_and(|within| age <= within)
+    }
+}
+
+impl Config {
+    /// Sets the policy applied to snapshots after each [`Config::snapshot_cas`].
+    ///
+    /// ```no_run
+    /// # use libset::{Config, Retention};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?
+    ///     .with_retention(Retention::default().keep_last(Some(5)));
+    /// config.snapshot_cas()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `retention` - The rules deciding which snapshots are kept.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_retention(mut self, retention: Retention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Removes the snapshots the retention policy no longer keeps.
+    ///
+    /// Blobs that are not referenced by any remaining snapshot are removed as well. This runs
+    /// automatically after each [`Config::snapshot_cas`], see [`Config::with_retention`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the ids of the removed snapshots or an `Error` if an error
+    /// occurred.
+    pub fn apply_retention(&self) -> Result<Vec<String>, Error> {
+        if self.retention.keeps_everything() {
+            return Ok(Vec::new());
+        }
+        let manifests = self.manifests()?;
+        let now = SystemTime::now();
+        let mut removed = Vec::new();
+        let mut referenced = HashSet::new();
+        for (position, (modified, path)) in manifests.into_iter().enumerate() {
+            let age = now.duration_since(modified).unwrap_or_default();
+            if self.retention.keeps(position, age) {
+                let text = self.fs.read(&path).map_err(|err| Error::io(&path, err))?;
+                let manifest: Manifest = String::from_utf8_lossy(&text).parse()?;
+                referenced.extend(manifest.files().map(|(_, digest)| digest.to_string()));
+            } else {
+                self.fs.remove(&path).map_err(|err| Error::io(&path, err))?;
+                self.log_policy.log("removed snapshot", None, &path);
+                removed.push(
+                    path.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into(),
+                );
+            }
+        }
+        if !removed.is_empty() {
+            self.remove_unreferenced_blobs(&referenced)?;
+        }
+        Ok(removed)
+    }
+
+    /// Removes the blobs whose hash is not in the given set.
+    fn remove_unreferenced_blobs(&self, referenced: &HashSet<String>) -> Result<(), Error> {
+        let dir = self.snapshot_dir().join("blobs");
+        let Ok(prefixes) = self.fs.list_dirs(&dir) else {
+            return Ok(());
+        };
+        for prefix in prefixes {
+            let Ok(blobs) = self.fs.list(&prefix) else {
+                continue;
+            };
+            let mut kept = false;
+            for blob in blobs {
+                let name = blob.file_name().unwrap_or_default().to_string_lossy();
+                if name.starts_with('.') {
+                    kept = true;
+                    continue;
+                }
+                let digest = format!(
+                    "{}{}",
+                    prefix.file_name().unwrap_or_default().to_string_lossy(),
+                    name
+                );
+                if referenced.contains(&digest) {
+                    kept = true;
+                } else {
+                    self.fs.remove(&blob).map_err(|err| Error::io(&blob, err))?;
+                }
+            }
+            // Directories inside hold blobs still being written.
+            let busy = self
+                .fs
+                .list_dirs(&prefix)
+                .map_or(true, |dirs| !dirs.is_empty());
+            if !kept && !busy {
+                self.fs
+                    .remove_dir_all(&prefix)
+                    .map_err(|err| Error::io(&prefix, err))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use std::{path::PathBuf, sync::atomic::Ordering};
+
+use crate::{entry::SEAL_MARKER, Config, Error};
+
+impl Config {
+    /// Makes this instance read-only, rejecting writes and removals with [`Error::Sealed`].
+    ///
+    /// Other instances keep writing, use [`Config::seal_store`] to stop them too. Reads,
+    /// snapshots and exports are unaffected.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Kiosk", 1, None)?;
+    /// config.seal();
+    /// assert!(config.set_plain("session", "42").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn seal(&self) {
+        self.sealed.store(true, Ordering::SeqCst);
+    }
+
+    /// Makes the whole store read-only, for this and every other instance of the application.
+    ///
+    /// A hidden marker file is written to the version root and checked before every write, so
+    /// the store stays sealed across restarts until [`Config::unseal`] is called. Pending
+    /// write-behind changes are flushed first.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// config.seal_store()?;
+    /// let id = config.snapshot_cas()?;
+    /// config.unseal()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the marker could not be written.
+    pub fn seal_store(&self) -> Result<(), Error> {
+        self.flush()?;
+        let marker = self.seal_marker();
+        self.fs
+            .write(&marker, b"", false)
+            .map_err(|err| Error::io(&marker, err))?;
+        self.seal();
+        self.log_policy.log("sealed store", None, &marker);
+        Ok(())
+    }
+
+    /// Allows writes again, removing the store marker left by [`Config::seal_store`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the marker could not be removed.
+    pub fn unseal(&self) -> Result<(), Error> {
+        let marker = self.seal_marker();
+        match self.fs.remove(&marker) {
+            Ok(()) => self.log_policy.log("unsealed store", None, &marker),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(Error::io(&marker, err)),
+        }
+        self.sealed.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Determines if writes are rejected, because this instance or the store is sealed.
+    pub fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::SeqCst) || self.fs.exists(&self.seal_marker())
+    }
+
+    /// Fails with `Error::Sealed` if writes are rejected.
+    pub(crate) fn check_sealed(&self) -> Result<(), Error> {
+        if self.is_sealed() {
+            return Err(Error::Sealed {
+                path: self.root.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the path of the marker sealing the store.
+    fn seal_marker(&self) -> PathBuf {
+        self.root.join(SEAL_MARKER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Archive, Config, Error};
+
+    #[test]
+    fn sealed_stores_reject_imports_and_restores() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        config.set_plain("session", "42").unwrap();
+        let id = config.snapshot_cas().unwrap();
+        let mut exported = Vec::new();
+        config.export_archive(&mut exported).unwrap();
+        let archive = Archive::read(exported.as_slice()).unwrap();
+        config.set_plain("session", "43").unwrap();
+
+        config.seal();
+        assert!(matches!(
+            config.import_archive(&archive),
+            Err(Error::Sealed { .. })
+        ));
+        assert!(matches!(config.restore(&id), Err(Error::Sealed { .. })));
+        config.unseal().unwrap();
+        assert_eq!(config.get_plain("session").unwrap(), "43");
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A value that is redacted when printed and wiped from memory when dropped.
+///
+/// The value can only be read through [`Secret::expose`], which makes every use easy to find
+/// when auditing code. Read and write secrets with [`Config::get_secret_json`] and
+/// [`Config::set_secret_json`], which also restrict the permissions of the stored file.
+///
+/// [`Config::get_secret_json`]: crate::Config::get_secret_json
+/// [`Config::set_secret_json`]: crate::Config::set_secret_json
+///
+/// ```
+/// use libset::Secret;
+///
+/// let token = Secret::new(String::from("hunter2"));
+/// assert_eq!(format!("{token:?}"), "Secret([REDACTED])");
+/// assert_eq!(token.expose(), "hunter2");
+/// ```
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wraps a value.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns a reference to the value.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns a mutable reference to the value.
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for Secret<T> {}
+
+#[cfg(feature = "json")]
+mod json {
+    use serde::{de::DeserializeOwned, Serialize};
+    use zeroize::{Zeroize, Zeroizing};
+
+    use super::Secret;
+    use crate::{deserialize, hooks::EventKind, utils::FileType, Config, Error};
+
+    impl Config {
+        /// Gets a json file holding a secret and deserializes it into a [`Secret`].
+        ///
+        /// The text read from disk is wiped as soon as it is deserialized. Structs can be
+        /// stored as secrets by implementing `Zeroize`, which the `zeroize` crate can derive.
+        /// Add an encryption middleware with [`Config::add_middleware`] to also keep the file
+        /// encrypted at rest.
+        ///
+        /// ```no_run
+        /// # use libset::{Config, Secret};
+        /// # fn main() -> Result<(), libset::Error> {
+        /// let config = Config::new("org.example.Demo", 1, None)?;
+        /// config.set_secret_json("api-token", "hunter2")?;
+        /// let token: Secret<String> = config.get_secret_json("api-token")?;
+        /// assert_eq!(token.expose(), "hunter2");
+        /// # Ok(())
+        /// # }
+        /// ```
+        ///
+        /// # Arguments
+        ///
+        /// * `key` - The key used to store the file.
+        ///
+        /// # Returns
+        ///
+        /// A `Result` containing the secret or an `Error` if an error occurred.
+        pub fn get_secret_json<T>(&self, key: &str) -> Result<Secret<T>, Error>
+        where
+            T: DeserializeOwned + Zeroize,
+        {
+            let key_path = self.resolve(key, FileType::Json)?;
+            let data = Zeroizing::new(self.read_file(key, FileType::Json, &key_path)?);
+            let value = deserialize(key, FileType::Json, &key_path, &data, &self.parse)?;
+            self.log("read file", key, &key_path);
+            Ok(Secret::new(value))
+        }
+
+        /// Serializes a secret into a json file only readable by the current user.
+        ///
+        /// The file is written with [`Filesystem::write_private`], on the local filesystem it
+        /// is created with mode `0600` on Unix before any data is written to it. The serialized
+        /// text is wiped once stored. Secrets bypass write-behind mode, see
+        /// [`Config::with_write_behind`].
+        ///
+        /// [`Filesystem::write_private`]: crate::Filesystem::write_private
+        ///
+        /// # Arguments
+        ///
+        /// * `key` - The key used to store the file.
+        /// * `value` - The value to be serialized and stored.
+        ///
+        /// # Returns
+        ///
+        /// A `Result` indicating success or an `Error` if an error occurred.
+        pub fn set_secret_json<T: Serialize + ?Sized>(
+            &self,
+            key: &str,
+            value: &T,
+        ) -> Result<(), Error> {
+            self.check_sealed()?;
+            let key_path = self.path(key, FileType::Json)?;
+            let data =
+                Zeroizing::new(self.serialize_value(key, FileType::Json, &key_path, value)?);
+            self.emit(EventKind::BeforeWrite, key, FileType::Json, &key_path);
+            if let Some(flusher) = &self.flusher {
+                flusher.cancel(&key_path);
+            }
+            self.cache.invalidate(&key_path);
+            let data =
+                Zeroizing::new(self.encode(key, FileType::Json, &key_path, data.as_bytes())?);
+            self.fs
+                .write_private(&key_path, &data, false)
+                .map_err(|err| Error::write(&key_path, err))?;
+            self.emit(EventKind::AfterWrite, key, FileType::Json, &key_path);
+            self.log("wrote file", key, &key_path);
+            Ok(())
+        }
+    }
+}
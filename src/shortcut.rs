@@ -0,0 +1,308 @@
+use std::{fmt, str::FromStr};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::units::ParseValueError;
+
+/// Keys with a name, in their canonical spelling followed by accepted aliases.
+const NAMED_KEYS: &[(ShortcutKey, &[&str])] = &[
+    (ShortcutKey::Enter, &["Enter", "Return"]),
+    (ShortcutKey::Escape, &["Escape", "Esc"]),
+    (ShortcutKey::Tab, &["Tab"]),
+    (ShortcutKey::Space, &["Space"]),
+    (ShortcutKey::Backspace, &["Backspace"]),
+    (ShortcutKey::Delete, &["Delete", "Del"]),
+    (ShortcutKey::Insert, &["Insert", "Ins"]),
+    (ShortcutKey::Home, &["Home"]),
+    (ShortcutKey::End, &["End"]),
+    (ShortcutKey::PageUp, &["PageUp", "PgUp"]),
+    (ShortcutKey::PageDown, &["PageDown", "PgDn"]),
+    (ShortcutKey::Up, &["Up", "ArrowUp"]),
+    (ShortcutKey::Down, &["Down", "ArrowDown"]),
+    (ShortcutKey::Left, &["Left", "ArrowLeft"]),
+    (ShortcutKey::Right, &["Right", "ArrowRight"]),
+    (ShortcutKey::Char('+'), &["Plus"]),
+];
+
+/// The modifier keys held down for a [`Shortcut`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    /// The Control key.
+    pub ctrl: bool,
+    /// The Alt key, Option on macOS.
+    pub alt: bool,
+    /// The Shift key.
+    pub shift: bool,
+    /// The Super key, Windows on Windows and Command on macOS.
+    pub logo: bool,
+}
+
+impl Modifiers {
+    /// No modifiers.
+    pub const NONE: Self = Self {
+        ctrl: false,
+        alt: false,
+        shift: false,
+        logo: false,
+    };
+
+    /// The modifier applications use for their main shortcuts, Command on macOS and Control
+    /// elsewhere.
+    pub const PRIMARY: Self = Self {
+        ctrl: cfg!(not(target_os = "macos")),
+        alt: false,
+        shift: false,
+        logo: cfg!(target_os = "macos"),
+    };
+
+    /// Returns whether no modifier is held.
+    pub fn is_empty(self) -> bool {
+        self == Self::NONE
+    }
+
+    /// Returns the modifiers in their canonical order, with their canonical names.
+    fn names(self) -> [(bool, &'static str); 4] {
+        [
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+            (self.logo, "Super"),
+        ]
+    }
+
+    /// Returns the flag of the modifier with the given name or alias, in any case.
+    fn flag(&mut self, name: &str) -> Option<&mut bool> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => &mut self.ctrl,
+            "alt" | "option" | "opt" => &mut self.alt,
+            "shift" => &mut self.shift,
+            "super" | "logo" | "meta" | "win" | "cmd" | "command" => &mut self.logo,
+            "primary" | "cmdorctrl" => {
+                if Self::PRIMARY.logo {
+                    &mut self.logo
+                } else {
+                    &mut self.ctrl
+                }
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// The key pressed with the modifiers of a [`Shortcut`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutKey {
+    /// A key producing a character. ASCII letters are stored in upper case.
+    Char(char),
+    /// A function key, from F1 to F24.
+    F(u8),
+    /// The Enter key.
+    Enter,
+    /// The Escape key.
+    Escape,
+    /// The Tab key.
+    Tab,
+    /// The space bar.
+    Space,
+    /// The Backspace key.
+    Backspace,
+    /// The Delete key.
+    Delete,
+    /// The Insert key.
+    Insert,
+    /// The Home key.
+    Home,
+    /// The End key.
+    End,
+    /// The Page Up key.
+    PageUp,
+    /// The Page Down key.
+    PageDown,
+    /// The up arrow.
+    Up,
+    /// The down arrow.
+    Down,
+    /// The left arrow.
+    Left,
+    /// The right arrow.
+    Right,
+}
+
+impl fmt::Display for ShortcutKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match NAMED_KEYS.iter().find(|(named, _)| named == self) {
+            Some((_, names)) => f.write_str(names[0]),
+            None => match self {
+                Self::Char(c) => write!(f, "{c}"),
+                Self::F(number) => write!(f, "F{number}"),
+                _ => unreachable!("Every named key is listed in NAMED_KEYS."),
+            },
+        }
+    }
+}
+
+impl FromStr for ShortcutKey {
+    type Err = ParseValueError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_key(input.trim()).map_err(|reason| ParseValueError::new("key", input, reason))
+    }
+}
+
+/// A keyboard shortcut, written in canonical form such as `Ctrl+Shift+S`.
+///
+/// Modifiers are separated from the key and from each other by `+`, in any case and with
+/// the aliases `Control`, `Option`, `Cmd`, `Win` or `Meta` for `Super`. `Primary` stands
+/// for Command on macOS and Control elsewhere. Shortcuts are written with the modifiers in
+/// the order `Ctrl`, `Alt`, `Shift`, `Super`, and letters in upper case, so the same
+/// shortcut always has the same text.
+///
+/// ```
+/// use libset::{Modifiers, Shortcut, ShortcutKey};
+///
+/// let save: Shortcut = "shift + ctrl + s".parse().unwrap();
+/// assert_eq!(save.key, ShortcutKey::Char('S'));
+/// assert_eq!(save.modifiers, Modifiers { ctrl: true, shift: true, ..Modifiers::NONE });
+/// assert_eq!(save.to_string(), "Ctrl+Shift+S");
+/// assert_eq!("Alt+F4".parse::<Shortcut>().unwrap().key, ShortcutKey::F(4));
+/// assert_eq!("Ctrl++".parse::<Shortcut>().unwrap().to_string(), "Ctrl+Plus");
+///
+/// let err = "Ctrl+Shfit+S".parse::<Shortcut>().unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "'Ctrl+Shfit+S' is not a valid shortcut: unknown modifier 'Shfit', \
+///      use Ctrl, Alt, Shift, Super or Primary"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    /// The modifiers held down.
+    pub modifiers: Modifiers,
+    /// The key pressed.
+    pub key: ShortcutKey,
+}
+
+impl Shortcut {
+    /// Creates a shortcut.
+    pub const fn new(modifiers: Modifiers, key: ShortcutKey) -> Self {
+        Self { modifiers, key }
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in self.modifiers.names() {
+            if held {
+                write!(f, "{name}+")?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = ParseValueError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let failed = |reason: String| ParseValueError::new("shortcut", input, reason);
+        let text = input.trim();
+        if text.is_empty() {
+            return Err(failed(
+                "expected modifiers and a key, such as Ctrl+S".to_string(),
+            ));
+        }
+        // A trailing `++` or a lone `+` is the plus key itself.
+        let (modifiers, key) = match text.strip_suffix('+') {
+            Some("") => (None, "+"),
+            Some(rest) => match rest.trim_end().strip_suffix('+') {
+                Some(modifiers) => (Some(modifiers), "+"),
+                None => return Err(failed("missing a key after the last '+'".to_string())),
+            },
+            None => match text.rsplit_once('+') {
+                Some((modifiers, key)) => (Some(modifiers), key.trim()),
+                None => (None, text),
+            },
+        };
+        let mut shortcut = Shortcut::new(Modifiers::NONE, parse_key(key).map_err(failed)?);
+        for name in modifiers
+            .into_iter()
+            .flat_map(|modifiers| modifiers.split('+'))
+        {
+            let name = name.trim();
+            let flag = shortcut.modifiers.flag(name).ok_or_else(|| {
+                failed(if name.is_empty() {
+                    "empty modifier between two '+'".to_string()
+                } else {
+                    format!("unknown modifier '{name}', use Ctrl, Alt, Shift, Super or Primary")
+                })
+            })?;
+            if *flag {
+                return Err(failed(format!("modifier '{name}' is repeated")));
+            }
+            *flag = true;
+        }
+        Ok(shortcut)
+    }
+}
+
+impl Serialize for Shortcut {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Shortcut {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ShortcutVisitor)
+    }
+}
+
+/// Deserializes a [`Shortcut`] from its string form.
+struct ShortcutVisitor;
+
+impl Visitor<'_> for ShortcutVisitor {
+    type Value = Shortcut;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a shortcut such as \"Ctrl+Shift+S\"")
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<Shortcut, E> {
+        text.parse().map_err(E::custom)
+    }
+}
+
+/// Parses the key of a shortcut, by name or as a single character.
+fn parse_key(name: &str) -> Result<ShortcutKey, String> {
+    if let Some((key, _)) = NAMED_KEYS
+        .iter()
+        .find(|(_, names)| names.iter().any(|alias| alias.eq_ignore_ascii_case(name)))
+    {
+        return Ok(*key);
+    }
+    if let Some(number) = name
+        .strip_prefix(['F', 'f'])
+        .and_then(|number| number.parse::<u8>().ok())
+    {
+        return match number {
+            1..=24 => Ok(ShortcutKey::F(number)),
+            _ => Err(format!("unknown function key '{name}', use F1 to F24")),
+        };
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => Err("missing a key after the modifiers".to_string()),
+        (Some(c), None) if !c.is_whitespace() && !c.is_control() => {
+            Ok(ShortcutKey::Char(c.to_ascii_uppercase()))
+        }
+        _ if Modifiers::default().flag(name).is_some() => {
+            Err(format!("'{name}' is a modifier, add a key after it"))
+        }
+        _ => Err(format!(
+            "unknown key '{name}', use a single character, F1 to F24 or a key name such as Enter"
+        )),
+    }
+}
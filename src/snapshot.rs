@@ -0,0 +1,235 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+use crate::{
+    deserialize,
+    manifest::{hash, is_hash, Manifest},
+    utils::{sanitize_name, FileType},
+    Config, Error,
+};
+
+impl Config {
+    /// Writes a file atomically, creating its parent directories.
+    fn write_atomic(&self, path: &Path, data: &[u8]) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            self.fs
+                .create_dir_all(parent)
+                .map_err(|err| Error::io(parent, err))?;
+        }
+        self.fs
+            .write(path, data, false)
+            .map_err(|err| Error::write(path, err))
+    }
+
+    /// Lists the snapshot manifests, the most recent first.
+    pub(crate) fn manifests(&self) -> Result<Vec<(SystemTime, PathBuf)>, Error> {
+        let dir = self.snapshot_dir().join("manifests");
+        let paths = match self.fs.list(&dir) {
+            Ok(paths) => paths,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(Error::io(&dir, err)),
+        };
+        let mut manifests = Vec::new();
+        for path in paths {
+            // Skips the temporary files of manifests being written.
+            if path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+            {
+                continue;
+            }
+            let metadata = self
+                .fs
+                .metadata(&path)
+                .map_err(|err| Error::io(&path, err))?;
+            // Storages without modification times list snapshots as the oldest.
+            let modified = metadata.modified.unwrap_or(SystemTime::UNIX_EPOCH);
+            manifests.push((modified, path));
+        }
+        manifests.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        Ok(manifests)
+    }
+
+    /// Stores a deduplicated snapshot of every file in the version root and its scopes.
+    ///
+    /// File contents are stored once as blobs named after their SHA-256 hash, and a manifest
+    /// lists which blob each file had. Taking frequent snapshots of a store that barely
+    /// changes only adds a new manifest. Snapshots are kept next to the version directory,
+    /// under `.snapshots`, and older ones are removed according to the policy set with
+    /// [`Config::with_retention`].
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let snapshot = config.snapshot_cas()?;
+    /// // ... the user changes some settings ...
+    /// config.restore(&snapshot)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the id of the manifest, which is also the store's
+    /// [fingerprint](Config::fingerprint), or an `Error` if an error occurred.
+    pub fn snapshot_cas(&self) -> Result<String, Error> {
+        let manifest = self.manifest_with(|digest, data| {
+            let blob = self.blob_path(digest);
+            if !self.fs.exists(&blob) {
+                self.write_atomic(&blob, data)?;
+            }
+            Ok(())
+        })?;
+        let id = manifest.fingerprint();
+        let path = self.snapshot_dir().join("manifests").join(&id);
+        // Taking the same snapshot again rewrites it, making it the most recent one.
+        self.write_atomic(&path, manifest.to_string().as_bytes())?;
+        self.apply_retention()?;
+        Ok(id)
+    }
+
+    /// Restores the store to the state recorded by a snapshot.
+    ///
+    /// Every blob is read and verified before any file is touched. Files that were not part
+    /// of the snapshot are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The manifest id returned by [`Config::snapshot_cas`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn restore(&self, id: &str) -> Result<(), Error> {
+        self.check_sealed()?;
+        let snapshot_error = |reason: String| Error::Snapshot {
+            id: id.to_string(),
+            reason,
+        };
+        if !is_hash(id) {
+            return Err(snapshot_error("invalid snapshot id".to_string()));
+        }
+        self.flush()?;
+
+        let path = self.snapshot_dir().join("manifests").join(id);
+        let manifest = self.fs.read(&path).map_err(|err| Error::io(&path, err))?;
+        let manifest: Manifest = String::from_utf8_lossy(&manifest).parse()?;
+
+        let mut files = Vec::new();
+        for (relative, digest) in manifest.files() {
+            let target = self.root.join(sanitize_name(relative)?);
+            let blob = self.blob_path(digest);
+            let data = self.fs.read(&blob).map_err(|err| Error::io(&blob, err))?;
+            if hash(&data) != digest {
+                return Err(snapshot_error(format!("blob {digest} is corrupted")));
+            }
+            files.push((target, data));
+        }
+
+        self.replace_files(&files)
+    }
+
+    /// Writes the given files and removes every other file in the store.
+    pub(crate) fn replace_files(&self, files: &[(PathBuf, Vec<u8>)]) -> Result<(), Error> {
+        self.check_sealed()?;
+        let restored: HashSet<&PathBuf> = files.iter().map(|(path, _)| path).collect();
+        for (path, data) in files {
+            self.cache.invalidate(path);
+            self.write_atomic(path, data)?;
+        }
+        for entry in self.walk()?.collect::<Result<Vec<_>, _>>()? {
+            if !restored.contains(&entry.path) {
+                self.cache.invalidate(&entry.path);
+                self.fs
+                    .remove(&entry.path)
+                    .map_err(|err| Error::io(&entry.path, err))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables or disables reading truncated files from the most recent snapshot.
+    ///
+    /// Reads that fail with [`Error::Truncated`], usually after a crash interrupted a write
+    /// that bypassed the atomic writer, return the value recorded by the newest snapshot
+    /// that contains the file instead. The damaged file is left untouched and a warning is
+    /// logged. Take snapshots with [`Config::snapshot_cas`].
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether truncated files should be read from snapshots.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_snapshot_fallback(mut self, enabled: bool) -> Self {
+        self.snapshot_fallback = enabled;
+        self
+    }
+
+    /// Reads a file from the newest snapshot containing it.
+    pub(crate) fn get_from_snapshot<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        file_type: FileType,
+        path: &Path,
+    ) -> Result<Option<T>, Error> {
+        if !path.starts_with(&self.root) {
+            return Ok(None);
+        }
+        let relative = self.relative(path);
+        let Ok(manifests) = self.manifests() else {
+            return Ok(None);
+        };
+        for (_, manifest) in manifests {
+            let Some(digest) = self
+                .fs
+                .read(&manifest)
+                .ok()
+                .and_then(|text| String::from_utf8_lossy(&text).parse::<Manifest>().ok())
+                .and_then(|manifest| {
+                    manifest
+                        .files()
+                        .find(|(file, _)| *file == relative)
+                        .map(|(_, digest)| digest.to_string())
+                })
+            else {
+                continue;
+            };
+            let blob = self.blob_path(&digest);
+            let Ok(bytes) = self.fs.read(&blob) else {
+                continue;
+            };
+            if hash(&bytes) != digest {
+                continue;
+            }
+            let data = self.decode_file(key, file_type, path, bytes)?;
+            let value = deserialize(key, file_type, path, &data, &self.parse)?;
+            warn!(key, path = %path.display(), "read truncated file from snapshot {}", manifest.display());
+            return Ok(Some(value));
+        }
+        Ok(None)
+    }
+
+    /// Returns the directory holding the snapshots of this version.
+    pub(crate) fn snapshot_dir(&self) -> PathBuf {
+        self.root
+            .with_file_name(".snapshots")
+            .join(format!("v{}", self.version))
+    }
+
+    /// Returns the path of the blob with the given hash.
+    fn blob_path(&self, digest: &str) -> PathBuf {
+        self.snapshot_dir()
+            .join("blobs")
+            .join(&digest[..2])
+            .join(&digest[2..])
+    }
+}
@@ -0,0 +1,370 @@
+//! Helpers for state applications keep between runs, such as the geometry of their windows.
+//!
+//! State is stored in the state directory instead of the config directory, `$XDG_STATE_HOME`
+//! on Linux and the local data directory elsewhere, so it isn't synced or backed up along
+//! with settings.
+//!
+//! Sessions are kept there as well, see [`Config::session`].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{Config, Error, FileType, LibsetResultExt, WriteStrategy};
+
+/// How long windows must stay unchanged before their state is written.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The key window states are stored under.
+const WINDOWS_KEY: &str = "windows";
+
+/// The key the session is stored under.
+const SESSION_KEY: &str = "session";
+
+/// The plain key present while the application runs, left behind by runs that crashed.
+const RUN_MARKER: &str = "running";
+
+/// Returns the directory application state is stored in.
+///
+/// # Returns
+///
+/// `$XDG_STATE_HOME` on Linux, the local data directory on other platforms, or `None` if the
+/// home directory can't be determined.
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::state_dir().or_else(dirs::data_local_dir)
+}
+
+impl Config {
+    /// Creates a new `Config` object stored in the state directory, see [`state_dir`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the application.
+    /// * `version` - The version of the state.
+    /// * `scope` - An optional scope for the application.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `Config` object or an `Error` if an error occurred.
+    pub fn new_state(name: &str, version: u64, scope: Option<&str>) -> Result<Self, Error> {
+        let user_path = state_dir().ok_or(Error::NoConfigDirectory)?;
+        Self::new_in(&user_path, name, version, scope).map(Config::recover_quietly)
+    }
+
+    /// Opens the session of the application, stored in the state directory.
+    ///
+    /// The session holds what is needed to restore the application after a crash, such as
+    /// the open documents or tabs. Opening it marks the application as running until the
+    /// `Session` is dropped or closed, so the next run can tell whether the previous one shut
+    /// down cleanly. Only one instance of the application is expected to open the session at
+    /// a time.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Editor", 1, None)?;
+    /// let mut session = config.session::<Vec<String>>()?;
+    /// if let Some(documents) = session.take_crashed_session()? {
+    ///     println!("restoring {} documents", documents.len());
+    /// }
+    /// session.save(&vec!["notes.md".to_string()])?;
+    /// session.close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Session` or an `Error` if the state directory could not be
+    /// created or the run marker could not be written.
+    pub fn session<T>(&self) -> Result<Session<T>, Error> {
+        let scope = self.scope.as_deref().and_then(Path::to_str);
+        let config = Config::new_state(&self.name, self.version, scope)?
+            .with_write_strategy(WriteStrategy::Atomic);
+        let crashed = config.has_plain(RUN_MARKER);
+        config.set_plain(RUN_MARKER, std::process::id())?;
+        Ok(Session {
+            config,
+            crashed,
+            closed: false,
+            item: PhantomData,
+        })
+    }
+}
+
+/// The session of an application, opened with [`Config::session`].
+///
+/// Sessions are always written atomically, so a crash while saving leaves the previous
+/// session intact. Dropping the `Session` records a clean shutdown, unless the thread is
+/// panicking.
+pub struct Session<T> {
+    config: Config,
+    crashed: bool,
+    closed: bool,
+    item: PhantomData<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> Session<T> {
+    /// Saves the current session, replacing the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The state to restore after a crash.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the session could not be written.
+    pub fn save(&self, session: &T) -> Result<(), Error> {
+        self.config.set_json(SESSION_KEY, session)
+    }
+
+    /// Returns the last saved session, whether or not the previous run crashed.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the session, `None` if none was saved, or an `Error` if it could
+    /// not be read.
+    pub fn load(&self) -> Result<Option<T>, Error> {
+        self.config.get_json(SESSION_KEY).ignore_missing()
+    }
+
+    /// Takes the session of the previous run if it didn't shut down cleanly.
+    ///
+    /// The session is only returned once, later calls return `None`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the session of the crashed run, `None` if the previous run shut
+    /// down cleanly or saved no session, or an `Error` if it could not be read.
+    pub fn take_crashed_session(&mut self) -> Result<Option<T>, Error> {
+        if !std::mem::take(&mut self.crashed) {
+            return Ok(None);
+        }
+        self.load()
+    }
+
+    /// Records a clean shutdown, reporting failures instead of logging them like dropping
+    /// does.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the run marker could not be removed.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.closed = true;
+        self.config
+            .remove(RUN_MARKER, FileType::Plain)
+            .ignore_missing()
+            .map(drop)
+    }
+}
+
+impl<T> Drop for Session<T> {
+    fn drop(&mut self) {
+        if self.closed || std::thread::panicking() {
+            return;
+        }
+        if let Err(err) = self
+            .config
+            .remove(RUN_MARKER, FileType::Plain)
+            .ignore_missing()
+        {
+            warn!(error = %err, "failed to record a clean shutdown");
+        }
+    }
+}
+
+/// The size, position and maximized state of a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowState {
+    /// The width of the window.
+    pub width: u32,
+    /// The height of the window.
+    pub height: u32,
+    /// The horizontal position of the window, `None` where the platform doesn't expose it,
+    /// such as on Wayland.
+    pub x: Option<i32>,
+    /// The vertical position of the window, `None` where the platform doesn't expose it.
+    pub y: Option<i32>,
+    /// Whether the window is maximized.
+    pub maximized: bool,
+}
+
+struct Shared {
+    config: Config,
+    pending: Mutex<HashMap<String, WindowState>>,
+}
+
+impl Shared {
+    /// Writes the states saved since the last write.
+    fn write(&self) -> Result<(), Error> {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let mut windows: BTreeMap<String, WindowState> =
+            self.config.get_json(WINDOWS_KEY).or_default_value()?;
+        windows.extend(pending.drain());
+        self.config.set_json(WINDOWS_KEY, windows)
+    }
+}
+
+/// The state of every window of an application, written in the background.
+///
+/// Windows report their geometry many times per second while they are moved or resized, so
+/// saved states are kept in memory and written once windows stay unchanged for a moment.
+/// States still waiting are written when the value is dropped.
+///
+/// ```no_run
+/// # use libset::state::{WindowState, WindowStates};
+/// # fn main() -> Result<(), libset::Error> {
+/// let windows = WindowStates::new("org.example.Editor")?;
+/// let state = windows.get("main")?.unwrap_or(WindowState {
+///     width: 1024,
+///     height: 768,
+///     x: None,
+///     y: None,
+///     maximized: false,
+/// });
+/// // On every resize:
+/// windows.save("main", WindowState { width: 1280, ..state });
+/// # Ok(())
+/// # }
+/// ```
+pub struct WindowStates {
+    shared: Arc<Shared>,
+    trigger: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WindowStates {
+    /// Opens the window states of an application, stored in the state directory.
+    ///
+    /// Bursts of changes are debounced with [`DEFAULT_DEBOUNCE`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the application.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `WindowStates` or an `Error` if the state directory could not
+    /// be created.
+    pub fn new(name: &str) -> Result<Self, Error> {
+        Ok(Self::with_config(
+            Config::new_state(name, 1, None)?,
+            DEFAULT_DEBOUNCE,
+        ))
+    }
+
+    /// Keeps window states in a configuration of your choice.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration to store the states in.
+    /// * `debounce` - How long windows must stay unchanged before their state is written.
+    ///
+    /// # Returns
+    ///
+    /// The `WindowStates`.
+    pub fn with_config(config: Config, debounce: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            config,
+            pending: Mutex::default(),
+        });
+        let (trigger, events) = mpsc::channel();
+        let thread = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                while events.recv().is_ok() {
+                    loop {
+                        match events.recv_timeout(debounce) {
+                            Ok(()) => continue,
+                            Err(RecvTimeoutError::Timeout) => break,
+                            Err(RecvTimeoutError::Disconnected) => return,
+                        }
+                    }
+                    if let Err(err) = shared.write() {
+                        warn!(error = %err, "failed to save window states");
+                    }
+                }
+            })
+        };
+        Self {
+            shared,
+            trigger: Some(trigger),
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the last state saved for a window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The identifier of the window.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the state, `None` if none was saved, or an `Error` if the stored
+    /// states could not be read.
+    pub fn get(&self, window: &str) -> Result<Option<WindowState>, Error> {
+        if let Some(state) = self.shared.pending.lock().unwrap().get(window) {
+            return Ok(Some(*state));
+        }
+        let mut windows: BTreeMap<String, WindowState> = self
+            .shared
+            .config
+            .get_json(WINDOWS_KEY)
+            .or_default_value()?;
+        Ok(windows.remove(window))
+    }
+
+    /// Saves the state of a window, it is written once the window stays unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The identifier of the window.
+    /// * `state` - The current state of the window.
+    pub fn save(&self, window: &str, state: WindowState) {
+        self.shared
+            .pending
+            .lock()
+            .unwrap()
+            .insert(window.to_string(), state);
+        if let Some(trigger) = &self.trigger {
+            trigger.send(()).ok();
+        }
+    }
+
+    /// Writes the saved states now instead of waiting for windows to stay unchanged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if the states could not be written.
+    pub fn flush(&self) -> Result<(), Error> {
+        self.shared.write()
+    }
+}
+
+impl Drop for WindowStates {
+    fn drop(&mut self) {
+        // The thread exits once the trigger is gone, then the remaining states are written.
+        self.trigger.take();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+        if let Err(err) = self.shared.write() {
+            warn!(error = %err, "failed to save window states");
+        }
+    }
+}
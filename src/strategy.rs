@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    entry::{ATOMIC_WRITE_PREFIX, BACKUP_SUFFIX},
+    filesystem::Filesystem,
+    manifest, Config, Error,
+};
+
+/// How files are written, set with [`Config::with_write_strategy`].
+///
+/// Atomic writes rename a temporary file over the destination, which some network
+/// filesystems and overlay mounts reject or make very slow. The other strategies overwrite
+/// the file in place instead, trading crash safety for compatibility. Failed in-place writes
+/// are reported as [`Error::WriteInPlace`], since the file may be left incomplete.
+///
+/// Batches follow the strategy as well, see [`Batch::commit`]. Secrets and snapshot restores
+/// are always written atomically.
+///
+/// [`Batch::commit`]: crate::Batch::commit
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WriteStrategy {
+    /// Write a temporary file and rename it over the destination. Readers and crashes never
+    /// observe a partially written file.
+    #[default]
+    Atomic,
+    /// Copy the current contents to a hidden backup next to the file, overwrite the file in
+    /// place, then remove the backup. An interrupted write leaves the backup behind, and
+    /// [`Config::recover`] restores it once abandoned if the file is missing or incomplete.
+    ///
+    /// The backup starts with a line recording the length and hash of the new contents,
+    /// followed by the previous contents.
+    DirectWithBackup,
+    /// Overwrite the file in place. An interrupted write leaves the file incomplete, and
+    /// readers may observe partial contents.
+    Direct,
+}
+
+impl WriteStrategy {
+    /// Writes the contents of a file.
+    ///
+    /// With `create_new`, an existing file is reported as an `Error::Write` caused by
+    /// `io::ErrorKind::AlreadyExists`, whatever the strategy.
+    pub(crate) fn write(
+        self,
+        fs: &dyn Filesystem,
+        path: &Path,
+        data: &[u8],
+        create_new: bool,
+    ) -> Result<(), Error> {
+        let failed = |err| Error::write(path, err);
+        let in_place = |backup: Option<PathBuf>| {
+            move |err: std::io::Error| {
+                if create_new && err.kind() == std::io::ErrorKind::AlreadyExists {
+                    failed(err)
+                } else {
+                    Error::WriteInPlace {
+                        path: path.to_path_buf(),
+                        backup,
+                        source: err,
+                    }
+                }
+            }
+        };
+        match self {
+            Self::Atomic => fs.write(path, data, create_new).map_err(failed),
+            Self::Direct => fs
+                .write_in_place(path, data, create_new)
+                .map_err(in_place(None)),
+            Self::DirectWithBackup => {
+                let previous = match fs.read(path) {
+                    Ok(previous) => Some(previous),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(err) => return Err(failed(err)),
+                };
+                let Some(previous) = previous else {
+                    return fs
+                        .write_in_place(path, data, create_new)
+                        .map_err(in_place(None));
+                };
+                if create_new {
+                    return Err(failed(std::io::ErrorKind::AlreadyExists.into()));
+                }
+                let backup = backup_path(path);
+                fs.write_in_place(&backup, &backup_contents(data, &previous), false)
+                    .map_err(|err| Error::io(&backup, err))?;
+                fs.write_in_place(path, data, false)
+                    .map_err(in_place(Some(backup.clone())))?;
+                fs.remove(&backup).map_err(|err| Error::io(&backup, err))
+            }
+        }
+    }
+}
+
+impl Config {
+    /// Sets how files are written, atomically by default.
+    ///
+    /// ```no_run
+    /// # use libset::{Config, WriteStrategy};
+    /// # fn main() -> Result<(), libset::Error> {
+    /// // The config directory lives on an NFS share.
+    /// let config = Config::new("org.example.Demo", 1, None)?
+    ///     .with_write_strategy(WriteStrategy::DirectWithBackup);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - How files are written.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Config` object.
+    pub fn with_write_strategy(mut self, strategy: WriteStrategy) -> Self {
+        self.write_strategy = strategy;
+        self.restart_flusher();
+        self
+    }
+}
+
+/// Returns the path of the backup kept while a file is overwritten in place.
+pub(crate) fn backup_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{ATOMIC_WRITE_PREFIX}.{name}{BACKUP_SUFFIX}"))
+}
+
+/// Returns the name of the file a backup was made for, if `name` is the name of a backup.
+pub(crate) fn backup_target(name: &str) -> Option<&str> {
+    name.strip_prefix(ATOMIC_WRITE_PREFIX)?
+        .strip_prefix('.')?
+        .strip_suffix(BACKUP_SUFFIX)
+        .filter(|target| !target.is_empty())
+}
+
+/// Builds the contents of a backup, recording the data about to be written so recovery can
+/// tell whether the write completed.
+fn backup_contents(data: &[u8], previous: &[u8]) -> Vec<u8> {
+    let mut contents = format!("{} {}\n", data.len(), manifest::hash(data)).into_bytes();
+    contents.extend_from_slice(previous);
+    contents
+}
+
+/// The state of a file overwritten in place, judged from its backup.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Interrupted<'a> {
+    /// The write completed, the backup can be dropped.
+    Completed,
+    /// The file is missing or was cut short, the previous contents should be restored.
+    Incomplete(&'a [u8]),
+    /// The file was replaced by another write since, the backup is outdated.
+    Replaced,
+}
+
+/// Compares a file with the backup made before it was overwritten in place.
+///
+/// # Returns
+///
+/// The state of the file, or `None` if the backup is damaged.
+pub(crate) fn interrupted<'a>(backup: &'a [u8], current: Option<&[u8]>) -> Option<Interrupted<'a>> {
+    let end = backup.iter().position(|byte| *byte == b'\n')?;
+    let header = std::str::from_utf8(&backup[..end]).ok()?;
+    let previous = &backup[end + 1..];
+    let (len, digest) = header.split_once(' ')?;
+    let len: usize = len.parse().ok()?;
+    if !manifest::is_hash(digest) {
+        return None;
+    }
+    Some(match current {
+        None => Interrupted::Incomplete(previous),
+        Some(current) if current.len() == len && manifest::hash(current) == digest => {
+            Interrupted::Completed
+        }
+        Some(current) if current.len() < len => Interrupted::Incomplete(previous),
+        Some(_) => Interrupted::Replaced,
+    })
+}
@@ -0,0 +1,122 @@
+use std::{collections::BTreeMap, time::SystemTime};
+
+use serde::Serialize;
+
+use crate::{Config, Entry, Error};
+
+/// How many of the largest files are listed in a [`Summary`].
+const LARGEST: usize = 5;
+
+/// Statistics about every file in a store.
+///
+/// The summary serializes to a stable structure, so it can be attached to bug reports or shown
+/// in a storage panel.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    /// How many files are stored.
+    pub keys: usize,
+    /// How many files are stored in each format, plain files are counted as `plain`.
+    pub formats: BTreeMap<String, usize>,
+    /// How many files are stored in each scope, the version root is counted as an empty string.
+    pub scopes: BTreeMap<String, usize>,
+    /// The combined size of every file in bytes.
+    pub total_bytes: u64,
+    /// The largest files, biggest first.
+    pub largest: Vec<Usage>,
+    /// The modification time of the file changed the longest ago.
+    pub oldest: Option<SystemTime>,
+    /// The modification time of the most recently changed file.
+    pub newest: Option<SystemTime>,
+}
+
+/// The space used by a stored file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    /// The path of the file relative to the version root, using `/` as separator.
+    pub path: String,
+    /// The size of the file in bytes.
+    pub bytes: u64,
+}
+
+impl Config {
+    /// Collects statistics about every file in the version root and its scopes.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// let summary = config.summary()?;
+    /// println!("{} files, {} bytes", summary.keys, summary.total_bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Summary` or an `Error` if the store could not be traversed.
+    pub fn summary(&self) -> Result<Summary, Error> {
+        self.flush()?;
+        let mut summary = Summary::default();
+        let mut sizes = Vec::new();
+        for entry in self.walk()? {
+            let entry = entry?;
+            summary.keys += 1;
+            let format = match entry.file_type.to_string() {
+                format if format.is_empty() => "plain".to_string(),
+                format => format,
+            };
+            *summary.formats.entry(format).or_default() += 1;
+            let scope = entry
+                .scope
+                .as_deref()
+                .map(|scope| self.relative(&self.root.join(scope)))
+                .unwrap_or_default();
+            *summary.scopes.entry(scope).or_default() += 1;
+            summary.total_bytes += entry.len;
+            if let Some(modified) = entry.modified {
+                summary.oldest = Some(summary.oldest.map_or(modified, |t| t.min(modified)));
+                summary.newest = Some(summary.newest.map_or(modified, |t| t.max(modified)));
+            }
+            sizes.push(Usage {
+                path: self.relative(&entry.path),
+                bytes: entry.len,
+            });
+        }
+        sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+        sizes.truncate(LARGEST);
+        summary.largest = sizes;
+        Ok(summary)
+    }
+
+    /// Lists the largest files in the version root and its scopes.
+    ///
+    /// Use this to show users what is taking up space and offer to remove specific keys, pass
+    /// `usize::MAX` to get the size of every file.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// for entry in config.largest_entries(10)? {
+    ///     println!("{}: {} bytes", entry.key, entry.len);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - How many entries to return at most.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the entries, biggest first, or an `Error` if the store could not
+    /// be traversed.
+    pub fn largest_entries(&self, count: usize) -> Result<Vec<Entry>, Error> {
+        self.flush()?;
+        let mut entries = self.walk()?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by(|a, b| b.len.cmp(&a.len).then_with(|| a.path.cmp(&b.path)));
+        entries.truncate(count);
+        Ok(entries)
+    }
+}
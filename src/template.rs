@@ -0,0 +1,58 @@
+use serde::Serialize;
+
+use crate::{utils::FileType, Config, Error};
+
+impl Config {
+    /// Renders a stored template into another key.
+    ///
+    /// The template is read as a plain file and rendered with
+    /// [minijinja](https://docs.rs/minijinja), the output is written as a plain file. This is
+    /// useful to generate configuration files for other programs from the application's
+    /// settings.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # use serde::Serialize;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// #[derive(Serialize)]
+    /// struct Server { name: String, port: u16 }
+    ///
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// config.set_plain("nginx.conf.tmpl", "server { listen {{ port }}; server_name {{ name }}; }")?;
+    /// let server = Server { name: "example.org".into(), port: 8080 };
+    /// config.render_template("nginx.conf.tmpl", &server, "nginx.conf")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The key of the stored template.
+    /// * `context` - The value exposed to the template.
+    /// * `target` - The key the rendered output is stored under.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `Error` if an error occurred.
+    pub fn render_template<C: Serialize>(
+        &self,
+        template: &str,
+        context: &C,
+        target: &str,
+    ) -> Result<(), Error> {
+        let path = self.resolve(template, FileType::Plain)?;
+        let source = self.read_file(template, FileType::Plain, &path)?;
+        let template_error = |source| Error::Template {
+            key: template.to_string(),
+            path,
+            source,
+        };
+        let mut env = minijinja::Environment::new();
+        env.set_keep_trailing_newline(true);
+        let output = env
+            .template_from_str(&source)
+            .and_then(|tmpl| tmpl.render(context))
+            .map_err(template_error)?;
+        self.set_plain(target, output)
+    }
+}
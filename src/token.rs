@@ -0,0 +1,88 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::Path,
+    time::SystemTime,
+};
+
+use crate::filesystem::Filesystem;
+
+/// Identifies the version of a file at the time it was read.
+///
+/// Returned by [`Config::get_json_versioned`](crate::Config::get_json_versioned) and its
+/// siblings, and passed back to [`Config::set_json_cas`](crate::Config::set_json_cas) to only
+/// write if nobody else changed the file in the meantime.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    modified: Option<SystemTime>,
+    hash: u64,
+}
+
+impl Token {
+    /// Creates a token for the stored contents of a file.
+    ///
+    /// `pending` marks contents that haven't reached the disk yet, whose modification time
+    /// isn't known.
+    pub(crate) fn new(fs: &dyn Filesystem, path: &Path, data: &[u8], pending: bool) -> Self {
+        let modified = if pending {
+            None
+        } else {
+            fs.metadata(path)
+                .ok()
+                .and_then(|metadata| metadata.modified)
+        };
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        Self {
+            modified,
+            hash: hasher.finish(),
+        }
+    }
+
+    /// Returns `true` if both tokens describe the same version of a file.
+    ///
+    /// Modification times are only compared when both are known.
+    pub(crate) fn matches(&self, other: &Token) -> bool {
+        self.hash == other.hash
+            && match (self.modified, other.modified) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use crate::{Config, Error};
+
+    #[test]
+    fn concurrent_compare_and_swap_loses_no_update() {
+        const THREADS: u64 = 8;
+        const INCREMENTS: u64 = 25;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_in(dir.path(), "org.example.Test", 1, None).unwrap();
+        config.set_json("count", 0).unwrap();
+        std::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|| {
+                    for _ in 0..INCREMENTS {
+                        loop {
+                            let (count, token) = config.get_json_versioned::<u64>("count").unwrap();
+                            match config.set_json_cas("count", count + 1, &token) {
+                                Ok(()) => break,
+                                Err(Error::Conflict { .. }) => continue,
+                                Err(err) => panic!("{err}"),
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(
+            config.get_json::<u64>("count").unwrap(),
+            THREADS * INCREMENTS
+        );
+    }
+}
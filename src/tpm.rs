@@ -0,0 +1,163 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+use chacha20poly1305::{
+    aead::{KeyInit, OsRng},
+    XChaCha20Poly1305,
+};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{EncryptionKey, Error, Filesystem, KeyProvider, StdFilesystem};
+
+/// The tool sealing keys to the TPM.
+const SYSTEMD_CREDS: &str = "systemd-creds";
+/// The name bound into sealed keys, decrypting them under another name fails.
+const CREDENTIAL_NAME: &str = "libset-key";
+
+/// A key provider sealing the key with the TPM2 chip.
+///
+/// A random key is generated the first time and stored encrypted by the TPM at the given
+/// path, using `systemd-creds`, so the plain key never touches the disk and the file is
+/// useless on another machine. When no TPM2 device is available the fallback provider is used
+/// instead, if one was set.
+///
+/// ```no_run
+/// # use libset::{Config, EncryptedKeys, Encryption, KeyringKey, TpmKey};
+/// # fn main() -> Result<(), libset::Error> {
+/// let provider = TpmKey::new("/var/lib/example/key.cred")
+///     .with_fallback(KeyringKey::new("org.example.Demo", "settings"));
+/// let mut config = Config::new("org.example.Demo", 1, None)?;
+/// config.add_middleware(Encryption::new(provider, EncryptedKeys::All));
+/// # Ok(())
+/// # }
+/// ```
+pub struct TpmKey {
+    path: PathBuf,
+    fallback: Option<Box<dyn KeyProvider>>,
+    key: OnceLock<EncryptionKey>,
+}
+
+impl TpmKey {
+    /// Creates a provider storing the sealed key at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            fallback: None,
+            key: OnceLock::new(),
+        }
+    }
+
+    /// Sets the provider used when no TPM2 device is available.
+    pub fn with_fallback(mut self, provider: impl KeyProvider + 'static) -> Self {
+        self.fallback = Some(Box::new(provider));
+        self
+    }
+
+    /// Determines if a TPM2 device can be used to seal keys.
+    pub fn has_tpm2() -> bool {
+        Command::new(SYSTEMD_CREDS)
+            .arg("has-tpm2")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    /// Unseals the stored key, or generates and seals a new one.
+    fn unseal(&self) -> Result<EncryptionKey, Error> {
+        if self.path.exists() {
+            let path = self.path.to_string_lossy();
+            let data = run(
+                &["decrypt", &format!("--name={CREDENTIAL_NAME}"), &path, "-"],
+                &[],
+            )?;
+            return EncryptionKey::try_from(data.as_slice())
+                .map_err(|_| Error::encryption(format!("Malformed key in {path}")));
+        }
+        let key: EncryptionKey = XChaCha20Poly1305::generate_key(&mut OsRng).into();
+        let sealed = run(
+            &[
+                "encrypt",
+                "--with-key=tpm2",
+                &format!("--name={CREDENTIAL_NAME}"),
+                "-",
+                "-",
+            ],
+            &key,
+        )?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| Error::io(parent, err))?;
+        }
+        StdFilesystem
+            .write(&self.path, &sealed, true)
+            .map_err(|err| Error::write(&self.path, err))?;
+        Ok(key)
+    }
+}
+
+impl KeyProvider for TpmKey {
+    fn key(&self) -> Result<EncryptionKey, Error> {
+        if let Some(key) = self.key.get() {
+            return Ok(*key);
+        }
+        if !Self::has_tpm2() {
+            return match &self.fallback {
+                Some(fallback) => fallback.key(),
+                None => Err(Error::encryption("No TPM2 device available".to_string())),
+            };
+        }
+        let mut key = self.unseal()?;
+        let cached = *self.key.get_or_init(|| key);
+        key.zeroize();
+        Ok(cached)
+    }
+
+    fn is_available(&self) -> bool {
+        Self::has_tpm2()
+            || self
+                .fallback
+                .as_ref()
+                .is_some_and(|fallback| fallback.is_available())
+    }
+}
+
+impl Drop for TpmKey {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.get_mut() {
+            key.zeroize();
+        }
+    }
+}
+
+/// Runs `systemd-creds` with the given arguments, passing `input` on stdin.
+fn run(args: &[&str], input: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+    let failed = |err: std::io::Error| Error::encryption(format!("{SYSTEMD_CREDS}: {err}"));
+    let mut child = Command::new(SYSTEMD_CREDS)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(failed)?;
+    // Keys are tiny, they fit in the pipe before anything is read back.
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input)
+        .map_err(failed)?;
+    let output = child.wait_with_output().map_err(failed)?;
+    let stdout = Zeroizing::new(output.stdout);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::encryption(format!(
+            "{SYSTEMD_CREDS}: {}",
+            stderr.trim()
+        )));
+    }
+    Ok(stdout)
+}
@@ -0,0 +1,201 @@
+use std::iter::Peekable;
+
+use crate::utils::FileType;
+
+/// The parts of a format's syntax that can be left open when a file is cut short.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Syntax {
+    /// Strings can be delimited with single quotes.
+    single_quotes: bool,
+    /// Backslashes escape characters in single quoted strings.
+    single_quote_escapes: bool,
+    /// Strings can be delimited with three quotes and span several lines.
+    triple_quotes: bool,
+    /// `#` starts a comment line.
+    hash_comments: bool,
+    /// `//` starts a comment line and `/*` a block comment.
+    slash_comments: bool,
+}
+
+impl FileType {
+    /// Returns the syntax truncation is detected with, `None` for formats without closing
+    /// delimiters.
+    ///
+    /// YAML and INI have no closing delimiters, and their unquoted values often hold
+    /// apostrophes or unbalanced brackets.
+    pub(crate) fn syntax(self) -> Option<Syntax> {
+        match self {
+            FileType::Plain => None,
+            // TOML literal strings don't support escapes.
+            #[cfg(feature = "toml")]
+            FileType::Toml => Some(Syntax {
+                single_quotes: true,
+                triple_quotes: true,
+                hash_comments: true,
+                ..Syntax::default()
+            }),
+            // Comments are blanked out before detection when reading commented json.
+            #[cfg(feature = "json")]
+            FileType::Json => Some(Syntax::default()),
+            // RON character literals support escapes.
+            #[cfg(feature = "ron")]
+            FileType::Ron => Some(Syntax {
+                single_quotes: true,
+                single_quote_escapes: true,
+                slash_comments: true,
+                ..Syntax::default()
+            }),
+            #[cfg(feature = "yaml")]
+            FileType::Yaml => None,
+            #[cfg(feature = "ini")]
+            FileType::Ini => None,
+            #[cfg(feature = "json5")]
+            FileType::Json5 => Some(Syntax {
+                single_quotes: true,
+                single_quote_escapes: true,
+                slash_comments: true,
+                ..Syntax::default()
+            }),
+        }
+    }
+}
+
+/// Checks whether a document that failed to parse ends before its structure is complete.
+///
+/// Files that were cut short by an interrupted write end inside a string or comment, or leave
+/// brackets open. A complete but otherwise invalid document is not considered truncated, and
+/// neither is an empty one, the format decides what an empty file holds.
+///
+/// # Arguments
+///
+/// * `data` - The document, with comments already removed for commented json.
+/// * `file_type` - The format of the document.
+pub(crate) fn is_truncated(data: &str, file_type: FileType) -> bool {
+    let Some(syntax) = file_type.syntax() else {
+        return false;
+    };
+    let mut depth = 0usize;
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let triple = syntax.triple_quotes && take_triple(&mut chars, c);
+                if !skip_string(&mut chars, c, triple, true) {
+                    return true;
+                }
+            }
+            '\'' if syntax.single_quotes => {
+                let triple = syntax.triple_quotes && take_triple(&mut chars, c);
+                if !skip_string(&mut chars, c, triple, syntax.single_quote_escapes) {
+                    return true;
+                }
+            }
+            '#' if syntax.hash_comments => skip_line(&mut chars),
+            '/' if syntax.slash_comments && chars.peek() == Some(&'/') => skip_line(&mut chars),
+            '/' if syntax.slash_comments && chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => previous = c,
+                        None => return true,
+                    }
+                }
+            }
+            '[' | '{' | '(' => depth += 1,
+            ']' | '}' | ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth > 0
+}
+
+/// Consumes the rest of a multi-line string delimiter, returning whether there was one.
+fn take_triple<I>(chars: &mut Peekable<I>, quote: char) -> bool
+where
+    I: Iterator<Item = char> + Clone,
+{
+    let mut ahead = chars.clone();
+    if ahead.next() == Some(quote) && ahead.next() == Some(quote) {
+        chars.next();
+        chars.next();
+        return true;
+    }
+    false
+}
+
+/// Skips the rest of a string, returning `false` if the data ends before it is closed.
+///
+/// A line break ends single line strings, those are invalid but not cut short.
+fn skip_string<I>(chars: &mut Peekable<I>, quote: char, triple: bool, escapes: bool) -> bool
+where
+    I: Iterator<Item = char> + Clone,
+{
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if escapes => {
+                chars.next();
+            }
+            '\n' if !triple => return true,
+            c if c == quote && (!triple || take_triple(chars, quote)) => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Skips the rest of a comment line.
+fn skip_line<I: Iterator<Item = char>>(chars: &mut Peekable<I>) {
+    for c in chars.by_ref() {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::Value;
+
+    use crate::{deserialize, parse::ParseOptions, utils::FileType, Error};
+
+    fn read<T: serde::de::DeserializeOwned>(
+        file_type: FileType,
+        data: &str,
+        options: &ParseOptions,
+    ) -> Result<T, Error> {
+        deserialize("key", file_type, Path::new("key"), data, options)
+    }
+
+    #[test]
+    fn reports_json_cut_short_as_truncated() {
+        let options = ParseOptions::default();
+        let result = read::<Value>(FileType::Json, "{\"a\": [1, 2", &options);
+        assert!(matches!(result, Err(Error::Truncated { .. })));
+        let result = read::<Value>(FileType::Json, "{\"a\": 1}}", &options);
+        assert!(matches!(result, Err(Error::Json { .. })));
+    }
+
+    #[test]
+    fn ignores_brackets_in_json_comments() {
+        let mut options = ParseOptions::default();
+        options.jsonc = true;
+        let value = read::<Value>(FileType::Json, "// {\n{\"a\": 1}", &options).unwrap();
+        assert_eq!(value, serde_json::json!({ "a": 1 }));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn reports_invalid_toml_as_parse_error() {
+        let options = ParseOptions::default();
+        let result = read::<toml::Table>(FileType::Toml, "a = 1\nb = = 2", &options);
+        assert!(matches!(result, Err(Error::TomlDeserialize { .. })));
+        let result = read::<toml::Table>(FileType::Toml, "a = [1,", &options);
+        assert!(matches!(result, Err(Error::Truncated { .. })));
+        let table = read::<toml::Table>(FileType::Toml, "", &options).unwrap();
+        assert!(table.is_empty());
+    }
+}
@@ -0,0 +1,324 @@
+use std::{fmt, marker::PhantomData, str::FromStr, time::Duration};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Decimal and binary byte units, with the number of bytes in each.
+const BYTE_UNITS: &[(&str, u64)] = &[
+    ("B", 1),
+    ("KB", 1_000),
+    ("MB", 1_000_000),
+    ("GB", 1_000_000_000),
+    ("TB", 1_000_000_000_000),
+    ("PB", 1_000_000_000_000_000),
+    ("KiB", 1 << 10),
+    ("MiB", 1 << 20),
+    ("GiB", 1 << 30),
+    ("TiB", 1 << 40),
+    ("PiB", 1 << 50),
+];
+
+/// Duration units from the largest to the smallest, with the nanoseconds in each.
+const DURATION_UNITS: &[(&str, u128)] = &[
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// The error returned when a human-readable value can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("'{input}' is not a valid {kind}: {reason}")]
+pub struct ParseValueError {
+    kind: &'static str,
+    input: String,
+    reason: String,
+}
+
+impl ParseValueError {
+    pub(crate) fn new(kind: &'static str, input: &str, reason: impl Into<String>) -> Self {
+        Self {
+            kind,
+            input: input.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// A number of bytes, written in human form such as `512MB` or `4GiB`.
+///
+/// Decimal (`KB`, `MB`, ...) and binary (`KiB`, `MiB`, ...) units are accepted in any case,
+/// with an optional space and fraction, as well as bare numbers of bytes. Sizes are written
+/// with the unit giving the smallest whole number.
+///
+/// ```
+/// use libset::ByteSize;
+///
+/// let size: ByteSize = "1.5 GB".parse().unwrap();
+/// assert_eq!(size, ByteSize(1_500_000_000));
+/// assert_eq!(size.to_string(), "1500MB");
+/// assert_eq!(ByteSize(4 << 30).to_string(), "4GiB");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    /// Returns the number of bytes.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ByteSize> for u64 {
+    fn from(size: ByteSize) -> Self {
+        size.0
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (unit, count) = BYTE_UNITS
+            .iter()
+            .filter(|(_, bytes)| self.0.is_multiple_of(*bytes))
+            .map(|(unit, bytes)| (unit, self.0 / bytes))
+            .min_by_key(|(_, count)| *count)
+            .unwrap_or((&"B", self.0));
+        write!(f, "{count}{unit}")
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = ParseValueError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let failed = |reason: &str| ParseValueError::new("size", input, reason);
+        let (number, unit) = split_number(input.trim());
+        let unit = unit.trim();
+        let (_, bytes) = BYTE_UNITS
+            .iter()
+            .find(|(name, _)| unit.is_empty() || name.eq_ignore_ascii_case(unit))
+            .ok_or_else(|| failed("unknown unit, use B, KB, MB, GB, TB, PB or KiB to PiB"))?;
+        let bytes = scale(number, *bytes as u128).ok_or_else(|| failed("expected a number"))?;
+        u64::try_from(bytes)
+            .map(Self)
+            .map_err(|_| failed("too large"))
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(HumanVisitor(PhantomData))
+    }
+}
+
+/// A duration, written in human form such as `30s`, `250ms` or `1h30m`.
+///
+/// The units `d`, `h`, `m` (or `min`), `s`, `ms`, `us` (or `µs`) and `ns` can be combined and
+/// take fractions, while bare numbers are read as seconds. Durations are written with the
+/// largest units first.
+///
+/// ```
+/// use std::time::Duration;
+/// use libset::HumanDuration;
+///
+/// let timeout: HumanDuration = "1h 30m".parse().unwrap();
+/// assert_eq!(Duration::from(timeout), Duration::from_secs(5400));
+/// assert_eq!(timeout.to_string(), "1h30m");
+/// assert_eq!("1.5s".parse::<HumanDuration>().unwrap().to_string(), "1s500ms");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanDuration(pub Duration);
+
+impl HumanDuration {
+    /// Returns the duration.
+    pub fn as_duration(self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<HumanDuration> for Duration {
+    fn from(duration: HumanDuration) -> Self {
+        duration.0
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.0.as_nanos();
+        if rest == 0 {
+            return f.write_str("0s");
+        }
+        for (unit, nanos) in DURATION_UNITS {
+            if rest >= *nanos {
+                write!(f, "{}{unit}", rest / nanos)?;
+                rest %= nanos;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = ParseValueError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let failed = |reason: &str| ParseValueError::new("duration", input, reason);
+        let mut rest = input.trim();
+        if rest.is_empty() {
+            return Err(failed("expected a number followed by a unit, such as 30s"));
+        }
+        let mut total = 0u128;
+        while !rest.is_empty() {
+            let (number, tail) = split_number(rest);
+            let tail = tail.trim_start();
+            let end = tail
+                .find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+                .unwrap_or(tail.len());
+            let nanos = match &tail[..end] {
+                "" if number == input.trim() => 1_000_000_000,
+                "min" => 60_000_000_000,
+                "µs" => 1_000,
+                unit => DURATION_UNITS
+                    .iter()
+                    .find(|(name, _)| *name == unit)
+                    .map(|(_, nanos)| *nanos)
+                    .ok_or_else(|| failed("unknown unit, use d, h, m, s, ms, us or ns"))?,
+            };
+            let nanos = scale(number, nanos).ok_or_else(|| failed("expected a number"))?;
+            total = total
+                .checked_add(nanos)
+                .ok_or_else(|| failed("too large"))?;
+            rest = tail[end..].trim_start();
+        }
+        let secs = u64::try_from(total / 1_000_000_000).map_err(|_| failed("too large"))?;
+        Ok(Self(Duration::new(secs, (total % 1_000_000_000) as u32)))
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(HumanVisitor(PhantomData))
+    }
+}
+
+/// Values written in human form or as a bare number of their base unit.
+trait Human: Sized {
+    /// What the value looks like, for error messages.
+    const EXPECTING: &'static str;
+
+    fn parse(text: &str) -> Result<Self, ParseValueError>;
+
+    fn from_number(number: u64) -> Self;
+}
+
+impl Human for ByteSize {
+    const EXPECTING: &'static str = "a size such as \"512MB\" or a number of bytes";
+
+    fn parse(text: &str) -> Result<Self, ParseValueError> {
+        text.parse()
+    }
+
+    fn from_number(number: u64) -> Self {
+        Self(number)
+    }
+}
+
+impl Human for HumanDuration {
+    const EXPECTING: &'static str = "a duration such as \"30s\" or a number of seconds";
+
+    fn parse(text: &str) -> Result<Self, ParseValueError> {
+        text.parse()
+    }
+
+    fn from_number(number: u64) -> Self {
+        Self(Duration::from_secs(number))
+    }
+}
+
+/// Deserializes a [`Human`] value from a string or a non-negative integer.
+struct HumanVisitor<T>(PhantomData<T>);
+
+impl<T: Human> Visitor<'_> for HumanVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(T::EXPECTING)
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<T, E> {
+        T::parse(text).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, number: u64) -> Result<T, E> {
+        Ok(T::from_number(number))
+    }
+
+    fn visit_i64<E: de::Error>(self, number: i64) -> Result<T, E> {
+        u64::try_from(number)
+            .map(T::from_number)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(number), &self))
+    }
+}
+
+/// Splits text into its leading number and the rest.
+fn split_number(text: &str) -> (&str, &str) {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    text.split_at(end)
+}
+
+/// Multiplies a decimal number by a unit, dropping anything smaller than the unit's base.
+///
+/// # Returns
+///
+/// The product, or `None` if the number is empty, malformed or the product overflows.
+fn scale(number: &str, unit: u128) -> Option<u128> {
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() || fraction.contains('.') {
+        return None;
+    }
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    // Digits beyond the 18th can't change the result for any unit.
+    let fraction = &fraction[..fraction.len().min(18)];
+    let numerator: u128 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse().ok()?
+    };
+    let fraction = numerator * unit / 10u128.pow(fraction.len() as u32);
+    whole.checked_mul(unit)?.checked_add(fraction)
+}
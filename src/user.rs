@@ -0,0 +1,91 @@
+use std::{
+    os::unix::fs::{chown, PermissionsExt},
+    path::Path,
+    sync::Arc,
+};
+
+use tracing::warn;
+
+use crate::{Config, Error, EventKind};
+
+/// The user database read to resolve user names and groups.
+const PASSWD: &str = "/etc/passwd";
+
+/// The directory holding the subtree of every user, relative to the version directory.
+const USERS_DIR: &str = "users";
+
+/// A user as listed in the user database.
+struct User {
+    uid: u32,
+    gid: u32,
+}
+
+/// Looks up a user by name or numeric id in the user database.
+fn lookup(user: &str) -> Result<User, Error> {
+    let passwd =
+        std::fs::read_to_string(PASSWD).map_err(|err| Error::io(Path::new(PASSWD), err))?;
+    let uid = user.parse::<u32>().ok();
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            let gid = fields.next()?.parse().ok()?;
+            Some((name, User { uid, gid }))
+        })
+        .find(|(name, entry)| *name == user || Some(entry.uid) == uid)
+        .map(|(_, entry)| entry)
+        .ok_or_else(|| Error::UnknownUser(user.to_string()))
+}
+
+/// Hands a file or directory over to a user and restricts it to them.
+fn own(path: &Path, user: &User, mode: u32) -> std::io::Result<()> {
+    chown(path, Some(user.uid), Some(user.gid))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+impl Config {
+    /// Moves the configuration into the subtree of a user, for services managing per-user
+    /// data while running as root.
+    ///
+    /// Files are stored in `users/<uid>` inside the version directory, with reads falling back
+    /// to the shared settings, see [`Config::scoped_with_fallback`]. The subtree and every file
+    /// written to it are owned by the user and only accessible by them.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let system = Config::new("org.example.Daemon", 1, None)?;
+    /// let alice = Config::new("org.example.Daemon", 1, None)?.for_user("alice")?;
+    /// alice.set_json("quota", 10_000)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The name or numeric id of the user.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated `Config` object, `Error::UnknownUser` if the user
+    /// isn't listed in `/etc/passwd`, or an `Error` if the subtree could not be created or
+    /// handed over to the user, usually because the process isn't running as root.
+    pub fn for_user(self, user: &str) -> Result<Self, Error> {
+        let user = lookup(user)?;
+        let mut config = self.scoped_with_fallback(&format!("{USERS_DIR}/{}", user.uid))?;
+        let dir = config.path.clone();
+        own(&dir, &user, 0o700).map_err(|err| Error::io(&dir, err))?;
+        config.hooks.push(Arc::new(move |event| {
+            if event.kind != EventKind::AfterWrite {
+                return;
+            }
+            if let Err(err) = own(event.path, &user, 0o600) {
+                warn!(path = %event.path.display(), error = %err, "failed to hand a file over to its user");
+            }
+        }));
+        config.restart_flusher();
+        Ok(config)
+    }
+}
@@ -1,10 +1,16 @@
-use std::{fmt::Display, path::Path};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
 use tracing::error;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use zeroize::Zeroize;
 
-use crate::Error;
+use crate::{filesystem::Filesystem, Error};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FileType {
     Plain,
     #[cfg(feature = "toml")]
@@ -13,6 +19,12 @@ pub enum FileType {
     Json,
     #[cfg(feature = "ron")]
     Ron,
+    #[cfg(feature = "yaml")]
+    Yaml,
+    #[cfg(feature = "ini")]
+    Ini,
+    #[cfg(feature = "json5")]
+    Json5,
 }
 
 impl Display for FileType {
@@ -24,12 +36,53 @@ impl Display for FileType {
             FileType::Json => write!(f, "json"),
             #[cfg(feature = "ron")]
             FileType::Ron => write!(f, "ron"),
+            #[cfg(feature = "yaml")]
+            FileType::Yaml => write!(f, "yaml"),
+            #[cfg(feature = "ini")]
+            FileType::Ini => write!(f, "ini"),
+            #[cfg(feature = "json5")]
+            FileType::Json5 => write!(f, "json5"),
             FileType::Plain => write!(f, ""),
         }
     }
 }
 
-/// Check that the name is relative.
+impl FileType {
+    /// Returns the file type matching an extension, if its feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - The file extension, without the leading dot.
+    ///
+    /// # Returns
+    ///
+    /// The matching `FileType`, or `None` if the extension is not a known format.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            #[cfg(feature = "toml")]
+            "toml" => Some(FileType::Toml),
+            #[cfg(feature = "json")]
+            "json" => Some(FileType::Json),
+            #[cfg(feature = "ron")]
+            "ron" => Some(FileType::Ron),
+            #[cfg(feature = "yaml")]
+            "yaml" => Some(FileType::Yaml),
+            #[cfg(feature = "ini")]
+            "ini" => Some(FileType::Ini),
+            #[cfg(feature = "json5")]
+            "json5" => Some(FileType::Json5),
+            _ => None,
+        }
+    }
+}
+
+/// Device names reserved by Windows, regardless of extension.
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Check that the name is relative and does not use a Windows reserved device name.
 ///
 /// # Arguments
 ///
@@ -41,14 +94,207 @@ impl Display for FileType {
 /// otherwise returns an `Error` indicating the invalid name.
 pub(crate) fn sanitize_name(name: &str) -> Result<&Path, Error> {
     let path = Path::new(name);
-    if path
+    let error = if !path
         .components()
         .all(|x| matches!(x, std::path::Component::Normal(_)))
     {
-        Ok(path)
+        Error::InvalidName(name.to_owned())
+    } else if path
+        .components()
+        .any(|x| is_reserved_name(&x.as_os_str().to_string_lossy()))
+    {
+        Error::ReservedName(name.to_owned())
     } else {
-        let error = Error::InvalidName(name.to_owned());
+        return Ok(path);
+    };
+    error!("{}", error.to_string());
+    Err(error)
+}
+
+/// Check whether a path component is a Windows reserved device name such as `con` or `com1`.
+///
+/// # Arguments
+///
+/// * `component` - A single path component.
+///
+/// # Returns
+///
+/// Returns `true` if Windows would treat the component as a device.
+fn is_reserved_name(component: &str) -> bool {
+    let stem = component.split('.').next().unwrap_or_default().trim_end();
+    WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+}
+
+/// Decode the raw contents of a file into a string.
+///
+/// # Arguments
+///
+/// * `key` - The key the contents belong to, used for error reporting.
+/// * `path` - The path the contents were read from, used for error reporting.
+/// * `bytes` - The raw file contents.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the decoded text. UTF-8 byte order marks are stripped and
+/// UTF-16 files starting with a byte order mark are converted to UTF-8, otherwise returns an
+/// `Error` indicating why the contents could not be decoded. Text without a UTF-16 byte order
+/// mark must be UTF-8 without NUL bytes. The raw contents are converted
+/// in place or wiped, so no copy of the text is left behind.
+pub(crate) fn decode(key: &str, path: &Path, mut bytes: Vec<u8>) -> Result<String, Error> {
+    const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+    let encoding_error = |reason: &str| {
+        let error = Error::Encoding {
+            key: key.to_owned(),
+            path: path.to_path_buf(),
+            reason: reason.to_owned(),
+        };
         error!("{}", error.to_string());
-        Err(error)
+        error
+    };
+
+    let utf16 = match bytes.as_slice() {
+        [0xFF, 0xFE, ..] => Some(false),
+        [0xFE, 0xFF, ..] => Some(true),
+        _ => None,
+    };
+
+    match utf16 {
+        Some(big_endian) => {
+            let body = &bytes[2..];
+            if !body.len().is_multiple_of(2) {
+                bytes.zeroize();
+                return Err(encoding_error("truncated UTF-16 data"));
+            }
+            let mut units: Vec<u16> = body
+                .chunks_exact(2)
+                .map(|pair| {
+                    if big_endian {
+                        u16::from_be_bytes([pair[0], pair[1]])
+                    } else {
+                        u16::from_le_bytes([pair[0], pair[1]])
+                    }
+                })
+                .collect();
+            bytes.zeroize();
+            // Every unit takes at most three bytes in UTF-8, so the text never reallocates.
+            let mut text = String::with_capacity(units.len() * 3);
+            let decoded =
+                char::decode_utf16(units.iter().copied()).try_for_each(|c| c.map(|c| text.push(c)));
+            units.zeroize();
+            match decoded {
+                Ok(()) => Ok(text),
+                Err(_) => {
+                    text.zeroize();
+                    Err(encoding_error("invalid UTF-16 data"))
+                }
+            }
+        }
+        None if bytes.contains(&0) => {
+            bytes.zeroize();
+            Err(encoding_error(
+                "contains NUL bytes, UTF-16 data needs a byte order mark",
+            ))
+        }
+        None => {
+            if bytes.starts_with(&UTF8_BOM) {
+                bytes.drain(..UTF8_BOM.len());
+            }
+            String::from_utf8(bytes).map_err(|err| {
+                err.into_bytes().zeroize();
+                encoding_error("invalid UTF-8 data")
+            })
+        }
+    }
+}
+
+/// Normalize a key name according to the configured key policy.
+///
+/// # Arguments
+///
+/// * `key` - The key to be normalized.
+/// * `nfc` - Whether to apply Unicode NFC normalization.
+/// * `fold_case` - Whether to fold the key to lowercase.
+///
+/// # Returns
+///
+/// Returns the normalized key, borrowing the original when no change was needed.
+pub(crate) fn normalize_key(key: &str, nfc: bool, fold_case: bool) -> Cow<'_, str> {
+    let mut key = Cow::Borrowed(key);
+    if nfc && !is_nfc(&key) {
+        key = Cow::Owned(key.nfc().collect());
+    }
+    if fold_case && key.chars().any(char::is_uppercase) {
+        key = Cow::Owned(key.to_lowercase());
+    }
+    key
+}
+
+/// Find an existing file matching the given path regardless of case.
+///
+/// # Arguments
+///
+/// * `fs` - The filesystem the file is stored in.
+/// * `path` - The path to look up.
+///
+/// # Returns
+///
+/// Returns the path of an existing file in the same directory whose name matches
+/// case-insensitively, or the original path if there is none.
+pub(crate) fn find_case_insensitive(fs: &dyn Filesystem, path: PathBuf) -> PathBuf {
+    if fs.exists(&path) {
+        return path;
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return path;
+    };
+    let name = name.to_string_lossy().to_lowercase();
+    let Ok(files) = fs.list(parent) else {
+        return path;
+    };
+    files
+        .into_iter()
+        .find(|file| {
+            file.file_name()
+                .is_some_and(|file| file.to_string_lossy().to_lowercase() == name)
+        })
+        .unwrap_or(path)
+}
+
+/// Formats bytes as lowercase hexadecimal.
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::decode;
+
+    fn decoded(bytes: &[u8]) -> Option<String> {
+        decode("key", Path::new("key"), bytes.to_vec()).ok()
+    }
+
+    #[test]
+    fn decodes_text_with_byte_order_marks() {
+        assert_eq!(decoded(b"\xEF\xBB\xBFtheme").as_deref(), Some("theme"));
+        let utf16: Vec<u8> = [0xFEFF, 0x74, 0xD83C, 0xDFA8]
+            .iter()
+            .flat_map(|unit: &u16| unit.to_le_bytes())
+            .collect();
+        assert_eq!(decoded(&utf16).as_deref(), Some("t\u{1F3A8}"));
+        assert_eq!(decoded(b"\xFF\xFE\x00\xD8"), None);
+        assert_eq!(decoded(b"\xC3"), None);
+    }
+
+    #[test]
+    fn rejects_utf16_without_byte_order_mark() {
+        assert_eq!(decoded(b"t\0h\0e\0m\0e\0"), None);
+        assert_eq!(decoded(b"\0t\0h\0e\0m\0e"), None);
+        assert_eq!(decoded(b"theme\0"), None);
+        assert_eq!(decoded(b"theme").as_deref(), Some("theme"));
     }
 }
@@ -0,0 +1,157 @@
+use std::path::{Component, Path};
+
+use tracing::error;
+
+use crate::{entry::ATOMIC_WRITE_PREFIX, utils::sanitize_name, Error};
+
+/// Characters that are not allowed in file names on Windows.
+const WINDOWS_RESERVED_CHARS: [char; 8] = ['<', '>', ':', '"', '\\', '|', '?', '*'];
+
+/// Configurable validation rules applied to key names.
+///
+/// The default validator only rejects names escaping the configuration directory and reserved
+/// names, nested keys such as `apps/editor` are accepted. Enable [`KeyValidator::portable`] to
+/// only accept names that also work on Windows.
+///
+/// Names starting with `.atomicwrite` are always rejected, they are reserved for the temporary
+/// files and backups libset creates and removes on its own.
+///
+/// ```
+/// use libset::KeyValidator;
+///
+/// assert!(KeyValidator::default().validate("col:ors.json").is_ok());
+///
+/// let validator = KeyValidator::default()
+///     .portable(true)
+///     .max_length(Some(32))
+///     .ascii_only(true);
+/// assert!(validator.validate("apps/colors.json").is_ok());
+/// assert!(validator.validate("col:ors.json").is_err());
+/// assert!(validator.validate("con.json").is_err());
+/// assert!(validator.validate(".atomicwrite.notes.1.0.tmp").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyValidator {
+    max_length: Option<usize>,
+    portable: bool,
+    ascii_only: bool,
+    allow_whitespace: bool,
+    reject_trailing_dots_and_spaces: bool,
+}
+
+impl Default for KeyValidator {
+    fn default() -> Self {
+        Self::permissive()
+    }
+}
+
+impl KeyValidator {
+    /// Creates a validator that only rejects names escaping the configuration directory and
+    /// reserved names, like the default one.
+    ///
+    /// # Returns
+    ///
+    /// A `KeyValidator` with every optional check disabled.
+    pub fn permissive() -> Self {
+        Self {
+            max_length: None,
+            portable: false,
+            ascii_only: false,
+            allow_whitespace: true,
+            reject_trailing_dots_and_spaces: false,
+        }
+    }
+
+    /// Sets the maximum length in bytes of each path component, `None` disables the check.
+    pub fn max_length(mut self, max_length: Option<usize>) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Rejects characters that are invalid on Windows, control characters and path components
+    /// ending with a dot or a space.
+    pub fn portable(mut self, portable: bool) -> Self {
+        self.portable = portable;
+        self
+    }
+
+    /// Restricts names to ASCII characters.
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Allows or rejects whitespace characters in names.
+    pub fn allow_whitespace(mut self, allow_whitespace: bool) -> Self {
+        self.allow_whitespace = allow_whitespace;
+        self
+    }
+
+    /// Rejects path components ending with a dot or a space.
+    pub fn reject_trailing_dots_and_spaces(mut self, reject: bool) -> Self {
+        self.reject_trailing_dots_and_spaces = reject;
+        self
+    }
+
+    /// Validates a name against the configured rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to be validated.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the name as a relative `Path` if it is valid,
+    /// otherwise returns an `Error` describing the violated rule.
+    pub fn validate<'a>(&self, name: &'a str) -> Result<&'a Path, Error> {
+        let path = sanitize_name(name)?;
+        for component in path.components() {
+            let Component::Normal(component) = component else {
+                continue;
+            };
+            let component = component.to_string_lossy();
+            if let Some(reason) = self.check_component(&component) {
+                let error = Error::InvalidKey {
+                    key: name.to_owned(),
+                    reason,
+                };
+                error!("{}", error.to_string());
+                return Err(error);
+            }
+        }
+        Ok(path)
+    }
+
+    fn check_component(&self, component: &str) -> Option<String> {
+        if component.starts_with(ATOMIC_WRITE_PREFIX) {
+            return Some(format!(
+                "starts with '{ATOMIC_WRITE_PREFIX}' which is reserved"
+            ));
+        }
+        if let Some(max_length) = self.max_length {
+            if component.len() > max_length {
+                return Some(format!("exceeds the maximum length of {max_length} bytes"));
+            }
+        }
+        if (self.portable || self.reject_trailing_dots_and_spaces)
+            && (component.ends_with('.') || component.ends_with(' '))
+        {
+            return Some("ends with a dot or a space".to_string());
+        }
+        component.chars().find_map(|c| {
+            if self.portable && c.is_control() {
+                Some(format!("contains the control character {c:?}"))
+            } else if self.portable && WINDOWS_RESERVED_CHARS.contains(&c) {
+                Some(format!(
+                    "contains the character '{c}' which is invalid on Windows"
+                ))
+            } else if self.ascii_only && !c.is_ascii() {
+                Some(format!("contains the non-ASCII character '{c}'"))
+            } else if !self.allow_whitespace && c.is_whitespace() {
+                Some("contains whitespace".to_string())
+            } else {
+                None
+            }
+        })
+    }
+}
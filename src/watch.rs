@@ -0,0 +1,250 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::SystemTime,
+};
+
+use notify::{event::ModifyKind, EventKind, RecursiveMode, Watcher as _};
+use tracing::error;
+
+use crate::{
+    entry::{is_temporary, scope_of, split_name},
+    utils::{sanitize_name, FileType},
+    Config, Error,
+};
+
+/// The kind of change detected on a stored file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// The file was created.
+    Created,
+    /// The contents or metadata of the file changed.
+    Modified,
+    /// The file was removed.
+    Removed,
+}
+
+/// Represents a change to a stored file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// The key of the changed file.
+    pub key: String,
+    /// The scope of the changed file, relative to the version root.
+    pub scope: Option<PathBuf>,
+    /// The format of the changed file.
+    pub file_type: FileType,
+    /// What happened to the file.
+    pub kind: ChangeKind,
+    /// The path of the changed file.
+    pub path: PathBuf,
+}
+
+/// Handle to an active watch, changes stop being reported once it is dropped.
+pub struct Watcher {
+    _inner: notify::RecommendedWatcher,
+}
+
+/// The kind of a raw filesystem event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawEventKind {
+    /// A file or directory was created.
+    Created,
+    /// The contents or metadata of a file or directory changed.
+    Modified,
+    /// A file or directory was renamed, reported for the old and the new path when known.
+    Renamed,
+    /// A file or directory was removed.
+    Removed,
+    /// A file or directory was opened or read.
+    Accessed,
+    /// Any other event reported by the operating system.
+    Other,
+}
+
+/// An event on any path under the store, as reported by the operating system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEvent {
+    /// The path the event happened on.
+    pub path: PathBuf,
+    /// What happened to the path.
+    pub kind: RawEventKind,
+    /// When the event was received.
+    pub timestamp: SystemTime,
+}
+
+/// Stream of raw events returned by [`Config::raw_events`], events stop being reported once
+/// it is dropped.
+///
+/// Iterating blocks until the next event arrives.
+pub struct RawEvents {
+    _inner: notify::RecommendedWatcher,
+    events: Receiver<RawEvent>,
+}
+
+impl RawEvents {
+    /// Returns the next event if one is waiting, without blocking.
+    pub fn try_next(&mut self) -> Option<RawEvent> {
+        self.events.try_recv().ok()
+    }
+}
+
+impl Iterator for RawEvents {
+    type Item = RawEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.recv().ok()
+    }
+}
+
+impl Config {
+    /// Watches a scope, or the whole store, for changes.
+    ///
+    /// The callback runs on a background thread for every file created, modified or removed
+    /// anywhere under the watched directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `scope` - The scope to watch, `None` watches the whole version root.
+    /// * `callback` - Function invoked with each change.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Watcher` handle or an `Error` if an error occurred.
+    pub fn watch_scope<F>(&self, scope: Option<&str>, callback: F) -> Result<Watcher, Error>
+    where
+        F: Fn(Change) + Send + 'static,
+    {
+        let dir = match scope {
+            Some(scope) => self.root.join(sanitize_name(scope)?),
+            None => self.root.clone(),
+        };
+        std::fs::create_dir_all(&dir).map_err(|err| Error::io(&dir, err))?;
+
+        let root = self.root.clone();
+        let watch_error = |source| Error::Watch {
+            path: dir.clone(),
+            source,
+        };
+        let watched = dir.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) => {
+                    for path in &event.paths {
+                        if let Some(change) = to_change(&root, path, event.kind) {
+                            callback(change);
+                        }
+                    }
+                }
+                Err(source) => {
+                    let error = Error::Watch {
+                        path: watched.clone(),
+                        source,
+                    };
+                    error!("{}", error.to_string())
+                }
+            })
+            .map_err(watch_error)?;
+        watcher
+            .watch(&dir, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+        Ok(Watcher { _inner: watcher })
+    }
+
+    /// Streams every filesystem event under the store, without interpreting it.
+    ///
+    /// Unlike [`Config::watch_scope`], events are reported for directories, temporary files,
+    /// backups and lock files too, and are not mapped to keys. Use it to drive your own
+    /// caching or merging logic.
+    ///
+    /// ```no_run
+    /// # use libset::Config;
+    /// # fn main() -> Result<(), libset::Error> {
+    /// let config = Config::new("org.example.Demo", 1, None)?;
+    /// for event in config.raw_events()? {
+    ///     println!("{:?} {}", event.kind, event.path.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the event stream or an `Error` if an error occurred.
+    pub fn raw_events(&self) -> Result<RawEvents, Error> {
+        let dir = self.root.clone();
+        std::fs::create_dir_all(&dir).map_err(|err| Error::io(&dir, err))?;
+
+        let watch_error = |source| Error::Watch {
+            path: dir.clone(),
+            source,
+        };
+        let watched = dir.clone();
+        let (sender, events) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) => {
+                    let timestamp = SystemTime::now();
+                    let kind = to_raw_kind(event.kind);
+                    for path in event.paths {
+                        let _ = sender.send(RawEvent {
+                            path,
+                            kind,
+                            timestamp,
+                        });
+                    }
+                }
+                Err(source) => {
+                    let error = Error::Watch {
+                        path: watched.clone(),
+                        source,
+                    };
+                    error!("{}", error.to_string())
+                }
+            })
+            .map_err(watch_error)?;
+        watcher
+            .watch(&dir, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+        Ok(RawEvents {
+            _inner: watcher,
+            events,
+        })
+    }
+}
+
+/// Maps a filesystem event kind onto a `RawEventKind`.
+fn to_raw_kind(kind: EventKind) -> RawEventKind {
+    match kind {
+        EventKind::Create(_) => RawEventKind::Created,
+        EventKind::Modify(ModifyKind::Name(_)) => RawEventKind::Renamed,
+        EventKind::Modify(_) => RawEventKind::Modified,
+        EventKind::Remove(_) => RawEventKind::Removed,
+        EventKind::Access(_) => RawEventKind::Accessed,
+        EventKind::Any | EventKind::Other => RawEventKind::Other,
+    }
+}
+
+/// Converts a filesystem event on a path into a `Change`, ignoring temporary files.
+fn to_change(root: &Path, path: &Path, kind: EventKind) -> Option<Change> {
+    if is_temporary(path.strip_prefix(root).unwrap_or(path)) {
+        return None;
+    }
+    let kind = match kind {
+        EventKind::Create(_) => ChangeKind::Created,
+        EventKind::Remove(_) => ChangeKind::Removed,
+        EventKind::Modify(_) if path.exists() => ChangeKind::Modified,
+        EventKind::Modify(_) => ChangeKind::Removed,
+        _ => return None,
+    };
+    if path.is_dir() {
+        return None;
+    }
+    let (key, file_type) = split_name(path.file_name()?.to_str()?);
+    Some(Change {
+        key,
+        scope: scope_of(root, path),
+        file_type,
+        kind,
+        path: path.to_path_buf(),
+    })
+}